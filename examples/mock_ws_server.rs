@@ -0,0 +1,70 @@
+//! Local WebSocket feed for trying `quant_marketstat_ws stream`.
+//!
+//! Serves the ticks of a CSV file as JSON messages in the default stream schema,
+//! one connection at a time, then closes the connection:
+//!
+//! ```text
+//! cargo run --example mock_ws_server -- data.csv 127.0.0.1:9001 50
+//! quant_marketstat_ws stream --url ws://127.0.0.1:9001 --every 2
+//! ```
+//!
+//! Arguments: CSV file (default `data.csv`), listen address (default `127.0.0.1:9001`)
+//! and delay between messages in ms (default `0`).
+
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use quant_marketstat_ws::csv_loader::read_ticks;
+use quant_marketstat_ws::tick::format_timestamp;
+use serde_json::json;
+use tungstenite::Message;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = args.first().map(String::as_str).unwrap_or("data.csv");
+    let addr = args.get(1).map(String::as_str).unwrap_or("127.0.0.1:9001");
+    let delay = Duration::from_millis(args.get(2).and_then(|d| d.parse().ok()).unwrap_or(0));
+
+    let ticks = read_ticks(path).unwrap_or_else(|err| {
+        eprintln!("Error: {} ({})", err, path);
+        std::process::exit(1);
+    });
+    let listener = TcpListener::bind(addr).unwrap_or_else(|err| {
+        eprintln!("Error: cannot listen on {}: {}", addr, err);
+        std::process::exit(1);
+    });
+    println!("Serving {} ticks from {} on ws://{}", ticks.len(), path, addr);
+
+    for stream in listener.incoming().flatten() {
+        let mut socket = match tungstenite::accept(stream) {
+            Ok(socket) => socket,
+            Err(err) => {
+                eprintln!("Handshake failed: {}", err);
+                continue;
+            }
+        };
+
+        // A non-tick message first, as real feeds send acks and heartbeats
+        let _ = socket.send(Message::Text(json!({ "event": "subscribed" }).to_string()));
+
+        for tick in &ticks {
+            let message = json!({
+                "timestamp": tick.timestamp.map(format_timestamp),
+                "symbol": tick.symbol,
+                "bid": tick.bid,
+                "ask": tick.ask,
+                "volume": tick.volume,
+            });
+            if socket.send(Message::Text(message.to_string())).is_err() {
+                break;
+            }
+            thread::sleep(delay);
+        }
+
+        let _ = socket.close(None);
+        // Drain until the client acknowledges the close
+        while socket.read().is_ok() {}
+        println!("Feed sent, waiting for the next connection");
+    }
+}
@@ -1,34 +1,29 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
-use csv::ReaderBuilder;
 
+use crate::csv_loader::read_ticks;
 use crate::stats;
+use crate::tick::{self, Tick};
 
-pub fn analyze_csv(path: &str) -> Result<(), Box<dyn Error>> {
-    let file = File::open(path)?;
-    let mut rdr = ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(BufReader::new(file));
+/// Loads ticks from a CSV file (see [`read_ticks`]) and prints the global summary.
+/// When the file has several symbols, one summary is printed per symbol.
+/// Returns the loaded ticks so they can be reused (e.g. kept in the menu session).
+pub fn analyze_csv(path: &str) -> Result<Vec<Tick>, Box<dyn Error>> {
+    let ticks = read_ticks(path)?;
 
-    let mut bids = Vec::new();
-    let mut asks = Vec::new();
-    let mut volumes = Vec::new();
-
-    for result in rdr.records() {
-        let record = result?;
-
-        let bid: f64 = record.get(0).unwrap_or("0").trim().parse()?;
-        let ask: f64 = record.get(1).unwrap_or("0").trim().parse()?;
-        let vol: f64 = record.get(2).unwrap_or("0").trim().parse()?;
+    println!("\n✅ Successfully loaded data from CSV. Running global stats analysis...");
 
-        bids.push(bid);
-        asks.push(ask);
-        volumes.push(vol);
+    let groups = tick::by_symbol(&ticks);
+    if groups.len() > 1 {
+        for (symbol, symbol_ticks) in &groups {
+            let name = if symbol.is_empty() { "(no symbol)" } else { symbol };
+            println!("\n=== {} ({} rows) ===", name, symbol_ticks.len());
+            let (bids, asks, volumes) = tick::columns(symbol_ticks);
+            stats::global_stats_summary(&bids, &asks, &volumes);
+        }
+    } else {
+        let (bids, asks, volumes) = tick::columns(&ticks);
+        stats::global_stats_summary(&bids, &asks, &volumes);
     }
 
-    println!("\n✅ Successfully loaded data from CSV. Running global stats analysis...");
-    stats::global_stats_summary(&bids, &asks, &volumes);
-
-    Ok(())
+    Ok(ticks)
 }
@@ -0,0 +1,122 @@
+//! Top-of-book imbalance and microprice.
+//!
+//! The mid `(bid + ask) / 2` ignores how much is quoted on each side. When the
+//! best bid has far more size than the best ask, the next move is more likely
+//! up, and the fair price sits closer to the ask. The order book imbalance
+//! measures that pressure and the microprice (the mid weighted by the size on
+//! the opposite side) prices it. Both need the `bid_size` and `ask_size` of
+//! the ticks.
+
+use crate::stats::StatsError;
+use crate::tick::{Frequency, Tick, Timestamp};
+
+/// Order book imbalance `(bid_size - ask_size) / (bid_size + ask_size)`, from -1 (only
+/// asks) to +1 (only bids). `None` when both sizes are zero.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::book::imbalance;
+///
+/// assert_eq!(imbalance(300.0, 100.0), Some(0.5));
+/// assert_eq!(imbalance(0.0, 0.0), None);
+/// ```
+pub fn imbalance(bid_size: f64, ask_size: f64) -> Option<f64> {
+    let total = bid_size + ask_size;
+    (total > 0.0).then(|| (bid_size - ask_size) / total)
+}
+
+/// Size-weighted microprice `(bid × ask_size + ask × bid_size) / (bid_size + ask_size)`:
+/// closer to the ask when the bid side is heavier. The mid when both sizes are zero.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::book::microprice;
+///
+/// // Three times more size on the bid: three quarters of the way to the ask
+/// assert!((microprice(10.0, 10.04, 300.0, 100.0) - 10.03).abs() < 1e-9);
+/// assert_eq!(microprice(10.0, 10.04, 100.0, 100.0), 10.02);
+/// ```
+pub fn microprice(bid: f64, ask: f64, bid_size: f64, ask_size: f64) -> f64 {
+    let total = bid_size + ask_size;
+    if total > 0.0 {
+        (bid * ask_size + ask * bid_size) / total
+    } else {
+        (bid + ask) / 2.0
+    }
+}
+
+/// Imbalance and microprice at one point of a tick series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPoint {
+    pub timestamp: Option<Timestamp>,
+    pub mid: f64,
+    pub microprice: f64,
+    pub imbalance: Option<f64>,
+}
+
+/// Mid, microprice and imbalance per tick, or at the last tick of each `Frequency::Every`
+/// period. Ticks without `bid_size` and `ask_size` are skipped.
+///
+/// # Errors
+/// * `EmptyInput` - no tick has both sizes
+/// * `NaN` - a price or size is NaN
+/// * `MissingTimestamps`, `UnsortedTimestamps` - for `Frequency::Every`
+/// * `InvalidWindow` - a period of zero
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::book::book_series;
+/// use quant_marketstat_ws::tick::{Frequency, Tick};
+///
+/// let quote = |bid_size: f64, ask_size: f64| Tick {
+///     bid_size: Some(bid_size),
+///     ask_size: Some(ask_size),
+///     ..Tick::from_quote(10.0, 10.04, 0.0)
+/// };
+/// let series = book_series(&[quote(100.0, 100.0), quote(100.0, 300.0)], Frequency::Tick).unwrap();
+/// assert_eq!(series[0].imbalance, Some(0.0));
+/// assert!((series[1].microprice - 10.01).abs() < 1e-9); // heavier ask pulls it down
+/// ```
+pub fn book_series(ticks: &[Tick], frequency: Frequency) -> Result<Vec<BookPoint>, StatsError> {
+    if let Frequency::Every(period) = frequency {
+        if period <= 0 {
+            return Err(StatsError::InvalidWindow);
+        }
+        if ticks.iter().any(|t| t.timestamp.is_none()) {
+            return Err(StatsError::MissingTimestamps);
+        }
+        if ticks.windows(2).any(|w| w[1].timestamp < w[0].timestamp) {
+            return Err(StatsError::UnsortedTimestamps);
+        }
+    }
+
+    let mut points: Vec<BookPoint> = Vec::new();
+    let mut open_period: Option<i64> = None;
+    for tick in ticks {
+        let (Some(bid_size), Some(ask_size)) = (tick.bid_size, tick.ask_size) else { continue };
+        if [tick.bid, tick.ask, bid_size, ask_size].iter().any(|v| v.is_nan()) {
+            return Err(StatsError::NaN);
+        }
+
+        let period = match frequency {
+            Frequency::Tick => None,
+            Frequency::Every(ms) => tick.timestamp.map(|ts| ts.div_euclid(ms)),
+        };
+        if period.is_some() && period == open_period {
+            points.pop();
+        }
+        open_period = period;
+
+        points.push(BookPoint {
+            timestamp: tick.timestamp,
+            mid: tick.mid(),
+            microprice: microprice(tick.bid, tick.ask, bid_size, ask_size),
+            imbalance: imbalance(bid_size, ask_size),
+        });
+    }
+
+    if points.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    Ok(points)
+}
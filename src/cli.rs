@@ -0,0 +1,1201 @@
+//! Non-interactive command line interface.
+//!
+//! Every menu option is also available as a subcommand so the engine can be
+//! driven from shell scripts or cron jobs:
+//!
+//! ```text
+//! quant_marketstat_ws vwap --file data.csv --column ask
+//! quant_marketstat_ws variance --values 10.5,10.6,10.4 --population
+//! quant_marketstat_ws pnl --positions trades.csv
+//! quant_marketstat_ws summary --file data.csv --output json
+//! quant_marketstat_ws stream --url ws://localhost:9001 --every 5s
+//! ```
+//!
+//! Exit codes: `0` success, `1` invalid data or I/O error, `2` usage error.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use quant_marketstat_ws::distribution;
+use quant_marketstat_ws::book;
+use quant_marketstat_ws::csv_loader::{self, ColumnMapping, CsvOptions, DecimalSeparator, HeaderMode, ParseMode};
+use quant_marketstat_ws::fees::FeeConfig;
+use quant_marketstat_ws::instruments::InstrumentRegistry;
+use quant_marketstat_ws::ledger::{CostMethod, Ledger, LedgerError};
+use quant_marketstat_ws::online::OnlineMarketStats;
+use quant_marketstat_ws::fx::{self, FxError, FxRates};
+use quant_marketstat_ws::mtm;
+use quant_marketstat_ws::performance::{self, PerformanceConfig};
+use quant_marketstat_ws::pnl::{calculate_pnl, AssetPosition, PnLResult};
+use quant_marketstat_ws::portfolio::{GroupBy, Portfolio};
+use quant_marketstat_ws::report::{self, Cell, OutputFormat};
+use quant_marketstat_ws::returns::{self, ReturnKind};
+use quant_marketstat_ws::risk::{self, VarMethod};
+use quant_marketstat_ws::rolling::{self, Window};
+use quant_marketstat_ws::spread::{self, SpreadValue};
+use quant_marketstat_ws::stats::{self, BookSizes, GroupPrice, StatsError};
+use quant_marketstat_ws::stream::{self, MessageSchema, StreamConfig};
+use quant_marketstat_ws::tick::{self, format_timestamp, parse_duration, Frequency, PriceField, Tick};
+use quant_marketstat_ws::volatility::{self, TradingCalendar};
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_DATA_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "\
+Usage: quant_marketstat_ws [COMMAND] [OPTIONS]
+
+Without a command the interactive menu is started.
+
+Commands:
+  mean                 --values LIST | --file FILE [--column bid|ask|mid|last] [--returns simple|log [--every DURATION]]
+  vwap                 --prices LIST --volumes LIST | --file FILE [--column bid|ask|mid|last]
+  vwap-group           --bids LIST --asks LIST --volumes LIST | --file FILE [--price mid|microprice]
+  variance             --values LIST | --file FILE [--column ...] [--returns ...] [--population]
+  std                  --values LIST | --file FILE [--column ...] [--returns ...] [--population]
+  distribution         --values LIST | --file FILE [--column ...] [--returns ...] [--quantiles LIST]
+                       | --prices LIST --volumes LIST | --file FILE --weighted
+                       [--output text|json|csv]
+                       min/max, quartiles, quantiles (default 0.01,0.05,0.95,0.99), skewness,
+                       excess kurtosis and Jarque-Bera; --weighted weights the prices by volume
+  spread               --file FILE [--quantiles LIST] [--horizons LIST] [--output text|json|csv]
+                       quoted spread (absolute and bps): average, time- and volume-weighted, percentiles
+                       (default 0.5,0.9,0.99); with trade prices (last column), effective spread and
+                       realized spread / price impact at each horizon, in ticks or durations
+                       (default 1m,5m, or 5,20 ticks without timestamps)
+  vwap-variance        --prices LIST --volumes LIST | --file FILE [--column ...] [--population]
+  vwap-std             --prices LIST --volumes LIST | --file FILE [--column ...] [--population]
+  vwap-group-variance  --bids LIST --asks LIST --volumes LIST | --file FILE [--price ...] [--population]
+  vwap-group-std       --bids LIST --asks LIST --volumes LIST | --file FILE [--price ...] [--population]
+  book                 --file FILE [--every tick|DURATION] [--output csv|json|text]
+                       mid, size-weighted microprice and order book imbalance per tick (or at the
+                       last tick of each period) of the rows of FILE with bid_size and ask_size
+  pnl                  --positions FILE [--currency CCY --fx RATES_FILE [--fx-base CCY]]
+                       [--by asset|class|tag [--output text|json|csv]] [--fees FEES_FILE]
+                       [--instruments INSTRUMENTS_FILE]
+                       with --currency, P&L converted and consolidated in that currency;
+                       with --by, portfolio totals and contribution to P&L per group
+  returns              --file FILE [--column bid|ask|mid|last] [--kind simple|log] [--every tick|DURATION]
+                       [--output csv|json|text]
+                       returns of the prices of FILE (default: simple returns of mid, tick to tick;
+                       --every 1s|1m|1h|1d uses the last price of each interval)
+  volatility           --file FILE [--column bid|ask|mid|last] [--every DURATION] [--calendar us|b3|fx|crypto|DAYS:HOURS]
+                       [--output text|json|csv]
+                       realized volatility estimators on OHLC bars of FILE (default: 5m bars of mid, US calendar)
+  var                  --file FILE [--column bid|ask|mid|last] [--every tick|DURATION] [--value AMOUNT]
+                       | --positions FILE --file FILE --every DURATION [--instruments INSTRUMENTS_FILE]
+                       [--confidence LIST] [--horizon N] [--method all|historical|gaussian|student-t[:DOF]|cornish-fisher]
+                       [--output text|json|csv]
+                       Value-at-Risk and Expected Shortfall in currency of a position worth AMOUNT
+                       (default: last price) or of the positions, from the returns of FILE
+                       (default: 95% and 99%, 1 period, every method)
+  performance          --equity LIST | --pnl LIST --capital AMOUNT
+                       | --positions FILE --file FILE --every DURATION --capital AMOUNT [--column ...]
+                       [--risk-free RATE] [--periods-per-year N | --calendar us|b3|fx|crypto|DAYS:HOURS]
+                       [--output text|json|csv]
+                       return, volatility, Sharpe, Sortino, max drawdown and its duration, Calmar,
+                       hit rate, profit factor and average win/loss of an equity curve
+                       (default: no risk-free rate, 252 periods a year or the --every periods of the calendar)
+  mtm                  --positions FILE --file FILE [--column bid|ask|mid|last] [--every tick|daily|DURATION]
+                       [--instruments INSTRUMENTS_FILE] [--fees FEES_FILE] [--output csv|json|text]
+                       mark-to-market P&L curve of the positions over the prices of FILE (default: mid, per tick)
+  ledger               --fills FILE [--method fifo|lifo|average] [--marks ASSET=PRICE,...] [--lots]
+                       [--allow-short] [--borrow-rate RATE] [--fees FEES_FILE] [--instruments INSTRUMENTS_FILE]
+                       [--output text|json|csv]
+                       realized P&L of matched lots and unrealized P&L of open positions
+  summary              --bids LIST --asks LIST --volumes LIST | --file FILE [--output text|json|csv] [--by-symbol]
+                       [--streaming] (one pass over FILE, constant memory)
+  rolling              --file FILE --window N|DURATION [--column bid|ask|mid|last] [--population] [--output csv|json|text]
+                       rolling VWAP and VWAP STD per tick (window: 20 ticks, or 30s, 5m, 1h)
+  stream               --url ws://HOST:PORT [--schema MAP] [--subscribe JSON] [--every N|DURATION]
+                       [--limit N] [--symbol SYMBOL] [--output text|json|csv]
+                       live global summary from a WebSocket feed of JSON messages
+  help                 Show this message
+
+LIST is a comma-separated list of numbers, e.g. 10.5,10.6,10.4
+FILE is a CSV with bid,ask,volume columns, or a header naming its columns
+(timestamp, symbol, bid, ask, bid_size, ask_size, last, volume).
+--symbol SYMBOL keeps only the rows of one instrument of FILE.
+
+FILE reading options:
+  --delimiter C          field delimiter (default ','; use ';' or tab for other exports)
+  --decimal dot|comma    decimal separator (comma: 10,5 and 1.234,56)
+  --header | --no-header force header detection (default: auto)
+  --columns MAP          map tick fields to columns by name or 0-based index,
+                         e.g. bid=Compra,ask=Venda,volume=3,timestamp=Hora
+  --lenient              skip invalid rows (reported on stderr) instead of failing
+--delimiter and --decimal also apply to the positions, fills, FX rates and instruments files.
+Stream options:
+  --schema MAP           JSON paths of the tick fields (default: bid,ask,volume,timestamp,symbol),
+                         e.g. bid=data.b,ask=data.a,volume=data.q or bid=/levels/0/bid
+  --every N|DURATION     report every N ticks or every duration, e.g. 5s (default: 100)
+  --limit N              stop after N ticks (default: until the server closes)
+FEES_FILE is a JSON fee schedule (see README); its fees replace the costs
+of the positions or fills it has a schedule for.
+INSTRUMENTS_FILE is a CSV of contract specs (symbol, asset_class, multiplier, tick_size,
+tick_value, currency); contract P&L is scaled by each symbol's multiplier.
+--column selects the price series taken from FILE (default: bid; mid with --returns).
+--price microprice prices each quote of the VWAP Group family at the size-weighted microprice
+instead of the mid, from the bid_size/ask_size columns of FILE or --bid-sizes/--ask-sizes LISTs.
+--returns simple|log computes mean, variance and std on returns of that series of FILE instead of
+price levels (resampled with --every, e.g. 1m).
+Variances are sample variances unless --population is given.";
+
+/// Error raised while running a subcommand, mapped onto the process exit code.
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad command line (unknown command, missing or malformed option)
+    Usage(String),
+    /// Input data could not be read or is not valid for the calculation
+    Data(String),
+}
+
+impl From<StatsError> for CliError {
+    fn from(err: StatsError) -> CliError {
+        CliError::Data(err.to_string())
+    }
+}
+
+impl From<FxError> for CliError {
+    fn from(err: FxError) -> CliError {
+        CliError::Data(err.to_string())
+    }
+}
+
+impl From<LedgerError> for CliError {
+    fn from(err: LedgerError) -> CliError {
+        CliError::Data(err.to_string())
+    }
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Data(_) => EXIT_DATA_ERROR,
+        }
+    }
+}
+
+/// Parsed `--key value` options and bare `--flag`s following the command name.
+struct Options {
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+/// Options that never take a value.
+const FLAGS: &[&str] = &["population", "weighted", "by-symbol", "streaming", "lots", "allow-short", "header", "no-header", "lenient", "help"];
+
+/// Options that take a value (`--key value` or `--key=value`).
+const VALUE_OPTIONS: &[&str] = &[
+    "values", "prices", "volumes", "bids", "asks", "bid-sizes", "ask-sizes", "file", "column", "price", "returns",
+    "kind", "every", "quantiles", "horizons", "output", "symbol", "positions", "fills", "currency", "fx", "fx-base",
+    "by", "fees", "instruments", "marks", "method", "borrow-rate", "confidence", "horizon", "value", "equity", "pnl",
+    "capital", "risk-free", "periods-per-year", "calendar", "window", "url", "schema", "subscribe", "limit",
+    "delimiter", "decimal", "columns",
+];
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, CliError> {
+        let mut values = HashMap::new();
+        let mut flags = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                return Err(CliError::Usage(format!("Unexpected argument '{}'", arg)));
+            };
+
+            if let Some((key, value)) = key.split_once('=') {
+                if FLAGS.contains(&key) {
+                    return Err(CliError::Usage(format!("Option --{} does not take a value", key)));
+                }
+                if !VALUE_OPTIONS.contains(&key) {
+                    return Err(CliError::Usage(format!("Unknown option --{}", key)));
+                }
+                values.insert(key.to_string(), value.to_string());
+            } else if FLAGS.contains(&key) {
+                flags.push(key.to_string());
+            } else if !VALUE_OPTIONS.contains(&key) {
+                return Err(CliError::Usage(format!("Unknown option --{}", key)));
+            } else {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("Option --{} requires a value", key)))?;
+                values.insert(key.to_string(), value.clone());
+            }
+        }
+
+        Ok(Options { values, flags })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.flags.iter().any(|f| f == key)
+    }
+
+    fn list(&self, key: &str) -> Result<Option<Vec<f64>>, CliError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(raw) => raw
+                .split(',')
+                .map(|s| {
+                    s.trim()
+                        .parse::<f64>()
+                        .map_err(|_| CliError::Usage(format!("Invalid number '{}' in --{}", s.trim(), key)))
+                })
+                .collect::<Result<Vec<f64>, CliError>>()
+                .map(Some),
+        }
+    }
+
+    fn require_list(&self, key: &str) -> Result<Vec<f64>, CliError> {
+        self.list(key)?
+            .ok_or_else(|| CliError::Usage(format!("Missing --{} (or use --file)", key)))
+    }
+}
+
+/// Runs the subcommand described by `args` (program name excluded) and returns the exit code.
+pub fn run(args: &[String]) -> i32 {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => {
+            println!("{}", USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    match dispatch(command, rest) {
+        Ok(()) => EXIT_OK,
+        Err(err) => {
+            match &err {
+                CliError::Usage(msg) => eprintln!("Error: {}\n\n{}", msg, USAGE),
+                CliError::Data(msg) => eprintln!("Error: {}", msg),
+            }
+            err.exit_code()
+        }
+    }
+}
+
+fn dispatch(command: &str, args: &[String]) -> Result<(), CliError> {
+    if matches!(command, "help" | "--help" | "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let opts = Options::parse(args)?;
+    if opts.flag("help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let is_population = opts.flag("population");
+    let label = if is_population { "Population" } else { "Sample" };
+
+    match command {
+        "mean" => {
+            let values = single_series(&opts, "values")?;
+            println!("Mean: {:.4}", stats::mean(&values)?);
+        }
+
+        "vwap" => {
+            let (prices, volumes) = priced_series(&opts)?;
+            let result = stats::vwap(&prices, &volumes)?;
+            println!("VWAP: {:.4}", result.value);
+            println!("Signal: {}", result.signal);
+        }
+
+        "vwap-group" => {
+            let ((bids, asks, volumes), sizes) = group_columns(&opts)?;
+            let result = stats::vwap_group_with(&bids, &asks, &volumes, GroupPrice::from_sizes(&sizes))?;
+            println!("VWAP Group{}: {:.4}", group_label(&sizes), result.value);
+            println!("Signal: {}", result.signal);
+        }
+
+        "variance" => {
+            let values = single_series(&opts, "values")?;
+            let result = stats::variance(&values, is_population)?;
+            println!("{label} Variance: {:.4}", result.value);
+            println!("Market Interpretation: {}", result.interpretation());
+        }
+
+        "std" => {
+            let values = single_series(&opts, "values")?;
+            let result = stats::std(&values, is_population)?;
+            println!("{label} Standard Deviation (STD): {:.4} => {}", result.value, result.interpretation());
+        }
+
+        "distribution" => {
+            let format = output_format(&opts)?;
+            let quantiles: Vec<f64> = match opts.get("quantiles") {
+                Some(_) => opts.require_list("quantiles")?,
+                None => vec![0.01, 0.05, 0.95, 0.99],
+            };
+            if let Some(q) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+                return Err(CliError::Usage(format!("Invalid quantile {} (expected 0 to 1, e.g. 0.05)", q)));
+            }
+
+            let (values, weights) = match opts.flag("weighted") {
+                true => priced_series(&opts)?,
+                false => {
+                    let values = single_series(&opts, "values")?;
+                    let weights = vec![1.0; values.len()];
+                    (values, weights)
+                }
+            };
+            let d = distribution::weighted_distribution(&values, &weights)?;
+
+            let mut rows: Vec<Vec<Cell>> = vec![
+                vec![Cell::Text("count".into()), Cell::Integer(d.count as i64)],
+                vec![Cell::Text("mean".into()), Cell::Number(d.mean)],
+                vec![Cell::Text("min".into()), Cell::Number(d.min)],
+                vec![Cell::Text("max".into()), Cell::Number(d.max)],
+                vec![Cell::Text("range".into()), Cell::Number(d.range)],
+            ];
+            if let Some(q) = d.quartiles {
+                rows.push(vec![Cell::Text("q1".into()), Cell::Number(q.q1)]);
+                rows.push(vec![Cell::Text("median".into()), Cell::Number(q.median)]);
+                rows.push(vec![Cell::Text("q3".into()), Cell::Number(q.q3)]);
+                rows.push(vec![Cell::Text("iqr".into()), Cell::Number(q.iqr)]);
+            }
+            for q in quantiles {
+                let value = match opts.flag("weighted") {
+                    true => distribution::weighted_quantile(&values, &weights, q)?,
+                    false => distribution::quantile(&values, q)?,
+                };
+                rows.push(vec![Cell::Text(format!("p{}", q * 100.0)), Cell::Number(value)]);
+            }
+            rows.push(vec![Cell::Text("skewness".into()), Cell::Number(d.skewness)]);
+            rows.push(vec![Cell::Text("excess_kurtosis".into()), Cell::Number(d.excess_kurtosis)]);
+            rows.push(vec![Cell::Text("jarque_bera".into()), Cell::Number(d.jarque_bera.statistic)]);
+            rows.push(vec![Cell::Text("jarque_bera_p_value".into()), Cell::Number(d.jarque_bera.p_value)]);
+            print!("{}", report::render_table(&["metric", "value"], &rows, format));
+            if format == OutputFormat::Text {
+                println!("{}", d.interpretation());
+            }
+        }
+
+        "spread" => {
+            let quantiles: Vec<f64> = match opts.get("quantiles") {
+                Some(_) => opts.require_list("quantiles")?,
+                None => vec![0.5, 0.9, 0.99],
+            };
+            if let Some(q) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+                return Err(CliError::Usage(format!("Invalid quantile {} (expected 0 to 1, e.g. 0.9)", q)));
+            }
+            let format = output_format(&opts)?;
+            let ticks = load_ticks(&opts)?;
+            let default_horizons = match ticks.iter().all(|t| t.timestamp.is_some()) {
+                true => "1m,5m",
+                false => "5,20",
+            };
+            let names: Vec<&str> = opts.get("horizons").unwrap_or(default_horizons).split(',').map(str::trim).collect();
+            let horizons: Vec<Window> = names
+                .iter()
+                .map(|h| h.parse::<Window>().map_err(CliError::Usage))
+                .collect::<Result<_, _>>()?;
+
+            let summary = spread::spread_summary(&ticks, &quantiles)?;
+            let row = |metric: String, value: Option<SpreadValue>| {
+                vec![
+                    Cell::Text(metric),
+                    Cell::from_option(value.map(|v| v.absolute)),
+                    Cell::from_option(value.map(|v| v.bps)),
+                ]
+            };
+            let mut rows: Vec<Vec<Cell>> = vec![
+                vec![Cell::Text("quotes".into()), Cell::Integer(summary.count as i64), Cell::Empty],
+                vec![Cell::Text("crossed".into()), Cell::Integer(summary.crossed as i64), Cell::Empty],
+                row("average".into(), Some(summary.average)),
+                row("time_weighted".into(), summary.time_weighted),
+                row("volume_weighted".into(), summary.volume_weighted),
+                row("min".into(), Some(summary.min)),
+                row("max".into(), Some(summary.max)),
+            ];
+            for (q, value) in &summary.percentiles {
+                rows.push(row(format!("p{}", q * 100.0), Some(*value)));
+            }
+
+            if ticks.iter().any(|t| t.last_price.is_some()) {
+                let execution = spread::execution_spreads(&ticks, &horizons)?;
+                rows.push(vec![Cell::Text("trades".into()), Cell::Integer(execution.trades as i64), Cell::Empty]);
+                rows.push(vec![
+                    Cell::Text("unclassified_trades".into()),
+                    Cell::Integer(execution.unclassified as i64),
+                    Cell::Empty,
+                ]);
+                rows.push(row("effective".into(), Some(execution.effective)));
+                rows.push(row("effective_volume_weighted".into(), execution.effective_volume_weighted));
+                for (name, realized) in names.iter().zip(&execution.realized) {
+                    rows.push(row(format!("realized_{}", name), realized.realized));
+                    rows.push(row(format!("price_impact_{}", name), realized.price_impact));
+                }
+            }
+            print!("{}", report::render_table(&["metric", "value", "bps"], &rows, format));
+        }
+
+        "vwap-variance" => {
+            let (prices, volumes) = priced_series(&opts)?;
+            let result = stats::variance_vwap(&prices, &volumes, is_population)?;
+            println!("{label} VWAP Variance: {:.4} => {}", result.value, result.interpretation());
+        }
+
+        "vwap-std" => {
+            let (prices, volumes) = priced_series(&opts)?;
+            let result = stats::std_vwap(&prices, &volumes, is_population)?;
+            println!("{label} VWAP Standard Deviation: {:.4} => {}", result.value, result.interpretation());
+        }
+
+        "vwap-group-variance" => {
+            let ((bids, asks, volumes), sizes) = group_columns(&opts)?;
+            let result = stats::variance_vwap_group_with(&bids, &asks, &volumes, is_population, GroupPrice::from_sizes(&sizes))?;
+            println!("VWAP Group Variance{}: {:.4} => {}", group_label(&sizes), result.value, result.interpretation());
+        }
+
+        "vwap-group-std" => {
+            let ((bids, asks, volumes), sizes) = group_columns(&opts)?;
+            let result = stats::std_vwap_group_with(&bids, &asks, &volumes, is_population, GroupPrice::from_sizes(&sizes))?;
+            println!("VWAP Group STD{}: {:.4} => {}", group_label(&sizes), result.value, result.interpretation());
+        }
+
+        "book" => {
+            let frequency: Frequency = opts.get("every").unwrap_or("tick").parse().map_err(CliError::Usage)?;
+            let ticks = load_ticks(&opts)?;
+
+            let series = book::book_series(&ticks, frequency)?;
+            let rows: Vec<Vec<Cell>> = series
+                .iter()
+                .enumerate()
+                .map(|(i, point)| {
+                    vec![
+                        Cell::Integer(i as i64 + 1),
+                        point.timestamp.map(|ts| Cell::Text(format_timestamp(ts))).unwrap_or(Cell::Empty),
+                        Cell::Number(point.mid),
+                        Cell::Number(point.microprice),
+                        Cell::from_option(point.imbalance),
+                    ]
+                })
+                .collect();
+            let headers = ["point", "timestamp", "mid", "microprice", "imbalance"];
+            print!("{}", report::render_table(&headers, &rows, output_format_or(&opts, OutputFormat::Csv)?));
+        }
+
+        "pnl" => {
+            let positions = load_positions(&opts)?;
+
+            if let Some(group_by) = opts.get("by") {
+                let group_by: GroupBy = group_by.parse().map_err(CliError::Usage)?;
+                let format = output_format(&opts)?;
+                let portfolio = match opts.get("currency") {
+                    Some(currency) => Portfolio::in_currency(positions, &fx_rates(&opts)?, currency)?,
+                    None => Portfolio::new(positions),
+                };
+                if let (OutputFormat::Text, Some(currency)) = (format, portfolio.currency()) {
+                    println!("Portfolio P&L in {}", currency);
+                }
+                print!("{}", report::render_breakdown(&portfolio, group_by, format));
+                return Ok(());
+            }
+
+            match opts.get("currency") {
+                None => {
+                    for pos in &positions {
+                        println!("\n--- P&L Result for {} ({}) ---", pos.asset_id, pos.side);
+                        print_pnl(&calculate_pnl(pos));
+                    }
+                }
+                Some(currency) => {
+                    let report = fx::consolidate(&positions, &fx_rates(&opts)?, currency)?;
+
+                    for (pos, position) in report.positions.iter().zip(&positions) {
+                        println!("\n--- P&L Result for {} ({}, {}) ---", pos.asset_id, position.side, pos.currency);
+                        print_pnl(&pos.local);
+                        if pos.currency != report.currency {
+                            println!("P&L in {}    : {:.2} (rate {})", report.currency, pos.reporting.pnl, pos.rate);
+                        }
+                    }
+                    println!("\n=== Consolidated P&L ({}) ===", report.currency);
+                    print_pnl(&report.total);
+                }
+            }
+        }
+
+        "returns" => {
+            let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+            let series = return_series(&opts, field)?;
+            let rows: Vec<Vec<Cell>> = series
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    vec![
+                        Cell::Integer(i as i64 + 1),
+                        r.timestamp.map(|ts| Cell::Text(format_timestamp(ts))).unwrap_or(Cell::Empty),
+                        Cell::Number(r.value),
+                    ]
+                })
+                .collect();
+            let headers = ["point", "timestamp", "return"];
+            print!("{}", report::render_table(&headers, &rows, output_format_or(&opts, OutputFormat::Csv)?));
+        }
+
+        "volatility" => {
+            let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+            let every = opts.get("every").unwrap_or("5m");
+            let period = parse_duration(every)
+                .ok_or_else(|| CliError::Usage(format!("Invalid --every '{}' (e.g. 1m, 5m, 1d)", every)))?;
+            let calendar: TradingCalendar = opts.get("calendar").unwrap_or("us").parse().map_err(CliError::Usage)?;
+            let format = output_format(&opts)?;
+            let ticks = load_ticks(&opts)?;
+
+            let estimates = volatility::estimate(&ticks, field, period, &calendar)?;
+            let rows: Vec<Vec<Cell>> = estimates
+                .iter()
+                .map(|e| {
+                    vec![
+                        Cell::Text(e.estimator.clone()),
+                        Cell::Number(e.variance),
+                        Cell::Number(e.volatility),
+                        Cell::Number(e.annualized),
+                    ]
+                })
+                .collect();
+            if format == OutputFormat::Text {
+                println!("{} bars of {}, calendar {}", every, field, calendar);
+            }
+            let headers = ["estimator", "variance", "volatility", "annualized"];
+            print!("{}", report::render_table(&headers, &rows, format));
+        }
+
+        "var" => {
+            let confidences: Vec<f64> = match opts.get("confidence") {
+                Some(_) => opts.require_list("confidence")?,
+                None => vec![0.95, 0.99],
+            };
+            if let Some(c) = confidences.iter().find(|c| !(**c > 0.0 && **c < 1.0)) {
+                return Err(CliError::Usage(format!("Invalid confidence {} (expected between 0 and 1, e.g. 0.99)", c)));
+            }
+            let horizon: usize = match opts.get("horizon") {
+                Some(h) => h
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("Invalid --horizon '{}' (expected a number of periods)", h)))?,
+                None => 1,
+            };
+            let methods: Vec<VarMethod> = match opts.get("method").unwrap_or("all") {
+                "all" => VarMethod::ALL.to_vec(),
+                name => vec![name.parse().map_err(CliError::Usage)?],
+            };
+            let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+            let format = output_format(&opts)?;
+
+            let pnl = match opts.get("positions") {
+                Some(_) => {
+                    let every = opts
+                        .get("every")
+                        .ok_or_else(|| CliError::Usage("--positions needs --every DURATION (e.g. 1d)".to_string()))?;
+                    let period = parse_duration(every)
+                        .ok_or_else(|| CliError::Usage(format!("Invalid --every '{}' (e.g. 1m, 1h, 1d)", every)))?;
+                    let positions = load_positions(&opts)?;
+                    let portfolio = risk::portfolio_pnl(&positions, &load_ticks(&opts)?, field, period)?;
+                    for asset in &portfolio.missing {
+                        eprintln!("Warning: no prices for {} in --file, left out", asset);
+                    }
+                    if format == OutputFormat::Text {
+                        let total = portfolio.exposures.values().fold(0.0, |total, e| total + e);
+                        println!("Portfolio exposure {:.2} over {} periods of {}", total, portfolio.pnl.len(), every);
+                    }
+                    portfolio.pnl
+                }
+                None => {
+                    let frequency: Frequency = opts.get("every").unwrap_or("tick").parse().map_err(CliError::Usage)?;
+                    let ticks = load_ticks(&opts)?;
+                    let prices: Vec<f64> = match frequency {
+                        Frequency::Tick => ticks.iter().filter_map(|t| field.of(t)).collect(),
+                        Frequency::Every(ms) => returns::resample(&ticks, field, ms)?.into_iter().map(|(_, p)| p).collect(),
+                    };
+                    let value = match opts.get("value") {
+                        Some(v) => v
+                            .parse::<f64>()
+                            .map_err(|_| CliError::Usage(format!("Invalid --value '{}' (expected an amount)", v)))?,
+                        None => *prices.last().ok_or(StatsError::EmptyInput)?,
+                    };
+                    if format == OutputFormat::Text {
+                        println!("Position value {:.2}, {} returns of {}", value, prices.len().saturating_sub(1), field);
+                    }
+                    risk::series_pnl(&prices, value)?
+                }
+            };
+
+            let mut rows: Vec<Vec<Cell>> = Vec::new();
+            for method in &methods {
+                for confidence in &confidences {
+                    let estimate = risk::value_at_risk(&pnl, *method, *confidence, horizon)?;
+                    rows.push(vec![
+                        Cell::Text(estimate.method.to_string()),
+                        Cell::Number(estimate.confidence),
+                        Cell::Integer(estimate.horizon as i64),
+                        Cell::Number(estimate.var),
+                        Cell::Number(estimate.expected_shortfall),
+                        Cell::from_option(estimate.dof),
+                    ]);
+                }
+            }
+            let headers = ["method", "confidence", "horizon", "var", "expected_shortfall", "dof"];
+            print!("{}", report::render_table(&headers, &rows, format));
+        }
+
+        "performance" => {
+            let risk_free = match opts.get("risk-free") {
+                Some(rate) => rate
+                    .parse::<f64>()
+                    .map_err(|_| CliError::Usage(format!("Invalid --risk-free '{}' (e.g. 0.05 for 5%)", rate)))?,
+                None => 0.0,
+            };
+            let calendar: TradingCalendar = opts.get("calendar").unwrap_or("us").parse().map_err(CliError::Usage)?;
+            let periods_per_year = match (opts.get("periods-per-year"), opts.get("every")) {
+                (Some(n), _) => n
+                    .parse::<f64>()
+                    .map_err(|_| CliError::Usage(format!("Invalid --periods-per-year '{}' (e.g. 252)", n)))?,
+                (None, Some(every)) => calendar.periods_per_year(
+                    parse_duration(every)
+                        .ok_or_else(|| CliError::Usage(format!("Invalid --every '{}' (e.g. 1h, 1d)", every)))?,
+                ),
+                (None, None) => 252.0,
+            };
+            let config = PerformanceConfig::new()
+                .risk_free_rate(risk_free)
+                .periods_per_year(periods_per_year);
+            let format = output_format(&opts)?;
+
+            let capital = || -> Result<f64, CliError> {
+                let capital = opts
+                    .get("capital")
+                    .ok_or_else(|| CliError::Usage("Missing --capital AMOUNT (starting equity of the P&L)".to_string()))?;
+                capital
+                    .parse::<f64>()
+                    .map_err(|_| CliError::Usage(format!("Invalid --capital '{}' (expected an amount)", capital)))
+            };
+            let perf = if opts.get("equity").is_some() {
+                performance::performance(&opts.require_list("equity")?, &config)?
+            } else if opts.get("positions").is_some() {
+                let every = opts
+                    .get("every")
+                    .ok_or_else(|| CliError::Usage("--positions needs --every DURATION (e.g. 1d)".to_string()))?;
+                let frequency: Frequency = every.parse().map_err(CliError::Usage)?;
+                let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+                let curve = mtm::pnl_series(&load_positions(&opts)?, &load_ticks(&opts)?, field, frequency)?;
+                let pnl: Vec<f64> = curve.iter().map(|point| point.change).collect();
+                performance::performance_from_pnl(&pnl, capital()?, &config)?
+            } else {
+                performance::performance_from_pnl(&opts.require_list("pnl")?, capital()?, &config)?
+            };
+
+            let dd = perf.drawdown;
+            let rows: Vec<Vec<Cell>> = vec![
+                vec![Cell::Text("periods".into()), Cell::Integer(perf.periods as i64)],
+                vec![Cell::Text("cumulative_return".into()), Cell::Number(perf.cumulative_return)],
+                vec![Cell::Text("annualized_return".into()), Cell::Number(perf.annualized_return)],
+                vec![Cell::Text("annualized_volatility".into()), Cell::Number(perf.annualized_volatility)],
+                vec![Cell::Text("sharpe".into()), Cell::from_option(perf.sharpe)],
+                vec![Cell::Text("sortino".into()), Cell::from_option(perf.sortino)],
+                vec![Cell::Text("max_drawdown".into()), Cell::Number(dd.max)],
+                vec![Cell::Text("max_drawdown_amount".into()), Cell::Number(dd.amount)],
+                vec![Cell::Text("drawdown_duration".into()), Cell::Integer(dd.duration as i64)],
+                vec![Cell::Text("drawdown_recovered".into()), Cell::Text(dd.recovery.is_some().to_string())],
+                vec![Cell::Text("longest_drawdown".into()), Cell::Integer(dd.longest_duration as i64)],
+                vec![Cell::Text("calmar".into()), Cell::from_option(perf.calmar)],
+                vec![Cell::Text("hit_rate".into()), Cell::from_option(perf.hit_rate)],
+                vec![Cell::Text("profit_factor".into()), Cell::from_option(perf.profit_factor)],
+                vec![Cell::Text("average_win".into()), Cell::from_option(perf.average_win)],
+                vec![Cell::Text("average_loss".into()), Cell::from_option(perf.average_loss)],
+            ];
+            print!("{}", report::render_table(&["metric", "value"], &rows, format));
+            if format == OutputFormat::Text {
+                println!("{}", perf.interpretation());
+            }
+        }
+
+        "mtm" => {
+            let positions = load_positions(&opts)?;
+            let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+            let frequency: Frequency = opts.get("every").unwrap_or("tick").parse().map_err(CliError::Usage)?;
+            let ticks = load_ticks(&opts)?;
+
+            let curve = mtm::pnl_series(&positions, &ticks, field, frequency)?;
+            let rows: Vec<Vec<Cell>> = curve
+                .iter()
+                .enumerate()
+                .map(|(i, point)| {
+                    vec![
+                        Cell::Integer(i as i64 + 1),
+                        point.timestamp.map(|ts| Cell::Text(format_timestamp(ts))).unwrap_or(Cell::Empty),
+                        Cell::Number(point.pnl),
+                        Cell::Number(point.change),
+                    ]
+                })
+                .collect();
+            let headers = ["point", "timestamp", "pnl", "change"];
+            print!("{}", report::render_table(&headers, &rows, output_format_or(&opts, OutputFormat::Csv)?));
+        }
+
+        "ledger" => {
+            let path = opts
+                .get("fills")
+                .ok_or_else(|| CliError::Usage("Missing --fills FILE".to_string()))?;
+            let method: CostMethod = opts.get("method").unwrap_or("fifo").parse().map_err(CliError::Usage)?;
+            let marks = market_prices(&opts)?;
+            let format = output_format(&opts)?;
+
+            let mut fills = csv_loader::load_fills(path, &csv_options(&opts)?).map_err(|err| CliError::Data(format!("{} ({})", err, path)))?;
+            let instruments = instruments(&opts)?;
+            if let Some(fees) = fee_config(&opts)? {
+                fees.apply_to_fills(&mut fills, &instruments);
+            }
+            if fills.is_empty() {
+                return Err(CliError::Data(format!("No fills found in '{}'", path)));
+            }
+            let borrow_rate = match opts.get("borrow-rate") {
+                Some(rate) => rate
+                    .parse::<f64>()
+                    .map_err(|_| CliError::Usage(format!("Invalid --borrow-rate '{}' (e.g. 0.02 for 2% a year)", rate)))?,
+                None => 0.0,
+            };
+            let mut ledger = Ledger::new(method)
+                .allow_short(opts.flag("allow-short"))
+                .borrow_rate(borrow_rate)
+                .instruments(instruments);
+            ledger.record_all(&fills)?;
+
+            let timestamp = |ts: Option<i64>| ts.map(|ts| Cell::Text(format_timestamp(ts))).unwrap_or(Cell::Empty);
+            if opts.flag("lots") {
+                let rows: Vec<Vec<Cell>> = ledger
+                    .closed_lots()
+                    .iter()
+                    .map(|lot| {
+                        vec![
+                            Cell::Text(lot.asset_id.clone()),
+                            Cell::Text(lot.side.to_string()),
+                            Cell::Number(lot.quantity),
+                            Cell::Number(lot.buy_price),
+                            Cell::Number(lot.sell_price),
+                            timestamp(lot.opened),
+                            timestamp(lot.closed),
+                            Cell::Number(lot.fees),
+                            Cell::Number(lot.borrow_fee),
+                            Cell::Number(lot.realized_pnl),
+                        ]
+                    })
+                    .collect();
+                let headers = [
+                    "asset_id", "side", "quantity", "buy_price", "sell_price", "opened", "closed", "fees", "borrow_fee",
+                    "realized_pnl",
+                ];
+                print!("{}", report::render_table(&headers, &rows, format));
+            } else {
+                let rows: Vec<Vec<Cell>> = ledger
+                    .report(&marks)
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            Cell::Text(r.asset_id.clone()),
+                            Cell::Number(r.realized_pnl),
+                            Cell::Number(r.open_quantity),
+                            Cell::from_option(r.average_cost),
+                            Cell::from_option(r.market_price),
+                            Cell::from_option(r.unrealized_pnl),
+                        ]
+                    })
+                    .collect();
+                let headers = ["asset_id", "realized_pnl", "open_quantity", "average_cost", "market_price", "unrealized_pnl"];
+                if format == OutputFormat::Text {
+                    println!("Lot matching: {}", method);
+                }
+                print!("{}", report::render_table(&headers, &rows, format));
+            }
+        }
+
+        "summary" => {
+            let format = output_format(&opts)?;
+
+            if opts.flag("by-symbol") {
+                // One report per instrument
+                for (symbol, ticks) in tick::by_symbol(&load_ticks(&opts)?) {
+                    let (bids, asks, volumes) = tick::columns(&ticks);
+                    let summary = stats::market_summary(&bids, &asks, &volumes)?;
+                    let name = if symbol.is_empty() { "(no symbol)" } else { symbol.as_str() };
+                    if format == OutputFormat::Text {
+                        println!("\n=== {} ({} rows) ===", name, ticks.len());
+                    }
+                    print!("{}", report::render(&summary, format));
+                }
+            } else if opts.flag("streaming") {
+                print!("{}", report::render(&stream_summary(&opts)?, format));
+            } else {
+                let (bids, asks, volumes) = market_columns(&opts)?;
+                let summary = stats::market_summary(&bids, &asks, &volumes)?;
+                print!("{}", report::render(&summary, format));
+            }
+        }
+
+        "rolling" => {
+            let window: Window = opts
+                .get("window")
+                .ok_or_else(|| CliError::Usage("Missing --window (e.g. 20 or 5m)".to_string()))?
+                .parse()
+                .map_err(CliError::Usage)?;
+            let field = price_field(&opts)?;
+            let ticks = load_ticks(&opts)?;
+
+            let prices = tick::prices(&ticks, field)
+                .ok_or_else(|| CliError::Data(format!("Some rows have no {} price", field)))?;
+            let volumes = tick::volumes(&ticks);
+            let timestamps = tick::timestamps(&ticks);
+
+            let vwaps = rolling::rolling_vwap(&prices, &volumes, window, timestamps.as_deref())?;
+            let stds = rolling::rolling_std_vwap(&prices, &volumes, window, timestamps.as_deref(), is_population)?;
+
+            let rows: Vec<Vec<Cell>> = ticks
+                .iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    vec![
+                        Cell::Integer(i as i64 + 1),
+                        t.timestamp.map(|ts| Cell::Text(format_timestamp(ts))).unwrap_or(Cell::Empty),
+                        Cell::Number(prices[i]),
+                        Cell::Number(volumes[i]),
+                        Cell::from_option(vwaps[i]),
+                        Cell::from_option(stds[i]),
+                    ]
+                })
+                .collect();
+            let headers = ["tick", "timestamp", "price", "volume", "rolling_vwap", "rolling_std_vwap"];
+            print!("{}", report::render_table(&headers, &rows, output_format_or(&opts, OutputFormat::Csv)?));
+        }
+
+        "stream" => {
+            let format = output_format(&opts)?;
+            let mut config = StreamConfig::new(
+                opts.get("url")
+                    .ok_or_else(|| CliError::Usage("Missing --url (e.g. ws://localhost:9001)".to_string()))?,
+            );
+            if let Some(spec) = opts.get("schema") {
+                config.schema = MessageSchema::parse(spec).map_err(CliError::Usage)?;
+            }
+            if let Some(every) = opts.get("every") {
+                config.report_every = every.parse().map_err(CliError::Usage)?;
+            }
+            if let Some(limit) = opts.get("limit") {
+                config.max_ticks = Some(
+                    limit
+                        .parse()
+                        .map_err(|_| CliError::Usage(format!("Invalid --limit '{}'", limit)))?,
+                );
+            }
+            config.subscribe = opts.get("subscribe").map(str::to_string);
+            config.symbol = opts.get("symbol").map(str::to_string);
+
+            let counters = stream::run(&config, |summary, counters| {
+                if format == OutputFormat::Text {
+                    println!("\n=== {} ticks ({} messages skipped) ===", counters.ticks, counters.skipped);
+                }
+                print!("{}", report::render(summary, format));
+                let _ = io::stdout().flush();
+            })
+            .map_err(CliError::Data)?;
+
+            eprintln!(
+                "Stream ended: {} messages, {} ticks, {} skipped",
+                counters.messages, counters.ticks, counters.skipped
+            );
+            if counters.ticks == 0 {
+                return Err(CliError::Data("No ticks received.".to_string()));
+            }
+        }
+
+        other => return Err(CliError::Usage(format!("Unknown command '{}'", other))),
+    }
+
+    Ok(())
+}
+
+/// Output format from `--output` (default: text).
+fn output_format(opts: &Options) -> Result<OutputFormat, CliError> {
+    output_format_or(opts, OutputFormat::Text)
+}
+
+/// Output format from `--output`, or `default` when not given.
+fn output_format_or(opts: &Options, default: OutputFormat) -> Result<OutputFormat, CliError> {
+    match opts.get("output") {
+        Some(name) => name.parse::<OutputFormat>().map_err(CliError::Usage),
+        None => Ok(default),
+    }
+}
+
+/// FX rates from `--fx FILE` (cross rates through `--fx-base`, default USD); empty without `--fx`.
+fn fx_rates(opts: &Options) -> Result<FxRates, CliError> {
+    let base = opts.get("fx-base").unwrap_or("USD");
+    match opts.get("fx") {
+        Some(path) => csv_loader::load_fx_rates(path, base, &csv_options(opts)?).map_err(|err| CliError::Data(format!("{} ({})", err, path))),
+        None => Ok(FxRates::new(base)),
+    }
+}
+
+/// Positions from `--positions FILE`, with `--instruments` specs and `--fees` costs applied.
+fn load_positions(opts: &Options) -> Result<Vec<AssetPosition>, CliError> {
+    let path = opts
+        .get("positions")
+        .ok_or_else(|| CliError::Usage("Missing --positions FILE".to_string()))?;
+    let report = csv_loader::load_positions(path, &csv_options(opts)?)
+        .map_err(|err| CliError::Data(format!("{} ({})", err, path)))?;
+    for row in &report.rejected {
+        eprintln!("Warning: position rejected: {}", row.reason);
+    }
+    let mut positions = report.positions;
+    instruments(opts)?.apply_to_positions(&mut positions);
+    if let Some(fees) = fee_config(opts)? {
+        fees.apply_to_positions(&mut positions);
+    }
+    if positions.is_empty() {
+        return Err(CliError::Data(format!("No positions found in '{}'", path)));
+    }
+    Ok(positions)
+}
+
+/// Instrument registry from `--instruments FILE`; empty (every multiplier 1) without it.
+fn instruments(opts: &Options) -> Result<InstrumentRegistry, CliError> {
+    match opts.get("instruments") {
+        Some(path) => csv_loader::load_instruments(path, &csv_options(opts)?).map_err(|err| CliError::Data(format!("{} ({})", err, path))),
+        None => Ok(InstrumentRegistry::new()),
+    }
+}
+
+/// Fee schedules from `--fees FILE`, if given.
+fn fee_config(opts: &Options) -> Result<Option<FeeConfig>, CliError> {
+    opts.get("fees")
+        .map(|path| FeeConfig::load(path).map_err(|err| CliError::Data(format!("{} ({})", err, path))))
+        .transpose()
+}
+
+/// Prints the components of a P&L result.
+fn print_pnl(result: &PnLResult) {
+    println!("Revenue       : {:.2}", result.revenue);
+    println!("Cost          : {:.2}", result.cost);
+    println!("Exposure      : {:.2}", result.exposure);
+    if result.borrow_fee != 0.0 {
+        println!("Borrow fee    : {:.2}", result.borrow_fee);
+    }
+    println!("Total P&L     : {:.2}", result.pnl);
+}
+
+/// Market prices from `--marks ASSET=PRICE,...`.
+fn market_prices(opts: &Options) -> Result<BTreeMap<String, f64>, CliError> {
+    let mut marks = BTreeMap::new();
+    for entry in opts.get("marks").unwrap_or("").split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let price = entry
+            .split_once('=')
+            .and_then(|(asset, price)| Some((asset.trim(), price.trim().parse::<f64>().ok()?)))
+            .ok_or_else(|| CliError::Usage(format!("Invalid --marks entry '{}' (expected ASSET=PRICE)", entry)))?;
+        marks.insert(price.0.to_string(), price.1);
+    }
+    Ok(marks)
+}
+
+/// Price field from `--column` (bid, ask, mid or last; default: bid).
+fn price_field(opts: &Options) -> Result<PriceField, CliError> {
+    opts.get("column").unwrap_or("bid").parse().map_err(CliError::Usage)
+}
+
+/// Builds the CSV reader options from `--delimiter`, `--decimal`, `--header/--no-header`,
+/// `--columns` and `--lenient`.
+fn csv_options(opts: &Options) -> Result<CsvOptions, CliError> {
+    let mut options = CsvOptions::default();
+
+    if let Some(delimiter) = opts.get("delimiter") {
+        options.delimiter = match delimiter {
+            "\\t" | "tab" => b'\t',
+            d if d.len() == 1 => d.as_bytes()[0],
+            other => return Err(CliError::Usage(format!("Invalid --delimiter '{}' (expected one character)", other))),
+        };
+    }
+    if let Some(decimal) = opts.get("decimal") {
+        options.decimal = match decimal {
+            "dot" | "." => DecimalSeparator::Dot,
+            "comma" | "," => DecimalSeparator::Comma,
+            other => return Err(CliError::Usage(format!("Invalid --decimal '{}' (expected dot or comma)", other))),
+        };
+    }
+    if opts.flag("header") {
+        options.header = HeaderMode::Present;
+    }
+    if opts.flag("no-header") {
+        options.header = HeaderMode::Absent;
+    }
+    if let Some(spec) = opts.get("columns") {
+        options.columns = ColumnMapping::parse(spec).map_err(CliError::Usage)?;
+    }
+    if opts.flag("lenient") {
+        options.mode = ParseMode::Lenient;
+    }
+
+    Ok(options)
+}
+
+/// Loads the ticks of `--file`, keeping only `--symbol` when given.
+fn load_ticks(opts: &Options) -> Result<Vec<Tick>, CliError> {
+    let path = opts
+        .get("file")
+        .ok_or_else(|| CliError::Usage("Missing --file FILE".to_string()))?;
+    let report = csv_loader::load_ticks(path, &csv_options(opts)?)
+        .map_err(|err| CliError::Data(format!("{} ({})", err, path)))?;
+    for row in &report.rejected {
+        eprintln!("Warning: line {} rejected: {}", row.line, row.reason);
+    }
+    let ticks = report.ticks;
+
+    match opts.get("symbol") {
+        Some(symbol) => {
+            let ticks = tick::for_symbol(&ticks, symbol);
+            if ticks.is_empty() {
+                return Err(CliError::Data(format!("No rows for symbol '{}' in '{}'", symbol, path)));
+            }
+            Ok(ticks)
+        }
+        None => Ok(ticks),
+    }
+}
+
+/// Summary of `--file` computed in one pass with the online accumulators, so the
+/// ticks are never held in memory. Honours `--symbol`.
+fn stream_summary(opts: &Options) -> Result<stats::MarketSummary, CliError> {
+    let path = opts
+        .get("file")
+        .ok_or_else(|| CliError::Usage("--streaming needs --file FILE".to_string()))?;
+    let symbol = opts.get("symbol");
+
+    let mut online = OnlineMarketStats::new();
+    let mut error = None;
+    let rejected = csv_loader::for_each_tick(path, &csv_options(opts)?, |tick| {
+        if error.is_some() || (symbol.is_some() && tick.symbol.as_deref() != symbol) {
+            return;
+        }
+        if let Err(err) = online.update(&tick) {
+            error = Some(err);
+        }
+    })
+    .map_err(|err| CliError::Data(format!("{} ({})", err, path)))?;
+
+    for row in &rejected {
+        eprintln!("Warning: line {} rejected: {}", row.line, row.reason);
+    }
+    if let Some(err) = error {
+        return Err(err.into());
+    }
+    if let (Some(symbol), 0) = (symbol, online.count()) {
+        return Err(CliError::Data(format!("No rows for symbol '{}' in '{}'", symbol, path)));
+    }
+    Ok(online.summary()?)
+}
+
+/// Loads bid/ask/volume columns from `--file`, or from `--bids/--asks/--volumes`.
+fn market_columns(opts: &Options) -> Result<csv_loader::MarketColumns, CliError> {
+    let (bids, asks, volumes) = match opts.get("file") {
+        Some(_) => tick::columns(&load_ticks(opts)?),
+        None => (
+            opts.require_list("bids")?,
+            opts.require_list("asks")?,
+            opts.require_list("volumes")?,
+        ),
+    };
+
+    if bids.is_empty() {
+        return Err(CliError::Data("No data provided.".to_string()));
+    }
+    if bids.len() != asks.len() || asks.len() != volumes.len() {
+        return Err(CliError::Data("All inputs must have the same length.".to_string()));
+    }
+    Ok((bids, asks, volumes))
+}
+
+/// Loads the columns of the VWAP Group family like `market_columns`, plus the bid and ask
+/// sizes when `--price microprice` is given (from the size columns of `--file`, or from
+/// `--bid-sizes/--ask-sizes`).
+fn group_columns(opts: &Options) -> Result<(csv_loader::MarketColumns, Option<BookSizes>), CliError> {
+    match opts.get("price").unwrap_or("mid") {
+        "mid" => return Ok((market_columns(opts)?, None)),
+        "microprice" | "micro" => {}
+        other => return Err(CliError::Usage(format!("Unknown --price '{}' (expected mid or microprice)", other))),
+    }
+    if opts.get("file").is_none() {
+        let sizes = (opts.require_list("bid-sizes")?, opts.require_list("ask-sizes")?);
+        return Ok((market_columns(opts)?, Some(sizes)));
+    }
+
+    let ticks = load_ticks(opts)?;
+    if ticks.is_empty() {
+        return Err(CliError::Data("No data provided.".to_string()));
+    }
+    let sizes: Option<Vec<(f64, f64)>> = ticks.iter().map(|t| Some((t.bid_size?, t.ask_size?))).collect();
+    let sizes = sizes
+        .ok_or_else(|| CliError::Data("--price microprice needs bid_size and ask_size on every row".to_string()))?;
+    Ok((tick::columns(&ticks), Some(sizes.into_iter().unzip())))
+}
+
+/// Suffix of the VWAP Group labels when priced at the microprice.
+fn group_label(sizes: &Option<BookSizes>) -> &'static str {
+    match sizes {
+        Some(_) => " (microprice)",
+        None => "",
+    }
+}
+
+/// Loads the `--column` prices (bid, ask, mid or last) and the volumes of `--file`.
+fn file_prices(opts: &Options) -> Result<(Vec<f64>, Vec<f64>), CliError> {
+    let field = price_field(opts)?;
+    let ticks = load_ticks(opts)?;
+    if ticks.is_empty() {
+        return Err(CliError::Data("No data provided.".to_string()));
+    }
+    let prices = tick::prices(&ticks, field)
+        .ok_or_else(|| CliError::Data(format!("Some rows have no {} price", field)))?;
+    Ok((prices, tick::volumes(&ticks)))
+}
+
+/// Returns of the `field` prices of `--file`, of `--returns` (or `--kind`) simple|log,
+/// resampled with `--every` (default: tick to tick).
+fn return_series(opts: &Options, field: PriceField) -> Result<Vec<returns::ReturnPoint>, CliError> {
+    let kind: ReturnKind = opts
+        .get("returns")
+        .or(opts.get("kind"))
+        .unwrap_or("simple")
+        .parse()
+        .map_err(CliError::Usage)?;
+    let frequency: Frequency = opts.get("every").unwrap_or("tick").parse().map_err(CliError::Usage)?;
+    let ticks = load_ticks(opts)?;
+    Ok(returns::tick_returns(&ticks, field, kind, frequency)?)
+}
+
+/// Loads a single series from `--file` (using `--column`, as returns with `--returns`) or
+/// from the inline list `--<key>`. Returns default to mid, like the `returns` command.
+fn single_series(opts: &Options, key: &str) -> Result<Vec<f64>, CliError> {
+    if opts.get("returns").is_some() {
+        if opts.get("file").is_none() {
+            return Err(CliError::Usage("--returns needs --file: returns are computed from the prices of FILE".into()));
+        }
+        let field: PriceField = opts.get("column").unwrap_or("mid").parse().map_err(CliError::Usage)?;
+        Ok(returns::values(&return_series(opts, field)?))
+    } else if opts.get("file").is_some() {
+        Ok(file_prices(opts)?.0)
+    } else {
+        opts.require_list(key)
+    }
+}
+
+/// Loads (prices, volumes) from `--file` (using `--column`) or from `--prices/--volumes`.
+fn priced_series(opts: &Options) -> Result<(Vec<f64>, Vec<f64>), CliError> {
+    if opts.get("file").is_some() {
+        file_prices(opts)
+    } else {
+        Ok((opts.require_list("prices")?, opts.require_list("volumes")?))
+    }
+}
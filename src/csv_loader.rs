@@ -1,32 +1,961 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufReader, Read};
+
+use csv::{ReaderBuilder, StringRecord};
+
+use crate::fx::FxRates;
+use crate::instruments::{Instrument, InstrumentRegistry};
+use crate::ledger::{Fill, Side};
+use crate::pnl::{AssetPosition, PositionSide};
+use crate::tick::{self, parse_timestamp, Tick};
+
+/// Column vectors loaded from a market data CSV: (bids, asks, volumes)
+pub type MarketColumns = (Vec<f64>, Vec<f64>, Vec<f64>);
+
+// OPTIONS ----------------------------------------------------------------------------
+/// Whether the first line of the file is a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderMode {
+    /// Header when none of the price and volume columns of the first line is a number
+    /// (their positions without a header: the mapping's indexes or `bid,ask,volume`);
+    /// always a header when the mapping names columns
+    #[default]
+    Auto,
+    Present,
+    Absent,
+}
+
+/// Decimal separator of the numbers in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecimalSeparator {
+    /// `1234.56`
+    #[default]
+    Dot,
+    /// `1234,56` or `1.234,56`, common in Brazilian/European exports (use with `;` as delimiter)
+    Comma,
+}
+
+impl DecimalSeparator {
+    /// Parses a number written with this decimal separator. The other separator is
+    /// accepted only as a thousands separator in valid positions (groups of three digits);
+    /// anywhere else the number is rejected rather than misread.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::csv_loader::DecimalSeparator;
+    ///
+    /// assert_eq!(DecimalSeparator::Dot.parse("1,234.5"), Some(1234.5));
+    /// assert_eq!(DecimalSeparator::Dot.parse("10,5"), None);
+    /// assert_eq!(DecimalSeparator::Comma.parse("1.234,5"), Some(1234.5));
+    /// assert_eq!(DecimalSeparator::Comma.parse("10,5"), Some(10.5));
+    /// // A dot decimal in a comma file is not 105
+    /// assert_eq!(DecimalSeparator::Comma.parse("10.5"), None);
+    /// ```
+    pub fn parse(&self, raw: &str) -> Option<f64> {
+        let raw = raw.trim();
+        let (decimal, group) = match self {
+            DecimalSeparator::Dot => ('.', ','),
+            DecimalSeparator::Comma => (',', '.'),
+        };
+        let (integer, fraction) = match raw.split_once(decimal) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (raw, None),
+        };
+        if fraction.is_some_and(|f| f.contains(group)) {
+            return None;
+        }
+        let integer = match integer.contains(group) {
+            true => ungroup(integer, group)?,
+            false => integer.to_string(),
+        };
+        match fraction {
+            Some(fraction) => format!("{}.{}", integer, fraction),
+            None => integer,
+        }
+        .parse::<f64>()
+        .ok()
+    }
+}
+
+/// Integer part without its thousands separators, or `None` unless they split it into a
+/// first group of 1 to 3 digits followed by groups of exactly 3 digits.
+fn ungroup(integer: &str, group: char) -> Option<String> {
+    let digits = integer.trim_start_matches(['+', '-']);
+    let sign = &integer[..integer.len() - digits.len()];
+    let groups: Vec<&str> = digits.split(group).collect();
+    let all_digits = |g: &str| g.chars().all(|c| c.is_ascii_digit());
+    let valid = (1..=3).contains(&groups[0].len())
+        && groups.iter().all(|g| all_digits(g))
+        && groups[1..].iter().all(|g| g.len() == 3);
+    valid.then(|| format!("{}{}", sign, groups.concat()))
+}
+
+/// Reference to a CSV column: by header name (case-insensitive) or by 0-based index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnRef {
+    Name(String),
+    Index(usize),
+}
+
+impl ColumnRef {
+    /// `"3"` is an index, anything else a header name.
+    pub fn parse(raw: &str) -> ColumnRef {
+        match raw.trim().parse::<usize>() {
+            Ok(idx) => ColumnRef::Index(idx),
+            Err(_) => ColumnRef::Name(raw.trim().to_string()),
+        }
+    }
+}
+
+/// User-chosen columns for the tick fields. Fields left as `None` are found by their
+/// usual header names (`bid`, `ask`, `volume`, ...) or, without a header, by the
+/// positional layout `bid,ask,volume`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ColumnMapping {
+    pub timestamp: Option<ColumnRef>,
+    pub symbol: Option<ColumnRef>,
+    pub bid: Option<ColumnRef>,
+    pub ask: Option<ColumnRef>,
+    pub bid_size: Option<ColumnRef>,
+    pub ask_size: Option<ColumnRef>,
+    pub last_price: Option<ColumnRef>,
+    pub volume: Option<ColumnRef>,
+}
+
+impl ColumnMapping {
+    /// Parses `field=column` pairs, e.g. `bid=Compra,ask=Venda,volume=4,timestamp=Hora`.
+    pub fn parse(spec: &str) -> Result<ColumnMapping, String> {
+        let mut mapping = ColumnMapping::default();
+
+        for pair in spec.split(',').filter(|p| !p.trim().is_empty()) {
+            let (field, column) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid column mapping '{}' (expected field=column)", pair.trim()))?;
+            let column = Some(ColumnRef::parse(column));
+
+            match field.trim().to_lowercase().as_str() {
+                "timestamp" => mapping.timestamp = column,
+                "symbol" => mapping.symbol = column,
+                "bid" => mapping.bid = column,
+                "ask" => mapping.ask = column,
+                "bid_size" => mapping.bid_size = column,
+                "ask_size" => mapping.ask_size = column,
+                "last" | "last_price" => mapping.last_price = column,
+                "volume" => mapping.volume = column,
+                other => return Err(format!("Unknown tick field '{}' in column mapping", other)),
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    /// Resolves the mapping to column indexes, using `header` for names and default aliases.
+    pub fn resolve(&self, header: Option<&StringRecord>) -> Result<TickColumns, String> {
+        let names: Vec<String> = header
+            .map(|h| h.iter().map(|n| n.trim().to_lowercase()).collect())
+            .unwrap_or_default();
+
+        let lookup = |column: &ColumnRef| match column {
+            ColumnRef::Index(idx) => Ok(*idx),
+            ColumnRef::Name(name) if header.is_some() => names
+                .iter()
+                .position(|n| *n == name.to_lowercase())
+                .ok_or_else(|| format!("Column '{}' not found in header", name)),
+            ColumnRef::Name(name) => Err(format!("Column '{}' is referenced by name but the file has no header", name)),
+        };
+        let optional = |column: &Option<ColumnRef>, aliases: &[&str]| match column {
+            Some(column) => lookup(column).map(Some),
+            None => Ok(names.iter().position(|n| aliases.contains(&n.as_str()))),
+        };
+        let required = |column: &Option<ColumnRef>, aliases: &[&str], position: usize| match column {
+            Some(column) => lookup(column),
+            None if header.is_none() => Ok(position),
+            None => names
+                .iter()
+                .position(|n| aliases.contains(&n.as_str()))
+                .ok_or_else(|| format!("Missing '{}' column in header", aliases[0])),
+        };
+
+        Ok(TickColumns {
+            timestamp: optional(&self.timestamp, TIMESTAMP_NAMES)?,
+            symbol: optional(&self.symbol, SYMBOL_NAMES)?,
+            bid: required(&self.bid, BID_NAMES, 0)?,
+            ask: required(&self.ask, ASK_NAMES, 1)?,
+            bid_size: optional(&self.bid_size, BID_SIZE_NAMES)?,
+            ask_size: optional(&self.ask_size, ASK_SIZE_NAMES)?,
+            last_price: optional(&self.last_price, LAST_NAMES)?,
+            volume: required(&self.volume, VOLUME_NAMES, 2)?,
+        })
+    }
+}
+
+/// What to do with rows that cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Stop at the first invalid row with an error
+    #[default]
+    Strict,
+    /// Skip invalid rows and list them in [`LoadReport::rejected`]
+    Lenient,
+}
+
+/// Options of the market data CSV reader. `CsvOptions::default()` reads comma-separated
+/// files with dot decimals, auto-detected header and strict row validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub header: HeaderMode,
+    pub delimiter: u8,
+    pub decimal: DecimalSeparator,
+    pub columns: ColumnMapping,
+    pub mode: ParseMode,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            header: HeaderMode::Auto,
+            delimiter: b',',
+            decimal: DecimalSeparator::Dot,
+            columns: ColumnMapping::default(),
+            mode: ParseMode::Strict,
+        }
+    }
+}
+
+/// A row skipped in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRow {
+    /// 1-based line number in the file
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Ticks read from a file, plus the rows rejected in lenient mode.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoadReport {
+    pub ticks: Vec<Tick>,
+    pub rejected: Vec<RejectedRow>,
+}
+//-------------------------------------------------------------------------------------
+
+/// Column indexes of the [`Tick`] fields in a CSV file.
+/// `bid`, `ask` and `volume` are required; the other fields are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickColumns {
+    pub timestamp: Option<usize>,
+    pub symbol: Option<usize>,
+    pub bid: usize,
+    pub ask: usize,
+    pub bid_size: Option<usize>,
+    pub ask_size: Option<usize>,
+    pub last_price: Option<usize>,
+    pub volume: usize,
+}
+
+impl Default for TickColumns {
+    /// Positional layout of the original files: bid,ask,volume
+    fn default() -> TickColumns {
+        TickColumns {
+            timestamp: None,
+            symbol: None,
+            bid: 0,
+            ask: 1,
+            bid_size: None,
+            ask_size: None,
+            last_price: None,
+            volume: 2,
+        }
+    }
+}
+
+/// Accepted header names (case-insensitive) for each tick field.
+const TIMESTAMP_NAMES: &[&str] = &["timestamp", "time", "datetime", "date", "ts"];
+const SYMBOL_NAMES: &[&str] = &["symbol", "ticker", "instrument", "asset", "asset_id"];
+const BID_NAMES: &[&str] = &["bid", "bid_price", "compra"];
+const ASK_NAMES: &[&str] = &["ask", "ask_price", "offer", "venda"];
+const BID_SIZE_NAMES: &[&str] = &["bid_size", "bid_qty", "bidsize"];
+const ASK_SIZE_NAMES: &[&str] = &["ask_size", "ask_qty", "asksize"];
+const LAST_NAMES: &[&str] = &["last", "last_price", "price", "trade_price"];
+const VOLUME_NAMES: &[&str] = &["volume", "vol", "qty", "quantity", "trade_volume"];
+
+impl TickColumns {
+    /// Maps named header columns onto tick fields, e.g. `timestamp,symbol,bid,ask,bid_size,ask_size,last,volume`.
+    pub fn from_header(header: &StringRecord) -> Result<TickColumns, String> {
+        ColumnMapping::default().resolve(Some(header))
+    }
+
+    /// Whether any of the bid, ask or volume fields of `record` is a number.
+    fn has_numbers(&self, record: &StringRecord, decimal: DecimalSeparator) -> bool {
+        [self.bid, self.ask, self.volume]
+            .iter()
+            .any(|idx| record.get(*idx).and_then(|f| decimal.parse(f)).is_some())
+    }
+
+    /// Builds a tick from one record; `line` is used in error messages.
+    pub fn parse(&self, record: &StringRecord, line: usize, decimal: DecimalSeparator) -> Result<Tick, String> {
+        let field = |idx: usize| record.get(idx).map(str::trim).unwrap_or("");
+        let number = |idx: usize, name: &str| {
+            if field(idx).is_empty() {
+                return Err(format!("Missing {} at line {}", name, line));
+            }
+            decimal
+                .parse(field(idx))
+                .filter(|v| !v.is_nan())
+                .ok_or_else(|| format!("Invalid {} '{}' at line {}", name, field(idx), line))
+        };
+        let optional = |idx: Option<usize>, name: &str| match idx {
+            Some(idx) if !field(idx).is_empty() => number(idx, name).map(Some),
+            _ => Ok(None),
+        };
+
+        let timestamp = match self.timestamp {
+            Some(idx) if !field(idx).is_empty() => Some(
+                parse_timestamp(field(idx))
+                    .ok_or_else(|| format!("Invalid timestamp '{}' at line {}", field(idx), line))?,
+            ),
+            _ => None,
+        };
+        let symbol = self
+            .symbol
+            .map(field)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+
+        Ok(Tick {
+            timestamp,
+            symbol,
+            bid: number(self.bid, "bid")?,
+            ask: number(self.ask, "ask")?,
+            bid_size: optional(self.bid_size, "bid_size")?,
+            ask_size: optional(self.ask_size, "ask_size")?,
+            last_price: optional(self.last_price, "last_price")?,
+            volume: number(self.volume, "volume")?,
+        })
+    }
+}
+
+// READERS ----------------------------------------------------------------------------
+/// Reads market data ticks from any reader with the given options.
+///
+/// In strict mode the first invalid row is an error; in lenient mode it is
+/// recorded in [`LoadReport::rejected`] and skipped. Missing or malformed
+/// values are never replaced by zero.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::csv_loader::{load_ticks_from_reader, ColumnMapping, CsvOptions, DecimalSeparator, ParseMode};
+///
+/// let data = "Compra;Venda;Volume\n10,5;11,0;1.000\n10,6;x;1500\n";
+/// let options = CsvOptions {
+///     delimiter: b';',
+///     decimal: DecimalSeparator::Comma,
+///     mode: ParseMode::Lenient,
+///     ..CsvOptions::default()
+/// };
+///
+/// let report = load_ticks_from_reader(data.as_bytes(), &options).unwrap();
+/// assert_eq!(report.ticks.len(), 1);
+/// assert_eq!(report.ticks[0].volume, 1000.0);
+/// assert_eq!(report.rejected[0].line, 3);
+///
+/// // Headerless, timestamp first: the numeric bid makes the first line data
+/// let data = "2024-03-01T10:00:00Z,PETR4,38.10,38.12,500\n2024-03-01T10:00:01Z,PETR4,38.11,38.13,300\n";
+/// let options = CsvOptions {
+///     columns: ColumnMapping::parse("timestamp=0,symbol=1,bid=2,ask=3,volume=4").unwrap(),
+///     ..CsvOptions::default()
+/// };
+/// assert_eq!(load_ticks_from_reader(data.as_bytes(), &options).unwrap().ticks.len(), 2);
+/// ```
+pub fn load_ticks_from_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<LoadReport, String> {
+    let mut ticks = Vec::new();
+    let rejected = for_each_tick_from_reader(reader, options, |tick| ticks.push(tick))?;
+    Ok(LoadReport { ticks, rejected })
+}
+
+/// Streams ticks from any reader to `on_tick`, one row at a time, without keeping
+/// them in memory. Same parsing rules as [`load_ticks_from_reader`]; returns the
+/// rows rejected in lenient mode.
+pub fn for_each_tick_from_reader<R: Read, F: FnMut(Tick)>(
+    reader: R,
+    options: &CsvOptions,
+    mut on_tick: F,
+) -> Result<Vec<RejectedRow>, String> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+
+    let mut rejected = Vec::new();
+    let mut columns: Option<TickColumns> = None;
+
+    for (i, result) in rdr.records().enumerate() {
+        let record = result.map_err(|err| format!("Failed to read record {}: {}", i + 1, err))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(i + 1);
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+
+        let columns = match &columns {
+            Some(columns) => columns,
+            None => {
+                let is_header = match options.header {
+                    HeaderMode::Present => true,
+                    HeaderMode::Absent => false,
+                    HeaderMode::Auto => match options.columns.resolve(None) {
+                        Ok(positional) => !positional.has_numbers(&record, options.decimal),
+                        Err(_) => true,
+                    },
+                };
+                if is_header {
+                    columns = Some(options.columns.resolve(Some(&record))?);
+                    continue;
+                }
+                columns.insert(options.columns.resolve(None)?)
+            }
+        };
+
+        match columns.parse(&record, line, options.decimal) {
+            Ok(tick) => on_tick(tick),
+            Err(reason) => match options.mode {
+                ParseMode::Strict => return Err(reason),
+                ParseMode::Lenient => rejected.push(RejectedRow { line, reason }),
+            },
+        }
+    }
+
+    Ok(rejected)
+}
+
+/// Reads market data ticks from a CSV file with the given options.
+pub fn load_ticks(path: &str, options: &CsvOptions) -> Result<LoadReport, String> {
+    let file = File::open(path).map_err(|_| "Failed to open file.")?;
+    load_ticks_from_reader(BufReader::new(file), options)
+}
+
+/// Streams market data ticks from a CSV file to `on_tick` (see [`for_each_tick_from_reader`]).
+pub fn for_each_tick<F: FnMut(Tick)>(path: &str, options: &CsvOptions, on_tick: F) -> Result<Vec<RejectedRow>, String> {
+    let file = File::open(path).map_err(|_| "Failed to open file.")?;
+    for_each_tick_from_reader(BufReader::new(file), options, on_tick)
+}
+
+/// Reads market data ticks from a CSV file with the default options.
+///
+/// When the first line is a header, named columns are mapped onto tick fields
+/// (see [`TickColumns::from_header`]), so extra or reordered columns are fine:
+///
+/// ```text
+/// timestamp,symbol,bid,ask,bid_size,ask_size,last,volume
+/// 2024-03-15T10:30:00,PETR4,37.10,37.12,500,800,37.11,1200
+/// ```
+///
+/// Without a header the original positional layout `bid,ask,volume` is used.
+pub fn read_ticks(path: &str) -> Result<Vec<Tick>, String> {
+    load_ticks(path, &CsvOptions::default()).map(|report| report.ticks)
+}
 
 /// Reads a CSV file with format: bid,ask,volume
 /// Returns vectors of (bids, asks, volumes)
-pub fn read_csv_to_vectors(path: &str) -> Result<(Vec<f64>, Vec<f64>, Vec<f64>), String> {
+///
+/// A header line (e.g. `Bid,Ask,Volume`) is skipped when its bid, ask and volume are not numeric.
+pub fn read_csv_to_vectors(path: &str) -> Result<MarketColumns, String> {
+    read_ticks(path).map(|ticks| tick::columns(&ticks))
+}
+//-------------------------------------------------------------------------------------
+
+// POSITIONS --------------------------------------------------------------------------
+/// Positions read from a file, plus the rows rejected in lenient mode.
+#[derive(Debug, Clone, Default)]
+pub struct PositionsReport {
+    pub positions: Vec<AssetPosition>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+/// Accepted header names (case-insensitive) for each `AssetPosition` field.
+const ASSET_ID_NAMES: &[&str] = &["asset_id", "asset", "symbol", "ticker", "instrument"];
+const BUY_PRICE_NAMES: &[&str] = &["buy_price", "entry_price", "preco_compra"];
+const SELL_PRICE_NAMES: &[&str] = &["sell_price", "exit_price", "preco_venda"];
+const CONTRACTED_NAMES: &[&str] = &["contracted_volume", "volume", "quantity", "qty"];
+const REALIZED_NAMES: &[&str] = &["realized_volume", "realized_qty", "filled_volume"];
+const MARKET_PRICE_NAMES: &[&str] = &["market_price", "mark", "mark_price"];
+const COSTS_NAMES: &[&str] = &["additional_costs", "costs", "fees", "commission"];
+const POSITION_SIDE_NAMES: &[&str] = &["side", "direction"];
+const BORROW_FEE_NAMES: &[&str] = &["borrow_fee", "borrow_cost"];
+const CURRENCY_NAMES: &[&str] = &["currency", "ccy", "moeda"];
+const ASSET_CLASS_NAMES: &[&str] = &["asset_class", "class"];
+const TAGS_NAMES: &[&str] = &["tags", "tag", "labels"];
+const MULTIPLIER_NAMES: &[&str] = &["multiplier", "contract_multiplier", "contract_size", "point_value"];
+
+/// Column indexes of the `AssetPosition` fields, from a header line.
+struct PositionColumns {
+    asset_id: usize,
+    buy_price: usize,
+    sell_price: usize,
+    contracted_volume: usize,
+    market_price: usize,
+    realized_volume: Option<usize>,
+    additional_costs: Option<usize>,
+    side: Option<usize>,
+    borrow_fee: Option<usize>,
+    currency: Option<usize>,
+    asset_class: Option<usize>,
+    tags: Option<usize>,
+    multiplier: Option<usize>,
+}
+
+impl PositionColumns {
+    fn from_header(header: &StringRecord) -> Result<PositionColumns, String> {
+        let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.trim().to_ascii_lowercase().as_str()));
+        let require = |names: &[&str]| find(names).ok_or_else(|| format!("Missing '{}' column in header", names[0]));
+
+        Ok(PositionColumns {
+            asset_id: require(ASSET_ID_NAMES)?,
+            buy_price: require(BUY_PRICE_NAMES)?,
+            sell_price: require(SELL_PRICE_NAMES)?,
+            contracted_volume: require(CONTRACTED_NAMES)?,
+            market_price: require(MARKET_PRICE_NAMES)?,
+            realized_volume: find(REALIZED_NAMES),
+            additional_costs: find(COSTS_NAMES),
+            side: find(POSITION_SIDE_NAMES),
+            borrow_fee: find(BORROW_FEE_NAMES),
+            currency: find(CURRENCY_NAMES),
+            asset_class: find(ASSET_CLASS_NAMES),
+            tags: find(TAGS_NAMES),
+            multiplier: find(MULTIPLIER_NAMES),
+        })
+    }
+
+    /// Builds a position from one record, listing every invalid field of the row.
+    fn parse(&self, record: &StringRecord, line: usize, decimal: DecimalSeparator) -> Result<AssetPosition, String> {
+        let mut errors: Vec<String> = Vec::new();
+        let field = |idx: usize| record.get(idx).map(str::trim).unwrap_or("");
+        let text = |idx: Option<usize>| idx.map(field).filter(|s| !s.is_empty()).map(str::to_string);
+
+        let mut number = |idx: Option<usize>, name: &str, required: bool| -> Option<f64> {
+            let raw = idx.map(field).unwrap_or("");
+            if raw.is_empty() {
+                if required {
+                    errors.push(format!("missing {}", name));
+                }
+                return None;
+            }
+            match decimal.parse(raw).filter(|v| v.is_finite()) {
+                Some(value) => Some(value),
+                None => {
+                    errors.push(format!("invalid {} '{}'", name, raw));
+                    None
+                }
+            }
+        };
+
+        let buy_price = number(Some(self.buy_price), "buy_price", true);
+        let sell_price = number(Some(self.sell_price), "sell_price", true);
+        let contracted_volume = number(Some(self.contracted_volume), "contracted_volume", true);
+        let market_price = number(Some(self.market_price), "market_price", true);
+        let realized_volume = number(self.realized_volume, "realized_volume", false);
+        let additional_costs = number(self.additional_costs, "additional_costs", false);
+        let borrow_fee = number(self.borrow_fee, "borrow_fee", false);
+        let multiplier = number(self.multiplier, "multiplier", false);
+
+        let asset_id = field(self.asset_id).to_string();
+        if asset_id.is_empty() {
+            errors.push("missing asset_id".to_string());
+        }
+        for (value, name) in [(buy_price, "buy_price"), (sell_price, "sell_price"), (market_price, "market_price")] {
+            if value.is_some_and(|v| v < 0.0) {
+                errors.push(format!("negative {}", name));
+            }
+        }
+        for (value, name) in [(additional_costs, "additional_costs"), (borrow_fee, "borrow_fee")] {
+            if value.is_some_and(|v| v < 0.0) {
+                errors.push(format!("negative {}", name));
+            }
+        }
+        if multiplier.is_some_and(|v| v <= 0.0) {
+            errors.push("multiplier must be positive".to_string());
+        }
+        let side = match text(self.side) {
+            Some(side) => side.parse::<PositionSide>().map_err(|err| errors.push(err)).ok(),
+            // Without a side, a negative contracted volume is a short
+            None => contracted_volume.map(PositionSide::from_quantity),
+        };
+        if side == Some(PositionSide::Long) && contracted_volume.is_some_and(|v| v < 0.0) {
+            errors.push("negative contracted_volume on a long side".to_string());
+        }
+        if realized_volume.is_some_and(|v| v < 0.0) {
+            errors.push("negative realized_volume".to_string());
+        }
+        if let (Some(PositionSide::Short), Some(realized), Some(contracted)) = (side, realized_volume, contracted_volume) {
+            if realized > contracted.abs() {
+                errors.push("realized_volume above contracted_volume on a short".to_string());
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(format!("Line {}: {}", line, errors.join("; ")));
+        }
+
+        Ok(AssetPosition {
+            asset_id,
+            buy_price: buy_price.unwrap_or_default(),
+            sell_price: sell_price.unwrap_or_default(),
+            contracted_volume: contracted_volume.unwrap_or_default().abs(),
+            realized_volume,
+            market_price: market_price.unwrap_or_default(),
+            additional_costs: additional_costs.unwrap_or(0.0),
+            side: side.unwrap_or_default(),
+            borrow_fee: borrow_fee.unwrap_or(0.0),
+            currency: text(self.currency),
+            asset_class: text(self.asset_class),
+            tags: text(self.tags)
+                .map(|tags| {
+                    tags.split([';', '|'])
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            multiplier,
+        })
+    }
+}
+
+/// Reads positions from any reader. The first line is a header naming the columns,
+/// in any order (extra columns are ignored):
+///
+/// ```text
+/// asset_id,buy_price,sell_price,contracted_volume,realized_volume,market_price,additional_costs
+/// PETR4,37.10,38.00,1000,,37.90,12.50
+/// ```
+///
+/// `asset_id`, `buy_price`, `sell_price`, `contracted_volume` and `market_price` are
+/// required; `realized_volume`, `additional_costs`, `side` (long/short), `borrow_fee`,
+/// `currency`, `asset_class`, `tags` (separated by `;` or `|`) and `multiplier` are optional
+/// and may be left empty. Without a side, a negative `contracted_volume` is a short of that size;
+/// with a `long` side it is an error. `realized_volume` may not be negative, and on a short it may
+/// not exceed `contracted_volume` (covering more than was sold); on a long it may, as an
+/// over-delivery valued at `market_price`.
+///
+/// Every row is validated and all its problems are reported at once. In strict mode
+/// the file is rejected with the errors of every invalid row; in lenient mode invalid
+/// rows are skipped and listed in [`PositionsReport::rejected`]. The delimiter and
+/// decimal separator of `options` apply; its tick column mapping does not.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::csv_loader::{load_positions_from_reader, CsvOptions, ParseMode};
+///
+/// let data = "asset_id,market_price,buy_price,sell_price,contracted_volume\n\
+///             PETR4,37.9,37.1,38.0,1000\n\
+///             VALE3,61.0,-1,x,100\n";
+/// let options = CsvOptions { mode: ParseMode::Lenient, ..CsvOptions::default() };
+///
+/// let report = load_positions_from_reader(data.as_bytes(), &options).unwrap();
+/// assert_eq!(report.positions.len(), 1);
+/// assert_eq!(report.rejected[0].reason, "Line 3: invalid sell_price 'x'; negative buy_price");
+///
+/// // Conflicting values are reported, not corrected
+/// let data = "asset_id,market_price,buy_price,sell_price,contracted_volume,side,realized_volume\n\
+///             PETR4,37.9,37.1,38.0,-1000,long,\n\
+///             VALE3,61.0,60.0,62.0,100,short,150\n\
+///             ITUB4,30.0,29.0,31.0,100,,-50\n";
+/// let report = load_positions_from_reader(data.as_bytes(), &options).unwrap();
+/// assert!(report.positions.is_empty());
+/// assert_eq!(report.rejected[0].reason, "Line 2: negative contracted_volume on a long side");
+/// assert_eq!(report.rejected[1].reason, "Line 3: realized_volume above contracted_volume on a short");
+/// assert_eq!(report.rejected[2].reason, "Line 4: negative realized_volume");
+/// ```
+pub fn load_positions_from_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<PositionsReport, String> {
+    let mut rdr = ReaderBuilder::new()
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+
+    let header = rdr.headers().map_err(|err| format!("Failed to read header: {}", err))?.clone();
+    let columns = PositionColumns::from_header(&header)?;
+
+    let mut report = PositionsReport::default();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result.map_err(|err| format!("Failed to read record {}: {}", i + 1, err))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+
+        match columns.parse(&record, line, options.decimal) {
+            Ok(position) => report.positions.push(position),
+            Err(reason) => report.rejected.push(RejectedRow { line, reason }),
+        }
+    }
+
+    if options.mode == ParseMode::Strict && !report.rejected.is_empty() {
+        let reasons: Vec<&str> = report.rejected.iter().map(|row| row.reason.as_str()).collect();
+        return Err(format!("Invalid positions:\n{}", reasons.join("\n")));
+    }
+    Ok(report)
+}
+
+/// Reads positions from a CSV file with the given options (see [`load_positions_from_reader`]).
+pub fn load_positions(path: &str, options: &CsvOptions) -> Result<PositionsReport, String> {
+    let file = File::open(path).map_err(|_| "Failed to open file.")?;
+    load_positions_from_reader(BufReader::new(file), options)
+}
+
+/// Reads a positions CSV file with the default options: any invalid row is an error.
+pub fn read_positions(path: &str) -> Result<Vec<AssetPosition>, String> {
+    load_positions(path, &CsvOptions::default()).map(|report| report.positions)
+}
+//-------------------------------------------------------------------------------------
+
+const SIDE_NAMES: &[&str] = &["side", "direction", "buy_sell"];
+const FILL_PRICE_NAMES: &[&str] = &["price", "fill_price", "exec_price"];
+const FEE_NAMES: &[&str] = &["fees", "fee", "commission", "costs"];
+
+/// Reads a trade fills CSV file with a header naming its columns, in any order:
+///
+/// ```text
+/// timestamp,asset_id,side,quantity,price,fees
+/// 2024-03-15T10:30:00,PETR4,buy,100,37.10,1.50
+/// 2024-03-15T11:00:00,PETR4,sell,60,37.40,1.00
+/// ```
+///
+/// `asset_id` (or `symbol`), `side` (buy/sell), `quantity` and `price` are required;
+/// `timestamp` and `fees` are optional. Fills are returned in file order. The delimiter
+/// and decimal separator of `options` apply.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::csv_loader::{load_fills_from_reader, CsvOptions, DecimalSeparator};
+///
+/// let data = "asset_id;side;quantity;price\nPETR4;buy;1.000;37,10\n";
+/// let options = CsvOptions { delimiter: b';', decimal: DecimalSeparator::Comma, ..CsvOptions::default() };
+/// let fills = load_fills_from_reader(data.as_bytes(), &options).unwrap();
+/// assert_eq!((fills[0].quantity, fills[0].price), (1000.0, 37.1));
+///
+/// let data = "asset_id,side,quantity,price\nPETR4,buy,100,inf\n";
+/// assert!(load_fills_from_reader(data.as_bytes(), &CsvOptions::default()).is_err());
+/// ```
+pub fn load_fills_from_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<Vec<Fill>, String> {
+    let mut rdr = ReaderBuilder::new()
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+
+    let header = rdr.headers().map_err(|err| format!("Failed to read header: {}", err))?.clone();
+    let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.trim().to_ascii_lowercase().as_str()));
+    let require = |names: &[&str], field: &str| find(names).ok_or_else(|| format!("Missing '{}' column in header", field));
+
+    let asset = require(SYMBOL_NAMES, "asset_id")?;
+    let side = require(SIDE_NAMES, "side")?;
+    let quantity = require(VOLUME_NAMES, "quantity")?;
+    let price = require(FILL_PRICE_NAMES, "price")?;
+    let timestamp = find(TIMESTAMP_NAMES);
+    let fees = find(FEE_NAMES);
+
+    let mut fills = Vec::new();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result.map_err(|err| format!("Failed to read record {}: {}", i + 1, err))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+
+        let field = |idx: usize| record.get(idx).map(str::trim).unwrap_or("");
+        let number = |idx: usize, name: &str| {
+            options
+                .decimal
+                .parse(field(idx))
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid {} '{}' at line {}", name, field(idx), line))
+        };
+
+        fills.push(Fill {
+            asset_id: match field(asset) {
+                "" => return Err(format!("Missing asset_id at line {}", line)),
+                id => id.to_string(),
+            },
+            side: field(side).parse::<Side>().map_err(|err| format!("{} at line {}", err, line))?,
+            quantity: number(quantity, "quantity")?,
+            price: number(price, "price")?,
+            fees: match fees {
+                Some(idx) if !field(idx).is_empty() => number(idx, "fees")?,
+                _ => 0.0,
+            },
+            timestamp: match timestamp {
+                Some(idx) if !field(idx).is_empty() => Some(
+                    parse_timestamp(field(idx))
+                        .ok_or_else(|| format!("Invalid timestamp '{}' at line {}", field(idx), line))?,
+                ),
+                _ => None,
+            },
+        });
+    }
+
+    Ok(fills)
+}
+
+/// Reads trade fills from a CSV file with the given options (see [`load_fills_from_reader`]).
+pub fn load_fills(path: &str, options: &CsvOptions) -> Result<Vec<Fill>, String> {
     let file = File::open(path).map_err(|_| "Failed to open file.")?;
-    let reader = BufReader::new(file);
+    load_fills_from_reader(BufReader::new(file), options)
+}
 
-    let mut bids = Vec::new();
-    let mut asks = Vec::new();
-    let mut volumes = Vec::new();
+/// Reads a trade fills CSV file with the default options.
+pub fn read_fills(path: &str) -> Result<Vec<Fill>, String> {
+    load_fills(path, &CsvOptions::default())
+}
 
-    for (i, line) in reader.lines().enumerate() {
-        let line = line.map_err(|_| format!("Failed to read line {}", i + 1))?;
-        let parts: Vec<&str> = line.trim().split(',').collect();
+/// Reads an FX rates CSV file into a table whose cross rates go through `base`.
+///
+/// The header names either a `pair` column (`USD/BRL`, `USDBRL`) or `base` and `quote`
+/// columns, plus a `rate` column: the price of one unit of the base currency in the quote.
+///
+/// ```text
+/// pair,rate
+/// USD/BRL,5.02
+/// EUR/USD,1.09
+/// ```
+///
+/// The delimiter and decimal separator of `options` apply.
+pub fn load_fx_rates_from_reader<R: Read>(reader: R, base: &str, options: &CsvOptions) -> Result<FxRates, String> {
+    let mut rdr = ReaderBuilder::new()
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(reader);
 
-        if parts.len() != 3 {
-            return Err(format!("Line {} is not properly formatted (needs 3 values)", i + 1));
+    let header = rdr.headers().map_err(|err| format!("Failed to read header: {}", err))?.clone();
+    let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.trim().to_ascii_lowercase().as_str()));
+    let currencies = find(&["base", "from"]).zip(find(&["quote", "to"]));
+    let pair = find(&["pair", "currency_pair", "symbol"]);
+    if currencies.is_none() && pair.is_none() {
+        return Err("Missing 'pair' (or 'base' and 'quote') column in header".to_string());
+    }
+    let rate = find(&["rate", "price", "fx_rate"]).ok_or("Missing 'rate' column in header")?;
+
+    let mut rates = FxRates::new(base);
+    for (i, result) in rdr.records().enumerate() {
+        let record = result.map_err(|err| format!("Failed to read record {}: {}", i + 1, err))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
         }
+        let field = |idx: usize| record.get(idx).map(str::trim).unwrap_or("");
 
-        let bid = parts[0].trim().parse::<f64>().map_err(|_| format!("Invalid bid at line {}", i + 1))?;
-        let ask = parts[1].trim().parse::<f64>().map_err(|_| format!("Invalid ask at line {}", i + 1))?;
-        let volume = parts[2].trim().parse::<f64>().map_err(|_| format!("Invalid volume at line {}", i + 1))?;
+        let (from, to) = match currencies {
+            Some((from, to)) => (field(from).to_string(), field(to).to_string()),
+            None => {
+                let pair = pair.map(field).unwrap_or("");
+                match pair.split_once(['/', '-']) {
+                    Some((from, to)) => (from.to_string(), to.to_string()),
+                    None if pair.len() == 6 && pair.is_ascii() => (pair[..3].to_string(), pair[3..].to_string()),
+                    None => return Err(format!("Invalid currency pair '{}' at line {}", pair, line)),
+                }
+            }
+        };
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("Missing currency at line {}", line));
+        }
 
-        bids.push(bid);
-        asks.push(ask);
-        volumes.push(volume);
+        let value = options
+            .decimal
+            .parse(field(rate))
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("Invalid rate '{}' at line {}", field(rate), line))?;
+        rates.insert(&from, &to, value).map_err(|err| format!("{} at line {}", err, line))?;
     }
 
-    Ok((bids, asks, volumes))
+    Ok(rates)
+}
+
+/// Reads an FX rates CSV file with the given options (see [`load_fx_rates_from_reader`]).
+pub fn load_fx_rates(path: &str, base: &str, options: &CsvOptions) -> Result<FxRates, String> {
+    let file = File::open(path).map_err(|_| "Failed to open file.")?;
+    load_fx_rates_from_reader(BufReader::new(file), base, options)
+}
+
+/// Reads an FX rates CSV file with the default options.
+pub fn read_fx_rates(path: &str, base: &str) -> Result<FxRates, String> {
+    load_fx_rates(path, base, &CsvOptions::default())
+}
+
+/// Reads an instrument registry CSV file.
+///
+/// The header names a `symbol` column and any of `asset_class`, `multiplier`,
+/// `tick_size`, `tick_value` and `currency`. Without a multiplier it is derived as
+/// `tick_value / tick_size`, else taken as `1`.
+///
+/// ```text
+/// symbol,asset_class,multiplier,tick_size,tick_value,currency
+/// WINFUT,futures,0.20,5,1.00,BRL
+/// WDOFUT,futures,10,0.5,5.00,BRL
+/// ES,futures,50,0.25,12.50,USD
+/// ```
+///
+/// The delimiter and decimal separator of `options` apply.
+pub fn load_instruments_from_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<InstrumentRegistry, String> {
+    let mut rdr = ReaderBuilder::new()
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+
+    let header = rdr.headers().map_err(|err| format!("Failed to read header: {}", err))?.clone();
+    let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.trim().to_ascii_lowercase().as_str()));
+    let symbol = find(&["symbol", "asset_id", "ticker", "instrument"]).ok_or("Missing 'symbol' column in header")?;
+    let asset_class = find(ASSET_CLASS_NAMES);
+    let multiplier = find(MULTIPLIER_NAMES);
+    let tick_size = find(&["tick_size", "tick"]);
+    let tick_value = find(&["tick_value"]);
+    let currency = find(CURRENCY_NAMES);
+
+    let mut registry = InstrumentRegistry::new();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result.map_err(|err| format!("Failed to read record {}: {}", i + 1, err))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let field = |idx: Option<usize>| idx.and_then(|idx| record.get(idx)).map(str::trim).unwrap_or("");
+        let text = |idx: Option<usize>| Some(field(idx)).filter(|s| !s.is_empty()).map(str::to_string);
+        let number = |idx: Option<usize>, name: &str| match field(idx) {
+            "" => Ok(None),
+            raw => options
+                .decimal
+                .parse(raw)
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid {} '{}' at line {}", name, raw, line)),
+        };
+
+        let symbol = text(Some(symbol)).ok_or_else(|| format!("Missing symbol at line {}", line))?;
+        let tick_size = number(tick_size, "tick_size")?;
+        let tick_value = number(tick_value, "tick_value")?;
+        let multiplier = match (number(multiplier, "multiplier")?, tick_size, tick_value) {
+            (Some(multiplier), _, _) => multiplier,
+            (None, Some(size), Some(value)) => value / size,
+            _ => 1.0,
+        };
+
+        registry.insert(Instrument {
+            symbol,
+            asset_class: text(asset_class),
+            multiplier,
+            tick_size,
+            tick_value,
+            currency: text(currency),
+        });
+    }
+
+    Ok(registry)
+}
+
+/// Reads an instrument registry CSV file with the given options (see [`load_instruments_from_reader`]).
+pub fn load_instruments(path: &str, options: &CsvOptions) -> Result<InstrumentRegistry, String> {
+    let file = File::open(path).map_err(|_| "Failed to open file.")?;
+    load_instruments_from_reader(BufReader::new(file), options)
+}
+
+/// Reads an instrument registry CSV file with the default options.
+pub fn read_instruments(path: &str) -> Result<InstrumentRegistry, String> {
+    load_instruments(path, &CsvOptions::default())
 }
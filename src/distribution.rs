@@ -0,0 +1,337 @@
+//! Distribution statistics: higher moments, quantiles and a normality test.
+//!
+//! Mean and variance say nothing about the shape of a distribution. Spreads and
+//! returns are typically skewed and fat-tailed, which this module makes visible:
+//! min/max/range, quartiles and arbitrary quantiles, skewness, excess kurtosis
+//! and the Jarque–Bera normality test, each unweighted or volume-weighted.
+//!
+//! Moments are accumulated in one pass by [`OnlineMoments`], which the
+//! streaming summary also uses; quantiles need the whole sample and are only
+//! computed in batch.
+
+use crate::stats::StatsError;
+
+/// Checks values and weights: same length, not empty, free of NaN, positive total weight.
+fn check_weighted(values: &[f64], weights: &[f64]) -> Result<(), StatsError> {
+    if values.len() != weights.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    if values.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if values.iter().chain(weights.iter()).any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    if weights.iter().sum::<f64>() <= 0.0 {
+        return Err(StatsError::ZeroVolume);
+    }
+    Ok(())
+}
+
+// QUANTILES --------------------------------------------------------------------------
+/// Quantile `q` (0 to 1) with linear interpolation between order statistics
+/// (the default of R, NumPy and Excel `PERCENTILE.INC`).
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::distribution::quantile;
+///
+/// let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+/// assert_eq!(quantile(&values, 0.5).unwrap(), 3.0);
+/// assert_eq!(quantile(&values, 0.25).unwrap(), 2.0);
+/// assert_eq!(quantile(&values, 0.9).unwrap(), 4.6);
+/// ```
+pub fn quantile(values: &[f64], q: f64) -> Result<f64, StatsError> {
+    if values.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Ok(sorted_quantile(&sorted, q))
+}
+
+/// Quantile of already sorted values.
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Volume-weighted quantile `q` (0 to 1): each value stands at the middle of its share
+/// of the total weight, and quantiles in between are interpolated linearly.
+///
+/// With equal weights this is the midpoint (Hazen) quantile, which differs slightly
+/// from [`quantile`] in the tails.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::distribution::weighted_quantile;
+///
+/// // Most of the volume traded at 10.2
+/// let prices = [10.0, 10.2, 10.4];
+/// let volumes = [100.0, 800.0, 100.0];
+/// assert_eq!(weighted_quantile(&prices, &volumes, 0.5).unwrap(), 10.2);
+/// ```
+pub fn weighted_quantile(values: &[f64], weights: &[f64], q: f64) -> Result<f64, StatsError> {
+    check_weighted(values, weights)?;
+    let sorted = sorted_pairs(values, weights);
+    Ok(sorted_weighted_quantile(&sorted, q))
+}
+
+/// (value, weight) pairs with a positive weight, sorted by value.
+fn sorted_pairs(values: &[f64], weights: &[f64]) -> Vec<(f64, f64)> {
+    let mut pairs: Vec<(f64, f64)> = values
+        .iter()
+        .zip(weights.iter())
+        .filter(|(_, w)| **w > 0.0)
+        .map(|(v, w)| (*v, *w))
+        .collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    pairs
+}
+
+fn sorted_weighted_quantile(sorted: &[(f64, f64)], q: f64) -> f64 {
+    let total: f64 = sorted.iter().map(|(_, w)| w).sum();
+    let q = q.clamp(0.0, 1.0);
+    let mut cumulative = 0.0;
+    let mut previous: Option<(f64, f64)> = None;
+
+    for (value, weight) in sorted {
+        let position = (cumulative + weight / 2.0) / total;
+        cumulative += weight;
+        if q <= position {
+            return match previous {
+                Some((prev_value, prev_position)) => {
+                    prev_value + (value - prev_value) * (q - prev_position) / (position - prev_position)
+                }
+                None => *value,
+            };
+        }
+        previous = Some((*value, position));
+    }
+    sorted[sorted.len() - 1].0
+}
+//-------------------------------------------------------------------------------------
+
+// SUMMARY ----------------------------------------------------------------------------
+/// Jarque–Bera test of normality: `JB = n/6 · (S² + K²/4)`, chi-squared with 2 degrees
+/// of freedom under normality.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JarqueBera {
+    pub statistic: f64,
+    /// Probability of a statistic at least this large if the data were normal
+    pub p_value: f64,
+}
+
+impl JarqueBera {
+    /// Test from the sample size (effective size when weighted), skewness and excess kurtosis.
+    pub fn new(n: f64, skewness: f64, excess_kurtosis: f64) -> JarqueBera {
+        let statistic = n / 6.0 * (skewness.powi(2) + excess_kurtosis.powi(2) / 4.0);
+        JarqueBera {
+            statistic,
+            // Survival function of the chi-squared distribution with 2 degrees of freedom
+            p_value: (-statistic / 2.0).exp(),
+        }
+    }
+
+    /// Whether normality is rejected at significance level `alpha` (e.g. `0.05`).
+    pub fn rejects_normality(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// First, second (median) and third quartiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quartiles {
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    /// Interquartile range `q3 - q1`
+    pub iqr: f64,
+}
+
+/// Shape of a series of values (population moments).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distribution {
+    /// Number of values (with a positive weight, when weighted)
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub range: f64,
+    /// Mean (volume-weighted mean, i.e. VWAP, when weighted)
+    pub mean: f64,
+    /// Third standardized moment; `0` for a constant series
+    pub skewness: f64,
+    /// Fourth standardized moment minus 3 (0 for a normal distribution); `0` for a constant series
+    pub excess_kurtosis: f64,
+    pub jarque_bera: JarqueBera,
+    /// `None` when computed in one pass (streaming)
+    pub quartiles: Option<Quartiles>,
+}
+
+impl Distribution {
+    /// Human-readable reading of the shape, from the Jarque–Bera test at 5%.
+    pub fn interpretation(&self) -> &'static str {
+        match (self.jarque_bera.rejects_normality(0.05), self.excess_kurtosis > 0.0) {
+            (false, _) => "Consistent with a normal distribution",
+            (true, true) => "Non-normal with fat tails → extreme moves more frequent than normal",
+            (true, false) => "Non-normal with thin tails → bounded or clustered values",
+        }
+    }
+}
+
+/// Distribution of `values`, all weighing the same.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::distribution::distribution;
+///
+/// let spreads = [0.01, 0.01, 0.02, 0.01, 0.01, 0.02, 0.01, 0.15];
+/// let d = distribution(&spreads).unwrap();
+/// assert_eq!(d.quartiles.unwrap().median, 0.01);
+/// assert!(d.skewness > 2.0);       // one wide spread in the right tail
+/// assert!(d.excess_kurtosis > 2.0);
+/// ```
+pub fn distribution(values: &[f64]) -> Result<Distribution, StatsError> {
+    let mut distribution = weighted_moments(values, &vec![1.0; values.len()])?;
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    distribution.quartiles = Some(quartiles(|q| sorted_quantile(&sorted, q)));
+    Ok(distribution)
+}
+
+/// Distribution of `values` weighted by `weights` (volumes). Moments are weighted and the
+/// Jarque–Bera test uses the effective sample size `(Σw)² / Σw²`.
+///
+/// Quartiles are always [`weighted_quantile`]s (midpoint convention), so they move
+/// continuously with the weights; with equal weights they are Hazen quantiles, not the
+/// linear (R-7) ones of [`distribution`].
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::distribution::weighted_distribution;
+///
+/// let prices = [10.0, 11.0, 12.0, 13.0];
+/// let equal = weighted_distribution(&prices, &[100.0; 4]).unwrap().quartiles.unwrap();
+/// let nudged = weighted_distribution(&prices, &[100.0, 100.0, 100.0, 100.0000001]).unwrap().quartiles.unwrap();
+/// assert_eq!((equal.q1, equal.median, equal.q3), (10.5, 11.5, 12.5));
+/// assert!((nudged.iqr - equal.iqr).abs() < 1e-6);
+/// ```
+pub fn weighted_distribution(values: &[f64], weights: &[f64]) -> Result<Distribution, StatsError> {
+    let mut distribution = weighted_moments(values, weights)?;
+    let sorted = sorted_pairs(values, weights);
+    distribution.quartiles = Some(quartiles(|q| sorted_weighted_quantile(&sorted, q)));
+    Ok(distribution)
+}
+
+/// Weighted moments, min and max of `values`, without quartiles.
+fn weighted_moments(values: &[f64], weights: &[f64]) -> Result<Distribution, StatsError> {
+    check_weighted(values, weights)?;
+    let mut moments = OnlineMoments::new();
+    for (value, weight) in values.iter().zip(weights.iter()) {
+        moments.update(*value, *weight)?;
+    }
+    moments.distribution()
+}
+
+fn quartiles(quantile: impl Fn(f64) -> f64) -> Quartiles {
+    let (q1, median, q3) = (quantile(0.25), quantile(0.5), quantile(0.75));
+    Quartiles {
+        q1,
+        median,
+        q3,
+        iqr: q3 - q1,
+    }
+}
+//-------------------------------------------------------------------------------------
+
+// ONLINE -----------------------------------------------------------------------------
+/// One-pass (streaming) weighted moments, min and max.
+///
+/// Keeps weighted power sums of the values shifted by the first one, so memory is
+/// constant; the resulting [`Distribution`] has no quartiles.
+#[derive(Debug, Clone, Default)]
+pub struct OnlineMoments {
+    count: usize,
+    shift: f64,
+    weight: f64,
+    weight_sq: f64,
+    sums: [f64; 4],
+    min: f64,
+    max: f64,
+}
+
+impl OnlineMoments {
+    pub fn new() -> OnlineMoments {
+        OnlineMoments::default()
+    }
+
+    /// Adds a value with its weight (1 for unweighted). Values with zero weight are ignored.
+    pub fn update(&mut self, value: f64, weight: f64) -> Result<(), StatsError> {
+        if value.is_nan() || weight.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        if weight <= 0.0 {
+            return Ok(());
+        }
+        if self.count == 0 {
+            self.shift = value;
+            self.min = value;
+            self.max = value;
+        }
+        self.count += 1;
+        self.weight += weight;
+        self.weight_sq += weight * weight;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        let d = value - self.shift;
+        let mut power = weight;
+        for sum in self.sums.iter_mut() {
+            power *= d;
+            *sum += power;
+        }
+        Ok(())
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Moments, min/max and Jarque–Bera of the values so far.
+    pub fn distribution(&self) -> Result<Distribution, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::EmptyInput);
+        }
+        let [r1, r2, r3, r4] = self.sums.map(|s| s / self.weight);
+        let m = r1;
+        let mu2 = (r2 - m * m).max(0.0);
+        let mu3 = r3 - 3.0 * m * r2 + 2.0 * m.powi(3);
+        let mu4 = r4 - 4.0 * m * r3 + 6.0 * m * m * r2 - 3.0 * m.powi(4);
+
+        // A constant series (up to rounding) has no defined shape
+        let scale = self.max - self.min;
+        let (skewness, excess_kurtosis) = if mu2 <= (scale * 1e-12).powi(2) {
+            (0.0, 0.0)
+        } else {
+            (mu3 / mu2.powf(1.5), mu4 / (mu2 * mu2) - 3.0)
+        };
+        let effective_n = self.weight * self.weight / self.weight_sq;
+
+        Ok(Distribution {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            range: self.max - self.min,
+            mean: self.shift + m,
+            skewness,
+            excess_kurtosis,
+            jarque_bera: JarqueBera::new(effective_n, skewness, excess_kurtosis),
+            quartiles: None,
+        })
+    }
+}
+//-------------------------------------------------------------------------------------
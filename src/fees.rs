@@ -0,0 +1,486 @@
+//! Fee and commission schedules.
+//!
+//! A [`FeeConfig`] is loaded from a JSON file and holds one [`FeeSchedule`] per
+//! symbol, asset class, or `"default"`. A schedule is a list of components
+//! (brokerage, exchange fees, regulatory fees, taxes, ...) and computes the cost
+//! of a trade leg; [`FeeConfig::position_costs`] and [`FeeConfig::fill_fees`]
+//! turn it into `AssetPosition::additional_costs` and `Fill::fees`.
+//!
+//! ```text
+//! {
+//!   "default": [
+//!     {"name": "brokerage", "type": "per_share", "rate": 0.005, "min": 1.00, "max": 50.0},
+//!     {"name": "SEC fee", "type": "percent", "rate": 0.0000278, "category": "regulatory"}
+//!   ],
+//!   "equity": [
+//!     {"name": "corretagem", "type": "fixed", "amount": 4.90},
+//!     {"name": "emolumentos", "type": "percent", "bps": 0.5, "category": "exchange"},
+//!     {"name": "ISS", "type": "tax", "rate": 0.05, "on": ["corretagem"]}
+//!   ],
+//!   "WDOFUT": [
+//!     {"name": "brokerage", "type": "tiered", "measure": "quantity",
+//!      "tiers": [{"up_to": 10, "rate": 1.20}, {"up_to": 100, "rate": 0.90}, {"rate": 0.50}]}
+//!   ]
+//! }
+//! ```
+//!
+//! Component types:
+//! - `fixed`: `amount` per trade leg (ticket fee)
+//! - `per_share` / `per_contract`: `rate` × quantity
+//! - `percent`: `rate` (fraction, or `bps`) × notional
+//! - `tiered`: the tier whose `up_to` covers the quantity (or notional, with
+//!   `"measure": "notional"`) gives the `rate` applied to the whole trade; tiers are
+//!   listed by ascending `up_to` and only the last one may omit it
+//! - `tax`: `rate` × the components named in `on`, which must be listed before it (all
+//!   non-tax components when omitted)
+//!
+//! Every component accepts `min` and `max` (`min` no greater than `max`) and an optional `category`
+//! (`commission`, `exchange`, `regulatory`, `tax`; `tax` components default to `tax`,
+//! the others to `commission`).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+use serde_json::Value;
+
+use crate::instruments::{Instrument, InstrumentRegistry};
+use crate::ledger::Fill;
+use crate::pnl::AssetPosition;
+
+/// Schedule used when neither the symbol nor the asset class has one.
+pub const DEFAULT_SCHEDULE: &str = "default";
+
+/// What a fee component is charged for, for reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeCategory {
+    /// Broker commission / corretagem
+    Commission,
+    /// Exchange fees / emolumentos
+    Exchange,
+    /// Regulatory fees (SEC, FINRA TAF, CVM, ...)
+    Regulatory,
+    /// Taxes on fees (ISS, ...) or on the trade
+    Tax,
+}
+
+impl FeeCategory {
+    fn parse(raw: &str) -> Result<FeeCategory, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "commission" | "brokerage" => Ok(FeeCategory::Commission),
+            "exchange" => Ok(FeeCategory::Exchange),
+            "regulatory" => Ok(FeeCategory::Regulatory),
+            "tax" => Ok(FeeCategory::Tax),
+            other => Err(format!("Unknown fee category '{}' (expected commission, exchange, regulatory or tax)", other)),
+        }
+    }
+}
+
+impl fmt::Display for FeeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FeeCategory::Commission => "commission",
+            FeeCategory::Exchange => "exchange",
+            FeeCategory::Regulatory => "regulatory",
+            FeeCategory::Tax => "tax",
+        })
+    }
+}
+
+/// What picks the tier of a tiered fee, and what its rate multiplies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TierMeasure {
+    /// Rate per share/contract, tier by quantity
+    Quantity,
+    /// Rate as a fraction of notional, tier by notional
+    Notional,
+}
+
+/// One tier: applies up to `up_to` (inclusive); `None` is the last, open-ended tier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tier {
+    pub up_to: Option<f64>,
+    pub rate: f64,
+}
+
+/// How a fee component is computed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeeKind {
+    Fixed(f64),
+    PerUnit(f64),
+    Percent(f64),
+    Tiered { measure: TierMeasure, tiers: Vec<Tier> },
+    /// Rate on other components (by name; all non-tax components when empty)
+    Tax { rate: f64, on: Vec<String> },
+}
+
+/// One line of a fee schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeComponent {
+    pub name: String,
+    pub category: FeeCategory,
+    pub kind: FeeKind,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// Amount charged by one component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeCharge {
+    pub name: String,
+    pub category: FeeCategory,
+    pub amount: f64,
+}
+
+/// Fees of one trade leg, per component.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FeeBreakdown {
+    pub charges: Vec<FeeCharge>,
+}
+
+impl FeeBreakdown {
+    pub fn total(&self) -> f64 {
+        self.charges.iter().map(|c| c.amount).fold(0.0, |total, amount| total + amount)
+    }
+
+    /// Total of the components of one category.
+    pub fn total_of(&self, category: FeeCategory) -> f64 {
+        self.charges
+            .iter()
+            .filter(|c| c.category == category)
+            .fold(0.0, |total, c| total + c.amount)
+    }
+}
+
+/// Components charged on every trade leg of an instrument.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FeeSchedule {
+    pub components: Vec<FeeComponent>,
+}
+
+impl FeeSchedule {
+    /// Fees of one trade leg of `quantity` (sign ignored) at `price`, the value of one
+    /// unit (price × contract multiplier for futures).
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::fees::FeeConfig;
+    ///
+    /// let config = FeeConfig::from_json(r#"{"default": [
+    ///     {"name": "brokerage", "type": "per_share", "rate": 0.01, "min": 5.0},
+    ///     {"name": "exchange", "type": "percent", "bps": 1, "category": "exchange"},
+    ///     {"name": "tax", "type": "tax", "rate": 0.10, "on": ["brokerage"]}
+    /// ]}"#).unwrap();
+    ///
+    /// let fees = config.schedule_for("AAPL", None).unwrap().compute(100.0, 50.0);
+    /// // brokerage max(1.00, 5.00) + exchange 0.01% of 5000 + 10% tax on brokerage
+    /// assert!((fees.total() - (5.0 + 0.5 + 0.5)).abs() < 1e-12);
+    ///
+    /// // Tiered by quantity, with a cap on the brokerage
+    /// let config = FeeConfig::from_json(r#"{"default": [
+    ///     {"name": "brokerage", "type": "tiered", "max": 60.0,
+    ///      "tiers": [{"up_to": 10, "rate": 1.20}, {"up_to": 100, "rate": 0.90}, {"rate": 0.50}]}
+    /// ]}"#).unwrap();
+    /// let schedule = config.schedule_for("WDOFUT", None).unwrap();
+    /// assert!((schedule.compute(10.0, 5.0).total() - 12.0).abs() < 1e-12); // up_to is inclusive
+    /// assert!((schedule.compute(50.0, 5.0).total() - 45.0).abs() < 1e-12);
+    /// assert_eq!(schedule.compute(100.0, 5.0).total(), 60.0); // 90 clamped to max
+    /// assert_eq!(schedule.compute(101.0, 5.0).total(), 50.5); // open-ended tier
+    /// ```
+    pub fn compute(&self, quantity: f64, price: f64) -> FeeBreakdown {
+        let quantity = quantity.abs();
+        let notional = quantity * price;
+        let mut breakdown = FeeBreakdown::default();
+
+        // Taxes last, so they can refer to any other component
+        let (taxes, others): (Vec<&FeeComponent>, Vec<&FeeComponent>) =
+            self.components.iter().partition(|c| matches!(c.kind, FeeKind::Tax { .. }));
+
+        for component in others.into_iter().chain(taxes) {
+            let raw = match &component.kind {
+                FeeKind::Fixed(amount) => *amount,
+                FeeKind::PerUnit(rate) => rate * quantity,
+                FeeKind::Percent(rate) => rate * notional,
+                FeeKind::Tiered { measure, tiers } => {
+                    let (measured, base) = match measure {
+                        TierMeasure::Quantity => (quantity, quantity),
+                        TierMeasure::Notional => (notional, notional),
+                    };
+                    let tier = tiers
+                        .iter()
+                        .find(|t| t.up_to.is_none_or(|up_to| measured <= up_to))
+                        .or(tiers.last());
+                    tier.map(|t| t.rate * base).unwrap_or(0.0)
+                }
+                FeeKind::Tax { rate, on } => {
+                    let base = breakdown
+                        .charges
+                        .iter()
+                        .filter(|c| match on.is_empty() {
+                            true => c.category != FeeCategory::Tax,
+                            false => on.iter().any(|name| name.eq_ignore_ascii_case(&c.name)),
+                        })
+                        .fold(0.0, |total, c| total + c.amount);
+                    rate * base
+                }
+            };
+
+            let amount = match (component.min, component.max) {
+                (Some(min), _) if raw < min => min,
+                (_, Some(max)) if raw > max => max,
+                _ => raw,
+            };
+            breakdown.charges.push(FeeCharge {
+                name: component.name.clone(),
+                category: component.category,
+                amount,
+            });
+        }
+
+        breakdown
+    }
+}
+
+/// Fee schedules by symbol, asset class and [`DEFAULT_SCHEDULE`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FeeConfig {
+    schedules: HashMap<String, FeeSchedule>,
+}
+
+/// Number at `key` of a component, with `name` for error messages.
+fn number(component: &Value, key: &str, name: &str) -> Result<Option<f64>, String> {
+    match component.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_f64()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Some)
+            .ok_or_else(|| format!("Fee '{}': '{}' must be a non-negative number", name, key)),
+    }
+}
+
+fn parse_component(component: &Value) -> Result<FeeComponent, String> {
+    let name = component
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("Every fee component needs a \"name\"")?
+        .to_string();
+    let kind_name = component
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Fee '{}': missing \"type\"", name))?;
+    let rate = || -> Result<f64, String> {
+        if let Some(bps) = number(component, "bps", &name)? {
+            return Ok(bps / 10_000.0);
+        }
+        number(component, "rate", &name)?.ok_or_else(|| format!("Fee '{}': missing \"rate\"", name))
+    };
+
+    let kind = match kind_name.to_ascii_lowercase().as_str() {
+        "fixed" | "ticket" => FeeKind::Fixed(
+            number(component, "amount", &name)?.ok_or_else(|| format!("Fee '{}': missing \"amount\"", name))?,
+        ),
+        "per_share" | "per_contract" | "per_unit" => FeeKind::PerUnit(rate()?),
+        "percent" | "notional" => FeeKind::Percent(rate()?),
+        "tiered" => {
+            let measure = match component.get("measure").and_then(Value::as_str).unwrap_or("quantity") {
+                "quantity" => TierMeasure::Quantity,
+                "notional" => TierMeasure::Notional,
+                other => return Err(format!("Fee '{}': unknown tier measure '{}' (expected quantity or notional)", name, other)),
+            };
+            let tiers = component
+                .get("tiers")
+                .and_then(Value::as_array)
+                .filter(|tiers| !tiers.is_empty())
+                .ok_or_else(|| format!("Fee '{}': \"tiers\" must be a non-empty list", name))?
+                .iter()
+                .map(|tier| {
+                    Ok(Tier {
+                        up_to: number(tier, "up_to", &name)?,
+                        rate: number(tier, "rate", &name)?.ok_or_else(|| format!("Fee '{}': every tier needs a \"rate\"", name))?,
+                    })
+                })
+                .collect::<Result<Vec<Tier>, String>>()?;
+            // The lookup takes the first tier that covers the trade, so bounds must ascend
+            let mut previous: Option<f64> = None;
+            for (i, tier) in tiers.iter().enumerate() {
+                match tier.up_to {
+                    None if i + 1 < tiers.len() => {
+                        return Err(format!("Fee '{}': only the last tier can omit \"up_to\"", name))
+                    }
+                    Some(up_to) if previous.is_some_and(|p| up_to <= p) => {
+                        return Err(format!("Fee '{}': tier \"up_to\" values must be in ascending order", name))
+                    }
+                    _ => previous = tier.up_to,
+                }
+            }
+            FeeKind::Tiered { measure, tiers }
+        }
+        "tax" => {
+            let on = match component.get("on") {
+                None | Some(Value::Null) => Vec::new(),
+                Some(Value::Array(names)) => names
+                    .iter()
+                    .map(|n| n.as_str().map(str::to_string))
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or_else(|| format!("Fee '{}': \"on\" must list component names", name))?,
+                Some(_) => return Err(format!("Fee '{}': \"on\" must be a list of component names", name)),
+            };
+            FeeKind::Tax { rate: rate()?, on }
+        }
+        other => return Err(format!("Fee '{}': unknown type '{}'", name, other)),
+    };
+
+    let category = match component.get("category").and_then(Value::as_str) {
+        Some(category) => FeeCategory::parse(category)?,
+        None if matches!(kind, FeeKind::Tax { .. }) => FeeCategory::Tax,
+        None => FeeCategory::Commission,
+    };
+
+    let (min, max) = (number(component, "min", &name)?, number(component, "max", &name)?);
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(format!("Fee '{}': \"min\" {} is above \"max\" {}", name, min, max));
+        }
+    }
+
+    Ok(FeeComponent {
+        category,
+        kind,
+        min,
+        max,
+        name,
+    })
+}
+
+impl FeeConfig {
+    /// Parses a fee config: a JSON object mapping a symbol, an asset class or `"default"`
+    /// to its list of components (see the module docs).
+    ///
+    /// Tiers must be listed by ascending `up_to`, and only the last one may omit it; `min`
+    /// may not exceed `max`; the `on` list of a tax names components listed before it.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::fees::FeeConfig;
+    ///
+    /// let open_ended_first = r#"{"default": [{"name": "b", "type": "tiered",
+    ///     "tiers": [{"rate": 0.50}, {"up_to": 10, "rate": 1.20}]}]}"#;
+    /// assert!(FeeConfig::from_json(open_ended_first).is_err());
+    ///
+    /// let descending = r#"{"default": [{"name": "b", "type": "tiered",
+    ///     "tiers": [{"up_to": 100, "rate": 0.90}, {"up_to": 10, "rate": 1.20}]}]}"#;
+    /// assert!(FeeConfig::from_json(descending).is_err());
+    ///
+    /// let min_above_max = r#"{"default": [{"name": "b", "type": "fixed", "amount": 1, "min": 10, "max": 5}]}"#;
+    /// assert!(FeeConfig::from_json(min_above_max).is_err());
+    ///
+    /// let on_not_a_list = r#"{"default": [{"name": "corretagem", "type": "fixed", "amount": 4.9},
+    ///     {"name": "ISS", "type": "tax", "rate": 0.05, "on": "corretagem"}]}"#;
+    /// assert!(FeeConfig::from_json(on_not_a_list).is_err());
+    ///
+    /// let on_unknown = r#"{"default": [{"name": "corretagem", "type": "fixed", "amount": 4.9},
+    ///     {"name": "ISS", "type": "tax", "rate": 0.05, "on": ["corretage"]}]}"#;
+    /// let err = FeeConfig::from_json(on_unknown).unwrap_err();
+    /// assert!(err.contains("'corretage'"));
+    /// ```
+    pub fn from_json(text: &str) -> Result<FeeConfig, String> {
+        let doc: Value = serde_json::from_str(text).map_err(|err| format!("Invalid fee config: {}", err))?;
+        let object = doc.as_object().ok_or("Fee config must be a JSON object of schedules")?;
+
+        let mut schedules = HashMap::new();
+        for (key, components) in object {
+            let components = components
+                .as_array()
+                .ok_or_else(|| format!("Schedule '{}' must be a list of fee components", key))?
+                .iter()
+                .map(parse_component)
+                .collect::<Result<Vec<FeeComponent>, String>>()?;
+            // A tax is computed on components listed before it
+            for (i, component) in components.iter().enumerate() {
+                let FeeKind::Tax { on, .. } = &component.kind else { continue };
+                if let Some(unknown) = on
+                    .iter()
+                    .find(|name| !components[..i].iter().any(|c| c.name.eq_ignore_ascii_case(name)))
+                {
+                    return Err(format!(
+                        "Fee '{}' in schedule '{}': \"on\" names '{}', which is not a component listed before it",
+                        component.name, key, unknown
+                    ));
+                }
+            }
+            schedules.insert(key.to_ascii_lowercase(), FeeSchedule { components });
+        }
+        Ok(FeeConfig { schedules })
+    }
+
+    /// Reads a fee config file.
+    pub fn load(path: &str) -> Result<FeeConfig, String> {
+        let text = fs::read_to_string(path).map_err(|_| "Failed to open file.")?;
+        FeeConfig::from_json(&text)
+    }
+
+    /// Schedule of an instrument: by symbol, else by asset class, else the default one.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::fees::FeeConfig;
+    ///
+    /// let config = FeeConfig::from_json(r#"{
+    ///     "default": [{"name": "default", "type": "fixed", "amount": 1.0}],
+    ///     "equity": [{"name": "equity", "type": "fixed", "amount": 2.0}],
+    ///     "PETR4": [{"name": "petr4", "type": "fixed", "amount": 3.0}]
+    /// }"#).unwrap();
+    /// let fee = |symbol: &str, class: Option<&str>| config.schedule_for(symbol, class).unwrap().compute(1.0, 1.0).total();
+    ///
+    /// assert_eq!(fee("petr4", Some("equity")), 3.0); // symbol first, case-insensitive
+    /// assert_eq!(fee("VALE3", Some("Equity")), 2.0); // then the asset class
+    /// assert_eq!(fee("VALE3", Some("future")), 1.0); // then the default
+    /// assert_eq!(fee("VALE3", None), 1.0);
+    /// ```
+    pub fn schedule_for(&self, symbol: &str, asset_class: Option<&str>) -> Option<&FeeSchedule> {
+        let lookup = |key: &str| self.schedules.get(&key.to_ascii_lowercase());
+        lookup(symbol)
+            .or_else(|| asset_class.and_then(lookup))
+            .or_else(|| lookup(DEFAULT_SCHEDULE))
+    }
+
+    /// Fees of a position's round trip (opening and closing legs), or `None` when no
+    /// schedule applies.
+    pub fn position_costs(&self, position: &AssetPosition) -> Option<f64> {
+        let schedule = self.schedule_for(&position.asset_id, position.asset_class.as_deref())?;
+        // Contract value per unit, so percentages apply to the full notional
+        let (quantity, multiplier) = (position.contracted_volume, position.multiplier());
+        Some(
+            schedule.compute(quantity, position.buy_price * multiplier).total()
+                + schedule.compute(quantity, position.sell_price * multiplier).total(),
+        )
+    }
+
+    /// Fees of one fill, or `None` when no schedule applies. The fill's `instrument`,
+    /// when known, gives its asset class and contract multiplier.
+    pub fn fill_fees(&self, fill: &Fill, instrument: Option<&Instrument>) -> Option<f64> {
+        let asset_class = instrument.and_then(|i| i.asset_class.as_deref());
+        let multiplier = instrument.map(|i| i.multiplier).unwrap_or(1.0);
+        let schedule = self.schedule_for(&fill.asset_id, asset_class)?;
+        Some(schedule.compute(fill.quantity, fill.price * multiplier).total())
+    }
+
+    /// Replaces `additional_costs` of every position that has a schedule with its computed fees.
+    pub fn apply_to_positions(&self, positions: &mut [AssetPosition]) {
+        for position in positions {
+            if let Some(costs) = self.position_costs(position) {
+                position.additional_costs = costs;
+            }
+        }
+    }
+
+    /// Replaces `fees` of every fill that has a schedule with its computed fees.
+    pub fn apply_to_fills(&self, fills: &mut [Fill], instruments: &InstrumentRegistry) {
+        for fill in fills {
+            if let Some(fees) = self.fill_fees(fill, instruments.get(&fill.asset_id)) {
+                fill.fees = fees;
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! FX rates and multi-currency P&L.
+//!
+//! Positions quoted in different currencies (USD stocks, EUR bonds, BRL
+//! futures, ...) give P&L numbers that cannot be added directly. An [`FxRates`]
+//! table converts amounts between currencies, deriving cross rates through a
+//! base currency, and [`consolidate`] sums `calculate_pnl` results in one
+//! reporting currency.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::pnl::{calculate_pnl, AssetPosition, PnLResult};
+
+/// Reasons why an amount cannot be converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FxError {
+    /// No direct, inverse or cross rate between the two currencies
+    MissingRate { from: String, to: String },
+}
+
+impl fmt::Display for FxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FxError::MissingRate { from, to } => write!(f, "No FX rate to convert {} to {}.", from, to),
+        }
+    }
+}
+
+impl std::error::Error for FxError {}
+
+/// Normalized currency code: trimmed, upper case.
+fn code(currency: &str) -> String {
+    currency.trim().to_ascii_uppercase()
+}
+
+/// Table of FX rates. A rate for `BASE/QUOTE` is the price of one unit of BASE in
+/// QUOTE (`USD/BRL = 5.0`: 1 USD = 5 BRL).
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::fx::FxRates;
+///
+/// let mut rates = FxRates::new("USD");
+/// rates.insert("USD", "BRL", 5.0).unwrap();
+/// rates.insert("EUR", "USD", 1.10).unwrap();
+///
+/// assert_eq!(rates.rate("BRL", "USD").unwrap(), 0.2);
+/// // EUR → BRL through USD
+/// assert!((rates.rate("EUR", "BRL").unwrap() - 5.5).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FxRates {
+    base: String,
+    rates: HashMap<(String, String), f64>,
+}
+
+impl FxRates {
+    /// Empty table whose cross rates go through `base` (e.g. "USD").
+    pub fn new(base: &str) -> FxRates {
+        FxRates {
+            base: code(base),
+            rates: HashMap::new(),
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Adds (or replaces) the rate of `base_currency/quote_currency`. Rates must be positive.
+    pub fn insert(&mut self, base_currency: &str, quote_currency: &str, rate: f64) -> Result<(), String> {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(format!("Invalid FX rate {} for {}/{}", rate, base_currency, quote_currency));
+        }
+        self.rates.insert((code(base_currency), code(quote_currency)), rate);
+        Ok(())
+    }
+
+    /// Direct or inverse rate, without going through the base currency.
+    fn pair_rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        if let Some(rate) = self.rates.get(&(from.to_string(), to.to_string())) {
+            return Some(*rate);
+        }
+        self.rates.get(&(to.to_string(), from.to_string())).map(|rate| 1.0 / rate)
+    }
+
+    /// Rate converting one unit of `from` into `to`: direct, inverse, or the cross
+    /// rate `from → base → to`.
+    pub fn rate(&self, from: &str, to: &str) -> Result<f64, FxError> {
+        let (from, to) = (code(from), code(to));
+
+        self.pair_rate(&from, &to)
+            .or_else(|| Some(self.pair_rate(&from, &self.base)? * self.pair_rate(&self.base, &to)?))
+            .ok_or(FxError::MissingRate { from, to })
+    }
+
+    /// Converts `amount` from one currency to another.
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Result<f64, FxError> {
+        Ok(amount * self.rate(from, to)?)
+    }
+}
+
+/// Currency of a position: its `currency` field, else the quote side of a pair-like
+/// `asset_id` ("EUR/BRL" → "BRL"), else `None` (taken as the reporting currency).
+pub fn position_currency(position: &AssetPosition) -> Option<String> {
+    position
+        .currency
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+        .map(code)
+        .or_else(|| position.asset_id.split_once('/').map(|(_, quote)| code(quote)))
+}
+
+/// P&L of one position, in its own currency and in the reporting currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertedPnl {
+    pub asset_id: String,
+    /// Position currency (the reporting currency when the position has none)
+    pub currency: String,
+    /// Rate applied (position currency → reporting currency)
+    pub rate: f64,
+    pub local: PnLResult,
+    pub reporting: PnLResult,
+}
+
+/// P&L of a set of positions, consolidated in one currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsolidatedPnl {
+    /// Reporting currency
+    pub currency: String,
+    pub positions: Vec<ConvertedPnl>,
+    /// Sum of the positions' P&L in the reporting currency
+    pub total: PnLResult,
+}
+
+/// Calculates the P&L of every position and converts it into `reporting_currency`.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::fx::{consolidate, FxRates};
+/// use quant_marketstat_ws::pnl::AssetPosition;
+///
+/// let mut rates = FxRates::new("USD");
+/// rates.insert("USD", "BRL", 5.0).unwrap();
+///
+/// let position = |id: &str, currency: &str| AssetPosition {
+///     asset_id: id.to_string(),
+///     buy_price: 10.0,
+///     sell_price: 11.0,
+///     contracted_volume: 100.0,
+///     currency: Some(currency.to_string()),
+///     ..Default::default()
+/// };
+/// let report = consolidate(&[position("AAPL", "USD"), position("PETR4", "BRL")], &rates, "BRL").unwrap();
+/// assert_eq!(report.total.pnl, 100.0 * 5.0 + 100.0);
+/// ```
+pub fn consolidate(positions: &[AssetPosition], rates: &FxRates, reporting_currency: &str) -> Result<ConsolidatedPnl, FxError> {
+    let currency = code(reporting_currency);
+    let mut converted = Vec::with_capacity(positions.len());
+    let mut total = PnLResult::default();
+
+    for position in positions {
+        let position_currency = position_currency(position).unwrap_or_else(|| currency.clone());
+        let rate = rates.rate(&position_currency, &currency)?;
+        let local = calculate_pnl(position);
+        let reporting = local.converted(rate);
+        total = total.add(&reporting);
+
+        converted.push(ConvertedPnl {
+            asset_id: position.asset_id.clone(),
+            currency: position_currency,
+            rate,
+            local,
+            reporting,
+        });
+    }
+
+    Ok(ConsolidatedPnl {
+        currency,
+        positions: converted,
+        total,
+    })
+}
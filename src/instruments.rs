@@ -0,0 +1,132 @@
+//! Instrument registry: contract specifications by symbol.
+//!
+//! One futures or options contract usually stands for a multiple of its quoted
+//! price: a point of the mini-index (WIN) is worth BRL 0.20, a point of the mini
+//! dollar (WDO) BRL 10, an ES point USD 50. An [`InstrumentRegistry`] holds the
+//! multiplier, tick size and tick value, currency and asset class of each
+//! symbol; [`InstrumentRegistry::apply_to_positions`] fills them into positions
+//! before `pnl::calculate_pnl`, and `Ledger::instruments` uses them for fills.
+
+use std::collections::HashMap;
+
+use crate::pnl::AssetPosition;
+
+/// Contract specification of one instrument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instrument {
+    pub symbol: String,
+    /// Asset class (e.g. "futures", "equity", "options")
+    pub asset_class: Option<String>,
+    /// Value of one point of price move for one contract (`1.0` for stocks)
+    pub multiplier: f64,
+    /// Minimum price increment
+    pub tick_size: Option<f64>,
+    /// Value of one tick for one contract (`tick_size × multiplier`)
+    pub tick_value: Option<f64>,
+    /// Currency of prices and P&L
+    pub currency: Option<String>,
+}
+
+impl Instrument {
+    /// Instrument with a multiplier and no other metadata.
+    pub fn new(symbol: &str, multiplier: f64) -> Instrument {
+        Instrument {
+            symbol: symbol.trim().to_string(),
+            asset_class: None,
+            multiplier,
+            tick_size: None,
+            tick_value: None,
+            currency: None,
+        }
+    }
+
+    /// Number of ticks in a price move (`None` without a tick size).
+    pub fn ticks(&self, price_move: f64) -> Option<f64> {
+        self.tick_size.filter(|size| *size > 0.0).map(|size| price_move / size)
+    }
+
+    /// Price rounded to the nearest tick (unchanged without a tick size).
+    pub fn round_to_tick(&self, price: f64) -> f64 {
+        match self.tick_size.filter(|size| *size > 0.0) {
+            Some(size) => (price / size).round() * size,
+            None => price,
+        }
+    }
+}
+
+/// Instruments by symbol (case-insensitive).
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::instruments::{Instrument, InstrumentRegistry};
+/// use quant_marketstat_ws::pnl::{calculate_pnl, AssetPosition};
+///
+/// let mut registry = InstrumentRegistry::new();
+/// registry.insert(Instrument::new("WDOFUT", 10.0));
+///
+/// // 2 mini dollar contracts bought at 5000 and sold at 5010.5
+/// let mut positions = vec![AssetPosition {
+///     asset_id: "WDOFUT".to_string(),
+///     buy_price: 5000.0,
+///     sell_price: 5010.5,
+///     contracted_volume: 2.0,
+///     ..Default::default()
+/// }];
+/// registry.apply_to_positions(&mut positions);
+///
+/// assert_eq!(calculate_pnl(&positions[0]).pnl, 10.5 * 10.0 * 2.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+}
+
+/// Registry key of a symbol.
+fn key(symbol: &str) -> String {
+    symbol.trim().to_ascii_uppercase()
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> InstrumentRegistry {
+        InstrumentRegistry::default()
+    }
+
+    /// Adds (or replaces) an instrument.
+    pub fn insert(&mut self, instrument: Instrument) {
+        self.instruments.insert(key(&instrument.symbol), instrument);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(&key(symbol))
+    }
+
+    /// Contract multiplier of a symbol, `1.0` when unknown.
+    pub fn multiplier(&self, symbol: &str) -> f64 {
+        self.get(symbol).map(|instrument| instrument.multiplier).unwrap_or(1.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    /// Fills the multiplier, currency and asset class of every registered position
+    /// where the position does not set them itself.
+    pub fn apply_to_positions(&self, positions: &mut [AssetPosition]) {
+        for position in positions {
+            let Some(instrument) = self.get(&position.asset_id) else { continue };
+            if position.multiplier.is_none() {
+                position.multiplier = Some(instrument.multiplier);
+            }
+            if position.currency.is_none() {
+                position.currency = instrument.currency.clone();
+            }
+            if position.asset_class.is_none() {
+                position.asset_class = instrument.asset_class.clone();
+            }
+        }
+    }
+}
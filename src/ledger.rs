@@ -0,0 +1,530 @@
+//! Trade ledger: buy/sell fills matched into lots.
+//!
+//! `pnl::AssetPosition` describes one round trip at a single buy and sell
+//! price. A [`Ledger`] instead takes the actual sequence of (partial) fills per
+//! asset, keeps the open lots, and matches every sell against them with the
+//! chosen [`CostMethod`]. Each match becomes a [`ClosedLot`] with its realized
+//! P&L; what is still open is marked at a market price for the unrealized P&L.
+//!
+//! Short selling is opt-in ([`Ledger::allow_short`]): a sell beyond the open
+//! long position then opens a short lot, later covered by buys, and short lots
+//! can be charged a borrow fee ([`Ledger::borrow_rate`]).
+//!
+//! Quantities are in contracts: with an instrument registry
+//! ([`Ledger::instruments`]) P&L and borrow fees are scaled by each asset's
+//! contract multiplier.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::instruments::InstrumentRegistry;
+use crate::pnl::PositionSide;
+use crate::tick::Timestamp;
+
+/// Quantities below this are treated as zero (float residue of partial fills).
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+/// Day count basis of the borrow rate (ACT/360, as in securities lending).
+const BORROW_DAY_BASIS: f64 = 360.0;
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// Reasons why a fill cannot be recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedgerError {
+    /// Quantity is zero, negative or not finite
+    InvalidQuantity { asset_id: String },
+    /// Price or fees are negative or not finite
+    InvalidPrice { asset_id: String },
+    /// A sell larger than the open long position, with short selling disabled
+    Oversell { asset_id: String, requested: f64, available: f64 },
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::InvalidQuantity { asset_id } => {
+                write!(f, "Invalid fill for {}: quantity must be positive.", asset_id)
+            }
+            LedgerError::InvalidPrice { asset_id } => {
+                write!(f, "Invalid fill for {}: price and fees must be non-negative numbers.", asset_id)
+            }
+            LedgerError::Oversell { asset_id, requested, available } => write!(
+                f,
+                "Cannot sell {} of {}: only {} open.",
+                requested, asset_id, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// Direction of a fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl FromStr for Side {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Side, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "buy" | "b" | "compra" => Ok(Side::Buy),
+            "sell" | "s" | "venda" => Ok(Side::Sell),
+            other => Err(format!("Unknown side '{}' (expected buy or sell)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        })
+    }
+}
+
+/// Lot matching method: which open lots a sell closes.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::ledger::{CostMethod, Fill, Ledger, Side};
+///
+/// let fill = |side, quantity, price| Fill {
+///     asset_id: "PETR4".to_string(), side, quantity, price, fees: 0.0, timestamp: None,
+/// };
+/// // 100 @ 10 and 100 @ 12 bought, 100 sold @ 13: (method, realized, cost of what is left)
+/// for (method, realized, average_cost) in [
+///     (CostMethod::Fifo, 300.0, 12.0),        // closes the lot @ 10
+///     (CostMethod::Lifo, 100.0, 10.0),        // closes the lot @ 12
+///     (CostMethod::AverageCost, 200.0, 11.0), // closes 100 of the pool @ 11
+/// ] {
+///     let mut ledger = Ledger::new(method);
+///     ledger.record(&fill(Side::Buy, 100.0, 10.0)).unwrap();
+///     ledger.record(&fill(Side::Buy, 100.0, 12.0)).unwrap();
+///     ledger.record(&fill(Side::Sell, 100.0, 13.0)).unwrap();
+///
+///     let report = ledger.asset_report("PETR4", None).unwrap();
+///     assert_eq!(report.realized_pnl, realized, "{}", method);
+///     assert_eq!(report.open_quantity, 100.0);
+///     assert_eq!(report.average_cost, Some(average_cost));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CostMethod {
+    /// Oldest lots first
+    #[default]
+    Fifo,
+    /// Newest lots first
+    Lifo,
+    /// One pooled lot at the weighted-average cost
+    AverageCost,
+}
+
+impl FromStr for CostMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CostMethod, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fifo" => Ok(CostMethod::Fifo),
+            "lifo" => Ok(CostMethod::Lifo),
+            "average" | "avg" | "average-cost" => Ok(CostMethod::AverageCost),
+            other => Err(format!("Unknown cost method '{}' (expected fifo, lifo or average)", other)),
+        }
+    }
+}
+
+impl fmt::Display for CostMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CostMethod::Fifo => "fifo",
+            CostMethod::Lifo => "lifo",
+            CostMethod::AverageCost => "average",
+        })
+    }
+}
+
+/// One execution (full or partial fill).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub asset_id: String,
+    pub side: Side,
+    /// Executed quantity (positive; the direction is `side`)
+    pub quantity: f64,
+    pub price: f64,
+    /// Commissions and fees of this fill
+    pub fees: f64,
+    pub timestamp: Option<Timestamp>,
+}
+
+/// Part of a position still open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    /// Signed quantity: positive for a long lot, negative for a short lot
+    pub quantity: f64,
+    /// Opening price (buy price of a long, sale price of a short)
+    pub price: f64,
+    /// Opening fees still attached to this quantity
+    pub fees: f64,
+    /// Opening time; for the pooled lot of `CostMethod::AverageCost`, the quantity-weighted
+    /// opening time of the fills pooled into it
+    pub timestamp: Option<Timestamp>,
+}
+
+/// A quantity bought and later sold (or sold short and later bought back), with its realized P&L.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedLot {
+    pub asset_id: String,
+    pub side: PositionSide,
+    /// Closed quantity (positive for both sides)
+    pub quantity: f64,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub opened: Option<Timestamp>,
+    pub closed: Option<Timestamp>,
+    /// Share of the buy and sell fees for this quantity
+    pub fees: f64,
+    /// Borrow fee of a short lot for the time it was open (zero for longs)
+    pub borrow_fee: f64,
+    /// `(sell_price - buy_price) × quantity - fees - borrow_fee`
+    pub realized_pnl: f64,
+}
+
+/// Per-asset totals of a ledger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetReport {
+    pub asset_id: String,
+    /// Sum of the closed lots' realized P&L (net of fees)
+    pub realized_pnl: f64,
+    /// Signed open quantity (negative for a short position)
+    pub open_quantity: f64,
+    /// Weighted-average opening price of the open lots
+    pub average_cost: Option<f64>,
+    pub market_price: Option<f64>,
+    /// `(market_price - average_cost) × open_quantity` (so a short gains when the price falls);
+    /// `None` without a market price
+    pub unrealized_pnl: Option<f64>,
+}
+
+/// Borrow fee of closing `quantity` of a short `lot` at `closed` (zero for longs or without timestamps).
+fn borrow_fee(rate: f64, lot: &Lot, quantity: f64, multiplier: f64, closed: Option<Timestamp>) -> f64 {
+    match (lot.quantity < 0.0, lot.timestamp, closed) {
+        (true, Some(opened), Some(closed)) if closed > opened => {
+            let days = (closed - opened) as f64 / MS_PER_DAY;
+            rate * lot.price * quantity * multiplier * days / BORROW_DAY_BASIS
+        }
+        _ => 0.0,
+    }
+}
+
+/// Open lots of one asset.
+#[derive(Debug, Clone, Default)]
+struct Book {
+    lots: VecDeque<Lot>,
+}
+
+impl Book {
+    fn open_quantity(&self) -> f64 {
+        self.lots.iter().map(|l| l.quantity).sum()
+    }
+
+    fn average_cost(&self) -> Option<f64> {
+        let quantity = self.open_quantity();
+        (quantity.abs() > QUANTITY_EPSILON).then(|| self.lots.iter().map(|l| l.quantity * l.price).sum::<f64>() / quantity)
+    }
+}
+
+/// Fills of any number of assets, matched into lots.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::ledger::{CostMethod, Fill, Ledger, Side};
+///
+/// let fill = |side, quantity, price| Fill {
+///     asset_id: "PETR4".to_string(), side, quantity, price, fees: 0.0, timestamp: None,
+/// };
+///
+/// let mut ledger = Ledger::new(CostMethod::Fifo);
+/// ledger.record(&fill(Side::Buy, 100.0, 10.0)).unwrap();
+/// ledger.record(&fill(Side::Buy, 100.0, 12.0)).unwrap();
+/// ledger.record(&fill(Side::Sell, 150.0, 13.0)).unwrap();
+///
+/// // FIFO: 100 @ 10 and 50 @ 12 closed at 13
+/// assert_eq!(ledger.closed_lots().len(), 2);
+/// let report = ledger.asset_report("PETR4", Some(11.0)).unwrap();
+/// assert_eq!(report.realized_pnl, 300.0 + 50.0);
+/// assert_eq!(report.unrealized_pnl, Some(-50.0));
+///
+/// // With short selling, the extra 100 sold open a short lot at 14
+/// let mut ledger = Ledger::new(CostMethod::Fifo).allow_short(true);
+/// ledger.record(&fill(Side::Buy, 100.0, 10.0)).unwrap();
+/// ledger.record(&fill(Side::Sell, 200.0, 14.0)).unwrap();
+/// let report = ledger.asset_report("PETR4", Some(13.0)).unwrap();
+/// assert_eq!(report.open_quantity, -100.0);
+/// assert_eq!(report.unrealized_pnl, Some(100.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    method: CostMethod,
+    allow_short: bool,
+    borrow_rate: f64,
+    instruments: InstrumentRegistry,
+    books: BTreeMap<String, Book>,
+    closed: Vec<ClosedLot>,
+}
+
+impl Ledger {
+    pub fn new(method: CostMethod) -> Ledger {
+        Ledger {
+            method,
+            ..Ledger::default()
+        }
+    }
+
+    /// Lets sells beyond the open long position open short lots (default: rejected as oversell).
+    pub fn allow_short(mut self, allow: bool) -> Ledger {
+        self.allow_short = allow;
+        self
+    }
+
+    /// Annual borrow rate charged on short lots (e.g. `0.02` for 2%), on the opening
+    /// notional and ACT/360 days between open and cover. Needs fill timestamps.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::ledger::{CostMethod, Fill, Ledger, Side};
+    ///
+    /// const DAY: i64 = 86_400_000;
+    /// let fill = |side, quantity, day| Fill {
+    ///     asset_id: "PETR4".to_string(), side, quantity, price: 10.0, fees: 0.0, timestamp: Some(day * DAY),
+    /// };
+    /// // 100 shorted on day 0, 100 more on day 9, all 200 covered on day 10:
+    /// // 0.36 × 10 × (100 × 10 + 100 × 1) / 360 = 11.00 of borrow, whatever the method
+    /// for method in [CostMethod::Fifo, CostMethod::Lifo, CostMethod::AverageCost] {
+    ///     let mut ledger = Ledger::new(method).allow_short(true).borrow_rate(0.36);
+    ///     ledger.record(&fill(Side::Sell, 100.0, 0)).unwrap();
+    ///     ledger.record(&fill(Side::Sell, 100.0, 9)).unwrap();
+    ///     ledger.record(&fill(Side::Buy, 200.0, 10)).unwrap();
+    ///
+    ///     let borrow: f64 = ledger.closed_lots().iter().map(|lot| lot.borrow_fee).sum();
+    ///     assert!((borrow - 11.0).abs() < 1e-9, "{}", method);
+    ///     assert!((ledger.asset_report("PETR4", None).unwrap().realized_pnl + 11.0).abs() < 1e-9);
+    /// }
+    /// ```
+    pub fn borrow_rate(mut self, rate: f64) -> Ledger {
+        self.borrow_rate = rate;
+        self
+    }
+
+    /// Contract multipliers of the assets (`1.0` for assets not in the registry).
+    pub fn instruments(mut self, instruments: InstrumentRegistry) -> Ledger {
+        self.instruments = instruments;
+        self
+    }
+
+    pub fn method(&self) -> CostMethod {
+        self.method
+    }
+
+    /// Records a fill. A fill against the open position closes lots (a sell closes long
+    /// lots, a buy covers short lots); the rest opens a new lot in the fill's direction.
+    /// An invalid fill, or a sell beyond the long position without short selling, is
+    /// rejected and changes nothing.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::ledger::{CostMethod, Fill, Ledger, LedgerError, Side};
+    ///
+    /// let fill = |side, quantity, price, fees| Fill {
+    ///     asset_id: "PETR4".to_string(), side, quantity, price, fees, timestamp: None,
+    /// };
+    ///
+    /// // Opening fees are shared pro rata between partial closes
+    /// let mut ledger = Ledger::new(CostMethod::Fifo);
+    /// ledger.record(&fill(Side::Buy, 100.0, 10.0, 10.0)).unwrap();
+    /// ledger.record(&fill(Side::Sell, 40.0, 11.0, 4.0)).unwrap();
+    /// ledger.record(&fill(Side::Sell, 60.0, 11.0, 3.0)).unwrap();
+    /// let lots = ledger.closed_lots();
+    /// assert_eq!((lots[0].fees, lots[0].realized_pnl), (4.0 + 4.0, 40.0 - 8.0));
+    /// assert_eq!((lots[1].fees, lots[1].realized_pnl), (6.0 + 3.0, 60.0 - 9.0));
+    /// assert!(ledger.open_lots("PETR4").is_empty());
+    ///
+    /// // Without short selling, an oversell is rejected and the ledger is unchanged
+    /// let mut ledger = Ledger::new(CostMethod::Fifo);
+    /// ledger.record(&fill(Side::Buy, 100.0, 10.0, 0.0)).unwrap();
+    /// assert_eq!(
+    ///     ledger.record(&fill(Side::Sell, 150.0, 12.0, 0.0)),
+    ///     Err(LedgerError::Oversell { asset_id: "PETR4".to_string(), requested: 150.0, available: 100.0 })
+    /// );
+    /// assert!(ledger.closed_lots().is_empty());
+    /// assert_eq!(ledger.asset_report("PETR4", None).unwrap().open_quantity, 100.0);
+    ///
+    /// // With it, the position flips: long to short, then back to long
+    /// let mut ledger = Ledger::new(CostMethod::Fifo).allow_short(true);
+    /// ledger.record(&fill(Side::Buy, 100.0, 10.0, 0.0)).unwrap();
+    /// ledger.record(&fill(Side::Sell, 150.0, 12.0, 0.0)).unwrap(); // closes 100, shorts 50 @ 12
+    /// assert_eq!(ledger.asset_report("PETR4", None).unwrap().open_quantity, -50.0);
+    /// ledger.record(&fill(Side::Buy, 80.0, 11.0, 0.0)).unwrap(); // covers 50, buys 30 @ 11
+    /// let report = ledger.asset_report("PETR4", None).unwrap();
+    /// assert_eq!(report.realized_pnl, 200.0 + 50.0);
+    /// assert_eq!((report.open_quantity, report.average_cost), (30.0, Some(11.0)));
+    /// ```
+    pub fn record(&mut self, fill: &Fill) -> Result<(), LedgerError> {
+        let asset_id = fill.asset_id.clone();
+        if !fill.quantity.is_finite() || fill.quantity <= 0.0 {
+            return Err(LedgerError::InvalidQuantity { asset_id });
+        }
+        if !(fill.price.is_finite() && fill.price >= 0.0 && fill.fees.is_finite() && fill.fees >= 0.0) {
+            return Err(LedgerError::InvalidPrice { asset_id });
+        }
+
+        let direction = match fill.side {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        };
+        let position = self.books.get(&asset_id).map(Book::open_quantity).unwrap_or(0.0);
+        let closing = if position * direction < 0.0 { fill.quantity.min(position.abs()) } else { 0.0 };
+        let opening = fill.quantity - closing;
+
+        if fill.side == Side::Sell && opening > QUANTITY_EPSILON && !self.allow_short {
+            return Err(LedgerError::Oversell {
+                asset_id,
+                requested: fill.quantity,
+                available: position.max(0.0),
+            });
+        }
+
+        let (method, borrow_rate) = (self.method, self.borrow_rate);
+        let multiplier = self.instruments.multiplier(&asset_id);
+        let book = self.books.entry(asset_id.clone()).or_default();
+
+        // Close lots against the open position
+        let mut remaining = closing;
+        while remaining > QUANTITY_EPSILON {
+            let lot = match method {
+                CostMethod::Lifo => book.lots.back_mut(),
+                CostMethod::Fifo | CostMethod::AverageCost => book.lots.front_mut(),
+            };
+            let Some(lot) = lot else { break };
+
+            let quantity = remaining.min(lot.quantity.abs());
+            let open_fees = lot.fees * quantity / lot.quantity.abs();
+            let fees = open_fees + fill.fees * quantity / fill.quantity;
+            let borrow_fee = borrow_fee(borrow_rate, lot, quantity, multiplier, fill.timestamp);
+            let side = PositionSide::from_quantity(lot.quantity);
+            let (buy_price, sell_price) = match side {
+                PositionSide::Long => (lot.price, fill.price),
+                PositionSide::Short => (fill.price, lot.price),
+            };
+
+            self.closed.push(ClosedLot {
+                asset_id: asset_id.clone(),
+                side,
+                quantity,
+                buy_price,
+                sell_price,
+                opened: lot.timestamp,
+                closed: fill.timestamp,
+                fees,
+                borrow_fee,
+                realized_pnl: (sell_price - buy_price) * quantity * multiplier - fees - borrow_fee,
+            });
+
+            lot.quantity -= lot.quantity.signum() * quantity;
+            lot.fees -= open_fees;
+            remaining -= quantity;
+            if lot.quantity.abs() <= QUANTITY_EPSILON {
+                match method {
+                    CostMethod::Lifo => book.lots.pop_back(),
+                    CostMethod::Fifo | CostMethod::AverageCost => book.lots.pop_front(),
+                };
+            }
+        }
+
+        // Open a lot with what is left
+        if opening > QUANTITY_EPSILON {
+            let lot = Lot {
+                quantity: direction * opening,
+                price: fill.price,
+                fees: fill.fees * opening / fill.quantity,
+                timestamp: fill.timestamp,
+            };
+            match (self.method, book.lots.front_mut()) {
+                // Average cost keeps a single pooled lot
+                (CostMethod::AverageCost, Some(pool)) => {
+                    let quantity = pool.quantity + lot.quantity;
+                    pool.price = (pool.quantity * pool.price + lot.quantity * lot.price) / quantity;
+                    // Quantity-weighted open time, so borrow accrues on each add from its own fill
+                    if let (Some(pooled), Some(added)) = (pool.timestamp, lot.timestamp) {
+                        let weighted = (pool.quantity * pooled as f64 + lot.quantity * added as f64) / quantity;
+                        pool.timestamp = Some(weighted.round() as Timestamp);
+                    }
+                    pool.quantity = quantity;
+                    pool.fees += lot.fees;
+                }
+                _ => book.lots.push_back(lot),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records fills in order, stopping at the first invalid one.
+    pub fn record_all(&mut self, fills: &[Fill]) -> Result<(), LedgerError> {
+        fills.iter().try_for_each(|fill| self.record(fill))
+    }
+
+    /// Lots closed so far, in the order they were closed.
+    pub fn closed_lots(&self) -> &[ClosedLot] {
+        &self.closed
+    }
+
+    /// Open lots of an asset, in matching order for FIFO (oldest first).
+    pub fn open_lots(&self, asset_id: &str) -> Vec<Lot> {
+        self.books
+            .get(asset_id)
+            .map(|book| book.lots.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Assets seen so far, sorted.
+    pub fn assets(&self) -> Vec<String> {
+        self.books.keys().cloned().collect()
+    }
+
+    /// Realized and unrealized P&L of one asset, marked at `market_price`.
+    pub fn asset_report(&self, asset_id: &str, market_price: Option<f64>) -> Option<AssetReport> {
+        let book = self.books.get(asset_id)?;
+        let open_quantity = book.open_quantity();
+        let average_cost = book.average_cost();
+
+        Some(AssetReport {
+            asset_id: asset_id.to_string(),
+            realized_pnl: self
+                .closed
+                .iter()
+                .filter(|lot| lot.asset_id == asset_id)
+                .fold(0.0, |total, lot| total + lot.realized_pnl),
+            open_quantity,
+            average_cost,
+            market_price,
+            unrealized_pnl: match (market_price, average_cost) {
+                (Some(market), Some(cost)) => Some((market - cost) * open_quantity * self.instruments.multiplier(asset_id)),
+                (Some(_), None) => Some(0.0),
+                _ => None,
+            },
+        })
+    }
+
+    /// Reports of every asset, each marked at its price in `market_prices` (when present).
+    pub fn report(&self, market_prices: &BTreeMap<String, f64>) -> Vec<AssetReport> {
+        self.books
+            .keys()
+            .filter_map(|asset_id| self.asset_report(asset_id, market_prices.get(asset_id).copied()))
+            .collect()
+    }
+}
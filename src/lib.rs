@@ -0,0 +1,52 @@
+//! # quant_marketstat_ws
+//!
+//! Quantitative market statistics engine: mean, VWAP, VWAP Group, variance,
+//! standard deviation (raw and volume-weighted), P&L, CSV loading, WebSocket
+//! streaming and text/JSON/CSV reports.
+//!
+//! The `quant_marketstat_ws` binary is a thin CLI on top of this library, so
+//! the same calculations can be embedded in other Rust programs:
+//!
+//! ```
+//! use quant_marketstat_ws::{pnl, stats};
+//!
+//! let prices = vec![10.0, 11.0, 12.0];
+//! let volumes = vec![100.0, 200.0, 150.0];
+//! let vwap = stats::vwap(&prices, &volumes).unwrap();
+//! assert!((vwap.value - 11.1111).abs() < 1e-4);
+//!
+//! let position = pnl::AssetPosition {
+//!     asset_id: "AAPL".to_string(),
+//!     buy_price: 10.0,
+//!     sell_price: 11.0,
+//!     contracted_volume: 100.0,
+//!     realized_volume: None,
+//!     market_price: 10.5,
+//!     additional_costs: 0.0,
+//!     ..Default::default()
+//! };
+//! assert_eq!(pnl::calculate_pnl(&position).pnl, 100.0);
+//! ```
+
+pub mod analyze_csv;
+pub mod book;
+pub mod csv_loader;
+pub mod distribution;
+pub mod fees;
+pub mod fx;
+pub mod instruments;
+pub mod ledger;
+pub mod mtm;
+pub mod online;
+pub mod performance;
+pub mod pnl;
+pub mod portfolio;
+pub mod report;
+pub mod returns;
+pub mod risk;
+pub mod rolling;
+pub mod spread;
+pub mod stats;
+pub mod stream;
+pub mod tick;
+pub mod volatility;
@@ -1,30 +1,207 @@
-mod stats; // <-- isso diz ao Rust: "existe um arquivo chamado stats.rs aqui"
-mod csv_loader; // <-- isso diz ao Rust: "existe um arquivo chamado csv_loader.rs aqui"
-mod analyze_csv; // <-- isso diz ao Rust: "existe um arquivo chamado analyze_csv.rs aqui"
-mod pnl; // <-- isso diz ao Rust: "existe um arquivo chamado pnl.rs aqui"
-use analyze_csv::analyze_csv;
-use pnl::{AssetPosition, calculate_pnl};
+// O motor (stats, pnl, csv) vive na biblioteca (lib.rs); o binário é só a interface.
+mod cli; // <-- subcomandos para uso em scripts (sem menu)
+mod session; // <-- dados carregados que ficam em memória entre as opções do menu
+use quant_marketstat_ws::analyze_csv::analyze_csv;
+use quant_marketstat_ws::csv_loader;
+use quant_marketstat_ws::performance::{self, PerformanceConfig};
+use quant_marketstat_ws::pnl::{AssetPosition, PositionSide, calculate_pnl};
+use quant_marketstat_ws::portfolio::{GroupBy, Portfolio};
+use quant_marketstat_ws::report::{self, OutputFormat};
+use quant_marketstat_ws::stats::{self, BookSizes, GroupPrice};
+use quant_marketstat_ws::tick::{self, Tick};
+use session::Session;
 
 
 use std::io;
 
 
+/// Helper function to read one trimmed line from stdin.
+/// Returns `None` on end of input.
+fn read_line() -> Option<String> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
 /// Helper function to read and parse comma-separated input into Vec<f64>
 fn read_and_parse_input(label: &str) -> Vec<f64> {
     println!("Enter {} separated by commas:", label);
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    let input = read_line().unwrap_or_default();
 
     input
-        .trim()
         .split(',')
         .filter_map(|s| s.trim().parse::<f64>().ok())
         .collect()
 }
 
-fn main() {
-    println!("Quantitative Market Analyzer - by Willy Sajbeni\n");
-    println!("Choose a function:");
+/// Reads one number, or `default` when the line is empty or invalid.
+fn read_number_or(label: &str, default: f64) -> f64 {
+    println!("{} (default {}):", label, default);
+    read_line().and_then(|s| s.parse::<f64>().ok()).unwrap_or(default)
+}
+
+/// Asks whether the data is population data (y/n).
+fn read_is_population() -> bool {
+    println!("Is this population data? (y/n):");
+    let type_input = read_line().unwrap_or_default();
+    matches!(type_input.to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Returns a single price series: from the loaded dataset (bid/ask/mid) or typed by hand.
+fn session_or_input_series(session: &Session, label: &str) -> Vec<f64> {
+    if session.is_loaded() {
+        println!("Using loaded dataset ({}).", session.status());
+        println!("Which series? (bid/ask/mid, or press Enter to type {} instead):", label);
+        let series = read_line().unwrap_or_default().to_lowercase();
+        if let Some(values) = session.column(&series) {
+            return values;
+        }
+    }
+    read_and_parse_input(label)
+}
+
+/// Returns (prices, volumes): a series from the loaded dataset plus its volumes, or typed by hand.
+fn session_or_input_priced(session: &Session) -> (Vec<f64>, Vec<f64>) {
+    if session.is_loaded() {
+        println!("Using loaded dataset ({}).", session.status());
+        println!("Which price series? (bid/ask/mid, or press Enter to type prices and volumes instead):");
+        let series = read_line().unwrap_or_default().to_lowercase();
+        if let Some(prices) = session.column(&series) {
+            return (prices, session.volumes());
+        }
+    }
+    (read_and_parse_input("prices"), read_and_parse_input("volumes"))
+}
+
+/// Returns the (bids, asks, volumes) of the session, asking for them when nothing is loaded.
+/// Manually entered data is kept in the session for the next options.
+fn session_or_input_market(session: &mut Session) -> Option<csv_loader::MarketColumns> {
+    if session.is_loaded() {
+        println!("Using loaded dataset ({}).", session.status());
+    } else {
+        let columns = read_market_columns()?;
+        session.replace(tick::from_columns(columns), "manual entry");
+    }
+    Some(session.columns())
+}
+
+/// Bid/ask sizes of the session when it has them and the microprice is wanted instead of the mid.
+fn read_microprice_sizes(session: &Session) -> Option<BookSizes> {
+    let sizes = session.book_sizes()?;
+    println!("Dataset has bid/ask sizes. Use the microprice instead of the mid? (y/n):");
+    let answer = read_line().unwrap_or_default();
+    matches!(answer.to_lowercase().as_str(), "y" | "yes").then_some(sizes)
+}
+
+/// Reads bid/ask/volume lists typed by hand, checking they have the same length.
+fn read_market_columns() -> Option<csv_loader::MarketColumns> {
+    let bids = read_and_parse_input("bid prices (compra)");
+    let asks = read_and_parse_input("ask prices (venda)");
+    let volumes = read_and_parse_input("volumes");
+
+    if bids.is_empty() || bids.len() != asks.len() || asks.len() != volumes.len() {
+        println!("All inputs must have the same length and not be empty.");
+        return None;
+    }
+    Some((bids, asks, volumes))
+}
+
+/// Reads new ticks for the session from a CSV path, or by hand when the path is left empty.
+/// Returns the ticks and a label describing their source.
+fn read_dataset() -> Option<(Vec<Tick>, String)> {
+    println!("Enter CSV file path (bid,ask,volume or named columns), or press Enter to type the values:");
+    let path = read_line()?;
+
+    if path.is_empty() {
+        return read_market_columns().map(|columns| (tick::from_columns(columns), "manual entry".to_string()));
+    }
+
+    match csv_loader::read_ticks(&path) {
+        Ok(ticks) => Some((ticks, path)),
+        Err(err) => {
+            println!("Error: {}", err);
+            None
+        }
+    }
+}
+
+/// Positions for option 10: from a CSV file with named columns, or typed by hand.
+fn read_positions() -> Option<Vec<AssetPosition>> {
+    println!("Positions CSV file (columns asset_id,buy_price,sell_price,contracted_volume,market_price,...),");
+    println!("or press Enter to type them by hand:");
+    let path = read_line()?;
+    if path.is_empty() {
+        return read_positions_by_hand();
+    }
+
+    let options = csv_loader::CsvOptions {
+        mode: csv_loader::ParseMode::Lenient,
+        ..Default::default()
+    };
+    match csv_loader::load_positions(&path, &options) {
+        Ok(report) => {
+            for row in &report.rejected {
+                println!("Skipped: {}", row.reason);
+            }
+            if report.positions.is_empty() {
+                println!("No valid positions found in '{}'.", path);
+                return None;
+            }
+            println!("Loaded {} positions from '{}'.", report.positions.len(), path);
+            Some(report.positions)
+        }
+        Err(err) => {
+            println!("Error: {} ({})", err, path);
+            None
+        }
+    }
+}
+
+/// Positions typed as comma-separated lists, one value per asset.
+fn read_positions_by_hand() -> Option<Vec<AssetPosition>> {
+    let buy_prices = read_and_parse_input("buy prices");
+    let sell_prices = read_and_parse_input("sell prices");
+    let volumes = read_and_parse_input("contracted volumes(amount) (negative for a short position)");
+    let market_prices = read_and_parse_input("market prices(price at time of analysis)");
+    let realized_volumes = read_and_parse_input("realized volumes(shares sold) (optional, press Enter to skip)");
+    let costs = read_and_parse_input("additional costs(Brokerage, fees, emoluments, slippage, financing, taxes, etc.) (optional, press Enter to skip)");
+    let borrow_fees = read_and_parse_input("borrow fees of short positions (optional, press Enter to skip)");
+
+    let len = buy_prices.len();
+
+    if sell_prices.len() != len || volumes.len() != len || market_prices.len() != len {
+        println!("All required inputs must have the same number of elements.");
+        return None;
+    }
+    // Optional lists are all-or-nothing: a partial list cannot be matched to its assets
+    for (values, name) in [(&realized_volumes, "Realized volumes"), (&costs, "Additional costs"), (&borrow_fees, "Borrow fees")] {
+        if !values.is_empty() && values.len() != len {
+            println!("{} must be skipped or have one value per asset ({} given, {} expected).", name, values.len(), len);
+            return None;
+        }
+    }
+
+    let positions = (0..len)
+        .map(|i| AssetPosition {
+            asset_id: format!("Asset_{}", i + 1),
+            buy_price: buy_prices[i],
+            sell_price: sell_prices[i],
+            contracted_volume: volumes[i].abs(),
+            realized_volume: realized_volumes.get(i).copied().map(f64::abs),
+            market_price: market_prices[i],
+            additional_costs: costs.get(i).copied().unwrap_or(0.0),
+            side: PositionSide::from_quantity(volumes[i]),
+            borrow_fee: borrow_fees.get(i).copied().unwrap_or(0.0),
+            ..Default::default()
+        })
+        .collect();
+    Some(positions)
+}
+
+fn print_menu(session: &Session) {
+    println!("\nChoose a function:  [{}]", session.status());
     println!("1 - Mean");
     println!("2 - VWAP (Volume Weighted Average Price)");
     println!("3 - VWAP Group (Bid + Ask) / 2");
@@ -37,251 +214,302 @@ fn main() {
     println!("10 - Profit & Loss Calculation (P&L Summary)");
     println!("11 - Full Market Stats Report (Global Summary)");
     println!("12 - Load data from CSV file (Bid, Ask, Volume columns)");
-    
+    println!("13 - Performance Metrics (Sharpe, Sortino, Drawdown)");
+    println!("Dataset: show | clear | append | replace      q - Quit");
+}
+
+fn main() {
+    // With arguments: run the requested subcommand and exit with its status code.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
+    println!("Quantitative Market Analyzer - by Willy Sajbeni");
 
+    let mut session = Session::default();
 
+    loop {
+        print_menu(&session);
 
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice).unwrap();
-    let choice = choice.trim();
+        let Some(choice) = read_line() else {
+            break; // end of input
+        };
 
-    match choice {
-        "1" => {
-            let prices = read_and_parse_input("prices");
-            if prices.is_empty() {
-                println!("Invalid price input.");
-                return;
+        match choice.to_lowercase().as_str() {
+            "1" => {
+                let prices = session_or_input_series(&session, "prices");
+                if prices.is_empty() {
+                    println!("Invalid price input.");
+                    continue;
+                }
+                match stats::mean(&prices) {
+                    Ok(mean) => println!("Mean: {:.4}", mean),
+                    Err(err) => println!("Error: {}", err),
+                }
             }
-            let mean = stats::mean(&prices);
-            println!("Mean: {:.4}", mean);
-        }
 
-        "2" => {
-            let prices = read_and_parse_input("prices");
-            let volumes = read_and_parse_input("volumes");
+            "2" => {
+                let (prices, volumes) = session_or_input_priced(&session);
+
+                if prices.len() != volumes.len() {
+                    println!("Prices and volumes must have the same length.");
+                    continue;
+                }
 
-            if prices.len() != volumes.len() {
-                println!("Prices and volumes must have the same length.");
-                return;
+                match stats::vwap(&prices, &volumes) {
+                    Ok(result) => {
+                        println!("VWAP: {:.4}", result.value);
+                        println!("Signal: {}", result.signal);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            let (vwap_value, signal) = stats::vwap(&prices, &volumes);
-            println!("VWAP: {:.4}", vwap_value);
-            println!("Signal: {}", signal);
-        }
+            "3" => {
+                let Some((bids, asks, volumes)) = session_or_input_market(&mut session) else {
+                    continue;
+                };
 
-        "3" => {
-            let bids = read_and_parse_input("bid prices (compra)");
-            let asks = read_and_parse_input("ask prices (venda)");
-            let volumes = read_and_parse_input("volumes");
+                let sizes = read_microprice_sizes(&session);
 
-            if bids.len() != asks.len() || bids.len() != volumes.len() {
-                println!("Prices and volumes must have the same length.");
-                return;
+                match stats::vwap_group_with(&bids, &asks, &volumes, GroupPrice::from_sizes(&sizes)) {
+                    Ok(result) => {
+                        println!("VWAP Group: {:.4}", result.value);
+                        println!("Signal: {}", result.signal);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            let (vwap_group, sinal) = stats::vwap_group(&bids, &asks, &volumes);
-            println!("VWAP Group: {:.4}", vwap_group);
-            println!("Signal: {}", sinal);
-        }
+            "4" => {
+                let data = session_or_input_series(&session, "values");
+                let is_population = read_is_population();
 
-        "4" => {
-            let data = read_and_parse_input("values");
+                let label = if is_population { "Population" } else { "Sample" };
+
+                match stats::variance(&data, is_population) {
+                    Ok(result) => {
+                        println!("{label} Variance: {:.4}", result.value);
+                        println!("Market Interpretation: {}", result.interpretation());
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            }
 
-            println!("Is this population data? (y/n):");
-            let mut type_input = String::new();
-            io::stdin().read_line(&mut type_input).unwrap();
-            let is_population = matches!(type_input.trim().to_lowercase().as_str(), "y" | "yes");
+            "5" => {
+                let data = session_or_input_series(&session, "values");
+                let is_population = read_is_population();
 
-            let (variance_value, explanation) = stats::variance(&data, is_population);
-            let label = if is_population { "Population" } else { "Sample" };
-            
-            println!("{label} Variance: {:.4}", variance_value);
-            println!("Market Interpretation: {}", explanation);
-            
-        }
+                let label = if is_population { "Population" } else { "Sample" };
 
-        "5" => {
-            let data = read_and_parse_input("values");
+                match stats::std(&data, is_population) {
+                    Ok(result) => println!("{label} Standard Deviation (STD): {:.4} => {}", result.value, result.interpretation()),
+                    Err(err) => println!("Error: {}", err),
+                }
+            }
 
-            println!("Is this population data? (y/n):");
-            let mut type_input = String::new();
-            io::stdin().read_line(&mut type_input).unwrap();
-            let is_population = matches!(type_input.trim().to_lowercase().as_str(), "y" | "yes");
 
-            let (std, note) = stats::std(&data, is_population);
-            let label = if is_population { "Population" } else { "Sample" };
+            "6" => {
+                let (prices, volumes) = session_or_input_priced(&session);
 
-            println!("{label} Standard Deviation (STD): {:.4} => {}", std, note);
-        }
+                if prices.len() != volumes.len() {
+                    println!("Prices and volumes must be of the same length.");
+                    continue;
+                }
 
+                let is_population = read_is_population();
 
-        "6" => {
-            let prices = read_and_parse_input("prices");
-            let volumes = read_and_parse_input("volumes");
+                let label = if is_population { "Population" } else { "Sample" };
 
-            if prices.len() != volumes.len() {
-                println!("Prices and volumes must be of the same length.");
-                return;
+                match stats::variance_vwap(&prices, &volumes, is_population) {
+                    Ok(result) => println!("{label} VWAP Variance: {:.4} => {}", result.value, result.interpretation()),
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            println!("Is this population data? (y/n):");
-            let mut type_input = String::new();
-            io::stdin().read_line(&mut type_input).unwrap();
-            let is_population = matches!(type_input.trim().to_lowercase().as_str(), "y" | "yes");
+            "7" => {
+                let (prices, volumes) = session_or_input_priced(&session);
 
-            let (variance, note) = stats::variance_vwap(&prices, &volumes, is_population);
-            let label = if is_population { "Population" } else { "Sample" };
+                if prices.len() != volumes.len() {
+                    println!("Prices and volumes must be of the same length.");
+                    continue;
+                }
 
-            println!("{label} VWAP Variance: {:.4} => {}", variance, note);
-        }
+                let is_population = read_is_population();
 
-        "7" => {
-            let prices = read_and_parse_input("prices");
-            let volumes = read_and_parse_input("volumes");
+                let label = if is_population { "Population" } else { "Sample" };
 
-            if prices.len() != volumes.len() {
-                println!("Prices and volumes must be of the same length.");
-                return;
+                match stats::std_vwap(&prices, &volumes, is_population) {
+                    Ok(result) => println!("{label} VWAP Standard Deviation: {:.4} => {}", result.value, result.interpretation()),
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            println!("Is this population data? (y/n):");
-            let mut type_input = String::new();
-            io::stdin().read_line(&mut type_input).unwrap();
-            let is_population = matches!(type_input.trim().to_lowercase().as_str(), "y" | "yes");
-
-            let (std, note) = stats::std_vwap(&prices, &volumes, is_population);
-            let label = if is_population { "Population" } else { "Sample" };
 
-            println!("{label} VWAP Standard Deviation: {:.4} => {}", std, note);
-        }
+            "8" => {
+                let Some((bids, asks, volumes)) = session_or_input_market(&mut session) else {
+                    continue;
+                };
 
+                let is_population = read_is_population();
 
-        "8" => {
-            let bids = read_and_parse_input("bid prices");
-            let asks = read_and_parse_input("ask prices");
-            let volumes = read_and_parse_input("volumes");
+                let sizes = read_microprice_sizes(&session);
 
-            if bids.len() != asks.len() || asks.len() != volumes.len() {
-                println!("Inputs must be the same length.");
-                return;
+                match stats::variance_vwap_group_with(&bids, &asks, &volumes, is_population, GroupPrice::from_sizes(&sizes)) {
+                    Ok(result) => println!("VWAP Group Variance: {:.4} => {}", result.value, result.interpretation()),
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            println!("Is this population data? (y/n):");
-            let mut pop_input = String::new();
-            io::stdin().read_line(&mut pop_input).unwrap();
-            let is_population = matches!(pop_input.trim().to_lowercase().as_str(), "y" | "yes");
+            "9" => {
+                let Some((bids, asks, volumes)) = session_or_input_market(&mut session) else {
+                    continue;
+                };
 
-            let (var, note) = stats::variance_vwap_group(&bids, &asks, &volumes, is_population);
-            println!("VWAP Group Variance: {:.4} => {}", var, note);
-        }
+                let is_population = read_is_population();
 
-        "9" => {
-            let bids = read_and_parse_input("bid prices");
-            let asks = read_and_parse_input("ask prices");
-            let volumes = read_and_parse_input("volumes");
+                let sizes = read_microprice_sizes(&session);
 
-            if bids.len() != asks.len() || asks.len() != volumes.len() {
-                println!("Inputs must be the same length.");
-                return;
+                match stats::std_vwap_group_with(&bids, &asks, &volumes, is_population, GroupPrice::from_sizes(&sizes)) {
+                    Ok(result) => println!("VWAP Group STD: {:.4} => {}", result.value, result.interpretation()),
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            println!("Is this population data? (y/n):");
-            let mut pop_input = String::new();
-            io::stdin().read_line(&mut pop_input).unwrap();
-            let is_population = matches!(pop_input.trim().to_lowercase().as_str(), "y" | "yes");
-
-            let (std, note) = stats::std_vwap_group(&bids, &asks, &volumes, is_population);
-            println!("VWAP Group STD: {:.4} => {}", std, note);
-        }
+            "10" => {
+                let Some(positions) = read_positions() else {
+                    continue;
+                };
 
-        "10" => {
-            let buy_prices = read_and_parse_input("buy prices");
-            let sell_prices = read_and_parse_input("sell prices");
-            let volumes = read_and_parse_input("contracted volumes(amount)");
-            let market_prices = read_and_parse_input("market prices(price at time of analysis)");
-            let realized_volumes = read_and_parse_input("realized volumes(shares sold) (optional, press Enter to skip)");
-            let costs = read_and_parse_input("additional costs(Brokerage, fees, emoluments, slippage, financing, taxes, etc.) (optional, press Enter to skip)");
+                for pos in &positions {
+                    let result = calculate_pnl(pos);
 
-            let len = buy_prices.len();
+                    println!("\n--- P&L Result for {} ({}) ---", pos.asset_id, pos.side);
+                    println!("Revenue       : {:.2}", result.revenue);
+                    println!("Cost          : {:.2}", result.cost);
+                    println!("Exposure      : {:.2}", result.exposure);
+                    if result.borrow_fee != 0.0 {
+                        println!("Borrow fee    : {:.2}", result.borrow_fee);
+                    }
+                    println!("Total P&L     : {:.2}", result.pnl);
+                }
 
-            if sell_prices.len() != len || volumes.len() != len || market_prices.len() != len {
-                println!("All required inputs must have the same number of elements.");
-                return;
+                if positions.len() > 1 {
+                    let portfolio = Portfolio::new(positions);
+                    println!("\n=== Portfolio P&L ===");
+                    print!("{}", report::render_breakdown(&portfolio, GroupBy::Asset, OutputFormat::Text));
+                }
             }
 
-            for i in 0..len {
-                let realized = realized_volumes.get(i).copied();
-                let extra_costs = costs.get(i).copied().unwrap_or(0.0);
-
-                let pos = AssetPosition {
-                    asset_id: format!("Asset_{}", i + 1),
-                    buy_price: buy_prices[i],
-                    sell_price: sell_prices[i],
-                    contracted_volume: volumes[i],
-                    realized_volume: realized,
-                    market_price: market_prices[i],
-                    additional_costs: extra_costs,
-                };
 
-                let result = calculate_pnl(&pos);
+            "11" => {
+                let Some((bids, asks, volumes)) = session_or_input_market(&mut session) else {
+                    continue;
+                };
 
-                println!("\n--- P&L Result for {} ---", pos.asset_id);
-                println!("Revenue       : {:.2}", result.revenue);
-                println!("Cost          : {:.2}", result.cost);
-                println!("Exposure      : {:.2}", result.exposure);
-                println!("Total P&L     : {:.2}", result.pnl);
+                stats::global_stats_summary(&bids, &asks, &volumes);
             }
-        }
 
+            "12" => {
+                println!("\nYou selected: Import CSV for VWAP Group Analysis.");
+                println!("Your CSV should contain three columns:");
+                println!(" 1. Bid prices (Compra)");
+                println!(" 2. Ask prices (Venda)");
+                println!(" 3. Volumes");
+                println!("Each row should be in the format: bid,ask,volume");
+                println!("Example: 10.2,10.5,1000");
+                println!("Or name your columns in a header line, e.g.:");
+                println!("timestamp,symbol,bid,ask,bid_size,ask_size,last,volume");
+                println!("Place your CSV file in the same directory as this program (where Cargo.toml is).");
+
+                loop {
+                    println!("\nEnter CSV file path (e.g., data.csv), or type 'exit' to go back:");
+
+                    let Some(path) = read_line() else {
+                        break;
+                    };
+
+                    if path.eq_ignore_ascii_case("exit") {
+                        println!("Returning to main menu.");
+                        break;
+                    }
 
-        "11" => {
-            let bids = read_and_parse_input("bid prices (compra)");
-            let asks = read_and_parse_input("ask prices (venda)");
-            let volumes = read_and_parse_input("volumes");
+                    match analyze_csv(&path) {
+                        Ok(ticks) => {
+                            // CSV processed successfully: keep it for the next options
+                            session.replace(ticks, &path);
+                            println!("\nDataset kept in memory ({}).", session.status());
+                            break;
+                        }
+                        Err(err) => {
+                            println!("Error: {}", err);
+                            println!("Please try again.");
+                        }
+                    }
+                }
+            }
 
-            if bids.len() != asks.len() || asks.len() != volumes.len() {
-                println!("All inputs must have the same length.");
-                return;
+            "13" => {
+                let pnl = read_and_parse_input("P&L per period (e.g. daily P&L)");
+                if pnl.is_empty() {
+                    println!("Invalid P&L input.");
+                    continue;
+                }
+                let capital = read_number_or("Starting capital", 100_000.0);
+                let config = PerformanceConfig::new()
+                    .risk_free_rate(read_number_or("Annual risk-free rate, e.g. 0.05", 0.0))
+                    .periods_per_year(read_number_or("Periods per year", 252.0));
+
+                match performance::performance_from_pnl(&pnl, capital, &config) {
+                    Ok(perf) => {
+                        let ratio = |value: Option<f64>| value.map_or("n/a".to_string(), |v| format!("{:.4}", v));
+                        println!("\n--- Performance over {} periods ---", perf.periods);
+                        println!("Cumulative return     : {:.2}%", perf.cumulative_return * 100.0);
+                        println!("Annualized return     : {:.2}%", perf.annualized_return * 100.0);
+                        println!("Annualized volatility : {:.2}%", perf.annualized_volatility * 100.0);
+                        println!("Sharpe ratio          : {}", ratio(perf.sharpe));
+                        println!("Sortino ratio         : {}", ratio(perf.sortino));
+                        println!(
+                            "Max drawdown          : {:.2}% ({:.2}), {} periods",
+                            perf.drawdown.max * 100.0,
+                            perf.drawdown.amount,
+                            perf.drawdown.duration
+                        );
+                        println!("Calmar ratio          : {}", ratio(perf.calmar));
+                        println!("Hit rate              : {}", ratio(perf.hit_rate));
+                        println!("Profit factor         : {}", ratio(perf.profit_factor));
+                        println!("Average win / loss    : {} / {}", ratio(perf.average_win), ratio(perf.average_loss));
+                        println!("Interpretation        : {}", perf.interpretation());
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
             }
 
-            stats::global_stats_summary(&bids, &asks, &volumes);
-        }
+            "show" => session.show(),
 
-        "12" => {
-            println!("\nYou selected: Import CSV for VWAP Group Analysis.");
-            println!("Your CSV should contain three columns:");
-            println!(" 1. Bid prices (Compra)");
-            println!(" 2. Ask prices (Venda)");
-            println!(" 3. Volumes");
-            println!("Each row should be in the format: bid,ask,volume");
-            println!("Example: 10.2,10.5,1000");
-            println!("Place your CSV file in the same directory as this program (where Cargo.toml is).");
-
-            loop {
-                println!("\nEnter CSV file path (e.g., data.csv), or type 'exit' to go back:");
-
-                let mut path = String::new();
-                std::io::stdin().read_line(&mut path).unwrap();
-                let path = path.trim();
-
-                if path.eq_ignore_ascii_case("exit") {
-                    println!("Returning to main menu.");
-                    break;
+            "clear" => {
+                session.clear();
+                println!("Dataset cleared.");
+            }
+
+            "append" => {
+                if let Some((ticks, source)) = read_dataset() {
+                    session.append(ticks, &source);
+                    println!("Rows appended ({}).", session.status());
                 }
+            }
 
-                match analyze_csv(path) {
-                    Ok(_) => break, // CSV processed successfully, exit loop
-                    Err(err) => {
-                        println!("Error: {}", err);
-                        println!("Please try again.");
-                    }
+            "replace" => {
+                if let Some((ticks, source)) = read_dataset() {
+                    session.replace(ticks, &source);
+                    println!("Dataset replaced ({}).", session.status());
                 }
             }
-        }
 
+            "q" | "quit" | "exit" => break,
 
-        _ => println!("Invalid option."),
+            _ => println!("Invalid option."),
+        }
     }
 }
-
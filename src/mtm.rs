@@ -0,0 +1,147 @@
+//! Mark-to-market P&L series.
+//!
+//! `pnl::calculate_pnl` values a position at one market price. Here the
+//! position (or a whole portfolio) is instead marked at every price of a tick
+//! series, giving a P&L curve: cumulative P&L and its change since the previous
+//! point, per tick or per period (e.g. daily closes). The curve is the input of
+//! risk and performance analysis.
+
+use crate::pnl::{AssetPosition, PositionSide};
+use crate::stats::StatsError;
+use crate::tick::{Frequency, PriceField, Tick, Timestamp};
+
+/// P&L at one point of the series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtmPoint {
+    /// Time of the last tick marked (`None` for ticks without timestamps)
+    pub timestamp: Option<Timestamp>,
+    /// Cumulative P&L of the marked positions
+    pub pnl: f64,
+    /// Change since the previous point (the first point's change is its P&L)
+    pub change: f64,
+}
+
+/// P&L of a position still open at `price`: the move from the entry price (buy price
+/// for a long, sell price for a short) times volume and multiplier, less costs and
+/// the borrow fee of a short.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::mtm::mark_to_market;
+/// use quant_marketstat_ws::pnl::AssetPosition;
+///
+/// let position = AssetPosition {
+///     asset_id: "PETR4".to_string(),
+///     buy_price: 37.0,
+///     contracted_volume: 100.0,
+///     additional_costs: 5.0,
+///     ..Default::default()
+/// };
+/// assert!((mark_to_market(&position, 37.5) - 45.0).abs() < 1e-9);
+/// ```
+pub fn mark_to_market(position: &AssetPosition, price: f64) -> f64 {
+    let (entry, borrow_fee) = match position.side {
+        PositionSide::Long => (position.buy_price, 0.0),
+        PositionSide::Short => (position.sell_price, position.borrow_fee),
+    };
+    position.side.sign() * (price - entry) * position.contracted_volume * position.multiplier()
+        - position.additional_costs
+        - borrow_fee
+}
+
+/// Marks `positions` at every tick of `ticks` and returns their total P&L curve.
+///
+/// A tick with a symbol prices the positions with that `asset_id`; a tick without
+/// one (single-instrument files) prices every position. Each position keeps its
+/// last price and only counts once it has been priced. Ticks that price no
+/// position, or lack the `field` price, are skipped. For one position, pass
+/// `std::slice::from_ref(&position)`.
+///
+/// # Errors
+/// * `EmptyInput` - no tick prices any position
+/// * `MissingTimestamps`, `UnsortedTimestamps` - for `Frequency::Every`
+/// * `InvalidWindow` - a period of zero
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::mtm::pnl_series;
+/// use quant_marketstat_ws::pnl::AssetPosition;
+/// use quant_marketstat_ws::tick::{Frequency, PriceField, Tick};
+///
+/// let position = AssetPosition {
+///     asset_id: "PETR4".to_string(),
+///     buy_price: 10.0,
+///     contracted_volume: 100.0,
+///     ..Default::default()
+/// };
+/// let ticks = [Tick::from_quote(10.0, 10.2, 1.0), Tick::from_quote(10.4, 10.6, 1.0)];
+///
+/// let curve = pnl_series(&[position], &ticks, PriceField::Mid, Frequency::Tick).unwrap();
+/// assert!((curve[1].pnl - 50.0).abs() < 1e-9);
+/// assert!((curve[1].change - 40.0).abs() < 1e-9);
+/// ```
+pub fn pnl_series(
+    positions: &[AssetPosition],
+    ticks: &[Tick],
+    field: PriceField,
+    frequency: Frequency,
+) -> Result<Vec<MtmPoint>, StatsError> {
+    if let Frequency::Every(period) = frequency {
+        if period <= 0 {
+            return Err(StatsError::InvalidWindow);
+        }
+        if ticks.iter().any(|t| t.timestamp.is_none()) {
+            return Err(StatsError::MissingTimestamps);
+        }
+        if ticks.windows(2).any(|w| w[1].timestamp < w[0].timestamp) {
+            return Err(StatsError::UnsortedTimestamps);
+        }
+    }
+
+    let mut marks: Vec<Option<f64>> = vec![None; positions.len()];
+    let mut points: Vec<MtmPoint> = Vec::new();
+    let mut previous = 0.0;
+    // Period of the point being built, replaced until the period ends
+    let mut open_period: Option<i64> = None;
+
+    for tick in ticks {
+        let Some(price) = field.of(tick) else { continue };
+        let mut priced = false;
+        for (position, mark) in positions.iter().zip(marks.iter_mut()) {
+            let matches = match &tick.symbol {
+                Some(symbol) => symbol.eq_ignore_ascii_case(&position.asset_id),
+                None => true,
+            };
+            if matches {
+                *mark = Some(mark_to_market(position, price));
+                priced = true;
+            }
+        }
+        if !priced {
+            continue;
+        }
+
+        let pnl = marks.iter().flatten().fold(0.0, |total, pnl| total + pnl);
+        let period = match frequency {
+            Frequency::Tick => None,
+            Frequency::Every(ms) => tick.timestamp.map(|ts| ts.div_euclid(ms)),
+        };
+        if period.is_some() && period == open_period {
+            points.pop();
+        } else if let Some(last) = points.last() {
+            previous = last.pnl;
+        }
+        open_period = period;
+
+        points.push(MtmPoint {
+            timestamp: tick.timestamp,
+            pnl,
+            change: pnl - previous,
+        });
+    }
+
+    if points.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    Ok(points)
+}
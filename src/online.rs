@@ -0,0 +1,374 @@
+//! Streaming (online) statistics accumulators.
+//!
+//! The `stats` functions need the whole slice in memory and make several passes
+//! over it. The accumulators here are fed one tick at a time, use O(1) memory and
+//! can be queried at any point, so they work on live feeds and on files larger
+//! than memory. Results use the same types (and thresholds) as `stats`.
+
+use crate::distribution::OnlineMoments;
+use crate::stats::{
+    DispersionResult, GroupSummary, MarketSignal, MarketSummary, Measure, SideSummary, StatsError,
+    VolatilityLevel, VwapResult,
+};
+use crate::tick::Tick;
+
+// ONLINE VWAP ---------------------------------------------------------------------------
+/// Running VWAP: `Σ(P·V) / ΣV` updated tick by tick.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::online::OnlineVwap;
+///
+/// let mut vwap = OnlineVwap::new();
+/// vwap.update(10.0, 100.0).unwrap();
+/// vwap.update(11.0, 300.0).unwrap();
+/// assert_eq!(vwap.value().unwrap().value, 10.75);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OnlineVwap {
+    count: usize,
+    total_value: f64,
+    total_volume: f64,
+    last_price: f64,
+}
+
+impl OnlineVwap {
+    pub fn new() -> OnlineVwap {
+        OnlineVwap::default()
+    }
+
+    /// Adds one trade. NaN inputs are rejected and leave the state unchanged.
+    pub fn update(&mut self, price: f64, volume: f64) -> Result<(), StatsError> {
+        if price.is_nan() || volume.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        self.count += 1;
+        self.total_value += price * volume;
+        self.total_volume += volume;
+        self.last_price = price;
+        Ok(())
+    }
+
+    /// Number of updates so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Sum of volumes so far.
+    pub fn total_volume(&self) -> f64 {
+        self.total_volume
+    }
+
+    /// Current VWAP and its signal against the last price.
+    pub fn value(&self) -> Result<VwapResult, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::EmptyInput);
+        }
+        if self.total_volume == 0.0 {
+            return Err(StatsError::ZeroVolume);
+        }
+
+        let vwap = self.total_value / self.total_volume;
+        Ok(VwapResult {
+            value: vwap,
+            last_price: self.last_price,
+            signal: MarketSignal::from_prices(vwap, self.last_price),
+        })
+    }
+}
+//-------------------------------------------------------------------------------------
+
+// ONLINE VARIANCE (Welford) -----------------------------------------------------------
+/// Running mean and variance with Welford's numerically stable update:
+///
+/// $$
+/// \delta = x - \bar{x}_{n-1}, \quad \bar{x}_n = \bar{x}_{n-1} + \delta / n, \quad M_n = M_{n-1} + \delta (x - \bar{x}_n)
+/// $$
+///
+/// Variance is `M / n` (population) or `M / (n - 1)` (sample).
+#[derive(Debug, Clone, Default)]
+pub struct OnlineVariance {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl OnlineVariance {
+    pub fn new() -> OnlineVariance {
+        OnlineVariance::default()
+    }
+
+    /// Adds one observation. NaN is rejected and leaves the state unchanged.
+    pub fn update(&mut self, value: f64) -> Result<(), StatsError> {
+        if value.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        Ok(())
+    }
+
+    /// Number of observations so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Running arithmetic mean.
+    pub fn mean(&self) -> Result<f64, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::EmptyInput);
+        }
+        Ok(self.mean)
+    }
+
+    /// Running variance, interpreted like `stats::variance`.
+    pub fn variance(&self, is_population: bool) -> Result<DispersionResult, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::EmptyInput);
+        }
+        let denominator = if is_population { self.count } else { self.count - 1 };
+        if denominator == 0 {
+            return Err(StatsError::InsufficientData);
+        }
+
+        let variance = self.m2 / denominator as f64;
+        Ok(DispersionResult {
+            value: variance,
+            level: VolatilityLevel::from_variance(variance),
+            measure: Measure::Variance,
+        })
+    }
+
+    /// Running standard deviation, interpreted like `stats::std`.
+    pub fn std(&self, is_population: bool) -> Result<DispersionResult, StatsError> {
+        let variance = self.variance(is_population)?;
+        Ok(DispersionResult {
+            value: variance.value.sqrt(),
+            level: variance.level,
+            measure: Measure::Std,
+        })
+    }
+}
+//-------------------------------------------------------------------------------------
+
+// ONLINE WEIGHTED VARIANCE (West) ------------------------------------------------------
+/// Running volume-weighted mean (the VWAP) and weighted variance around it, with
+/// West's weighted version of Welford's update:
+///
+/// $$
+/// W_n = W_{n-1} + w, \quad \bar{x}_n = \bar{x}_{n-1} + \frac{w}{W_n}(x - \bar{x}_{n-1}), \quad S_n = S_{n-1} + w (x - \bar{x}_{n-1})(x - \bar{x}_n)
+/// $$
+///
+/// Variance is `S / W` (population) or `S / (W - 1)` (sample), the same weighting as
+/// `stats::variance_vwap`. Zero weights are counted but do not move the estimates.
+#[derive(Debug, Clone, Default)]
+pub struct OnlineWeightedVariance {
+    count: usize,
+    total_weight: f64,
+    mean: f64,
+    s: f64,
+    last_value: f64,
+    measure: Option<(Measure, Measure)>,
+}
+
+impl OnlineWeightedVariance {
+    pub fn new() -> OnlineWeightedVariance {
+        OnlineWeightedVariance::default()
+    }
+
+    /// Accumulator whose results are reported as VWAP Group variance/STD (for mid prices).
+    pub fn new_group() -> OnlineWeightedVariance {
+        OnlineWeightedVariance {
+            measure: Some((Measure::GroupVariance, Measure::GroupStd)),
+            ..OnlineWeightedVariance::default()
+        }
+    }
+
+    /// Adds one value with its weight (volume). NaN is rejected and leaves the state unchanged.
+    pub fn update(&mut self, value: f64, weight: f64) -> Result<(), StatsError> {
+        if value.is_nan() || weight.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        self.count += 1;
+        self.last_value = value;
+        if weight == 0.0 {
+            return Ok(());
+        }
+
+        self.total_weight += weight;
+        let delta = value - self.mean;
+        self.mean += (weight / self.total_weight) * delta;
+        self.s += weight * delta * (value - self.mean);
+        Ok(())
+    }
+
+    /// Number of updates so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Sum of weights so far.
+    pub fn total_weight(&self) -> f64 {
+        self.total_weight
+    }
+
+    fn check(&self) -> Result<(), StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::EmptyInput);
+        }
+        if self.total_weight == 0.0 {
+            return Err(StatsError::ZeroVolume);
+        }
+        Ok(())
+    }
+
+    /// Weighted mean (VWAP) with its signal against the last value.
+    pub fn vwap(&self) -> Result<VwapResult, StatsError> {
+        self.check()?;
+        Ok(VwapResult {
+            value: self.mean,
+            last_price: self.last_value,
+            signal: MarketSignal::from_prices(self.mean, self.last_value),
+        })
+    }
+
+    /// Weighted variance around the weighted mean.
+    pub fn variance(&self, is_population: bool) -> Result<DispersionResult, StatsError> {
+        self.check()?;
+        let denominator = if is_population { self.total_weight } else { self.total_weight - 1.0 };
+        if denominator <= 0.0 {
+            return Err(StatsError::InsufficientData);
+        }
+
+        let variance = self.s / denominator;
+        Ok(DispersionResult {
+            value: variance,
+            level: VolatilityLevel::from_variance(variance),
+            measure: self.measure.map(|m| m.0).unwrap_or(Measure::VwapVariance),
+        })
+    }
+
+    /// Weighted standard deviation around the weighted mean.
+    pub fn std(&self, is_population: bool) -> Result<DispersionResult, StatsError> {
+        let std = self.variance(is_population)?.value.sqrt();
+        Ok(DispersionResult {
+            value: std,
+            level: VolatilityLevel::from_std(std),
+            measure: self.measure.map(|m| m.1).unwrap_or(Measure::VwapStd),
+        })
+    }
+}
+//-------------------------------------------------------------------------------------
+
+// ONLINE MARKET SUMMARY ---------------------------------------------------------------
+/// Online counterpart of `stats::market_summary`: feed ticks one at a time and get the
+/// global summary (population estimates) at any point.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::online::OnlineMarketStats;
+/// use quant_marketstat_ws::stats;
+/// use quant_marketstat_ws::tick::Tick;
+///
+/// let (bids, asks, volumes) = ([10.5, 10.6, 10.4], [11.0, 11.1, 10.9], [100.0, 150.0, 120.0]);
+/// let mut online = OnlineMarketStats::new();
+/// for i in 0..3 {
+///     online.update(&Tick::from_quote(bids[i], asks[i], volumes[i])).unwrap();
+/// }
+///
+/// let batch = stats::market_summary(&bids, &asks, &volumes).unwrap();
+/// let streamed = online.summary().unwrap();
+/// assert!((batch.group.std.value - streamed.group.std.value).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OnlineMarketStats {
+    bid: OnlineVariance,
+    ask: OnlineVariance,
+    bid_weighted: OnlineWeightedVariance,
+    ask_weighted: OnlineWeightedVariance,
+    mid_weighted: OnlineWeightedVariance,
+    /// Unweighted and volume-weighted moments of bid, ask and (weighted) mid
+    bid_moments: OnlineMoments,
+    ask_moments: OnlineMoments,
+    bid_weighted_moments: OnlineMoments,
+    ask_weighted_moments: OnlineMoments,
+    mid_weighted_moments: OnlineMoments,
+    last_ask: f64,
+}
+
+impl OnlineMarketStats {
+    pub fn new() -> OnlineMarketStats {
+        OnlineMarketStats {
+            mid_weighted: OnlineWeightedVariance::new_group(),
+            ..OnlineMarketStats::default()
+        }
+    }
+
+    /// Adds one tick. A tick with NaN prices or volume is rejected and leaves the state unchanged.
+    pub fn update(&mut self, tick: &Tick) -> Result<(), StatsError> {
+        if tick.bid.is_nan() || tick.ask.is_nan() || tick.volume.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        self.bid.update(tick.bid)?;
+        self.ask.update(tick.ask)?;
+        self.bid_weighted.update(tick.bid, tick.volume)?;
+        self.ask_weighted.update(tick.ask, tick.volume)?;
+        self.mid_weighted.update(tick.mid(), tick.volume)?;
+        self.bid_moments.update(tick.bid, 1.0)?;
+        self.ask_moments.update(tick.ask, 1.0)?;
+        self.bid_weighted_moments.update(tick.bid, tick.volume)?;
+        self.ask_weighted_moments.update(tick.ask, tick.volume)?;
+        self.mid_weighted_moments.update(tick.mid(), tick.volume)?;
+        self.last_ask = tick.ask;
+        Ok(())
+    }
+
+    /// Number of ticks so far.
+    pub fn count(&self) -> usize {
+        self.bid.count()
+    }
+
+    fn side(
+        values: &OnlineVariance,
+        weighted: &OnlineWeightedVariance,
+        moments: &OnlineMoments,
+        weighted_moments: &OnlineMoments,
+    ) -> Result<SideSummary, StatsError> {
+        Ok(SideSummary {
+            mean: values.mean()?,
+            vwap: weighted.vwap()?,
+            variance: values.variance(true)?,
+            std: values.std(true)?,
+            vwap_variance: weighted.variance(true)?,
+            vwap_std: weighted.std(true)?,
+            distribution: moments.distribution()?,
+            vwap_distribution: weighted_moments.distribution()?,
+        })
+    }
+
+    /// Current global summary, same layout as `stats::market_summary`; distributions have
+    /// no quartiles, which need the whole sample.
+    pub fn summary(&self) -> Result<MarketSummary, StatsError> {
+        let group_vwap = self.mid_weighted.vwap()?;
+
+        Ok(MarketSummary {
+            rows: self.count(),
+            bid: Self::side(&self.bid, &self.bid_weighted, &self.bid_moments, &self.bid_weighted_moments)?,
+            ask: Self::side(&self.ask, &self.ask_weighted, &self.ask_moments, &self.ask_weighted_moments)?,
+            group: GroupSummary {
+                // VWAP Group is compared against the last ask, as in `stats::vwap_group`
+                vwap: VwapResult {
+                    value: group_vwap.value,
+                    last_price: self.last_ask,
+                    signal: MarketSignal::from_prices(group_vwap.value, self.last_ask),
+                },
+                variance: self.mid_weighted.variance(true)?,
+                std: self.mid_weighted.std(true)?,
+                distribution: self.mid_weighted_moments.distribution()?,
+            },
+        })
+    }
+}
+//-------------------------------------------------------------------------------------
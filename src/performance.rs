@@ -0,0 +1,282 @@
+//! Performance metrics of a P&L or equity curve.
+//!
+//! P&L alone says nothing about the risk taken to earn it. From an equity
+//! curve (account value per period, or starting capital plus cumulative P&L)
+//! this module derives cumulative and annualized return, annualized
+//! volatility, the Sharpe, Sortino and Calmar ratios, the maximum drawdown and
+//! how long the curve stayed under water, plus trade-style figures on the
+//! period P&L: hit rate, profit factor and average win/loss.
+
+use crate::returns::{returns, ReturnKind};
+use crate::stats::StatsError;
+
+/// Annualization and risk-free rate used by [`performance`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerformanceConfig {
+    /// Annual risk-free rate, e.g. `0.05` for 5%
+    pub risk_free_rate: f64,
+    /// Periods of the curve in a year, e.g. 252 for daily points
+    pub periods_per_year: f64,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> PerformanceConfig {
+        PerformanceConfig {
+            risk_free_rate: 0.0,
+            periods_per_year: 252.0,
+        }
+    }
+}
+
+impl PerformanceConfig {
+    pub fn new() -> PerformanceConfig {
+        PerformanceConfig::default()
+    }
+
+    pub fn risk_free_rate(mut self, rate: f64) -> PerformanceConfig {
+        self.risk_free_rate = rate;
+        self
+    }
+
+    pub fn periods_per_year(mut self, periods: f64) -> PerformanceConfig {
+        self.periods_per_year = periods;
+        self
+    }
+
+    /// Risk-free rate compounded over one period.
+    fn risk_free_per_period(&self) -> f64 {
+        (1.0 + self.risk_free_rate).powf(1.0 / self.periods_per_year) - 1.0
+    }
+}
+
+/// Equity curve of a P&L series: `capital`, then `capital` plus the cumulative P&L
+/// after each period (one more point than `pnl`).
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::performance::equity_curve;
+///
+/// assert_eq!(equity_curve(&[50.0, -20.0], 1000.0), vec![1000.0, 1050.0, 1030.0]);
+/// ```
+pub fn equity_curve(pnl: &[f64], capital: f64) -> Vec<f64> {
+    let mut equity = Vec::with_capacity(pnl.len() + 1);
+    equity.push(capital);
+    for change in pnl {
+        equity.push(equity[equity.len() - 1] + change);
+    }
+    equity
+}
+
+// DRAWDOWN ---------------------------------------------------------------------------
+/// Largest peak-to-trough fall of an equity curve and time under water.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drawdown {
+    /// Largest fall from a previous peak, as a fraction of the peak (`0.2` = -20%)
+    pub max: f64,
+    /// The same fall in currency units
+    pub amount: f64,
+    /// Index of the peak the maximum drawdown starts from
+    pub peak: usize,
+    /// Index of its lowest point
+    pub trough: usize,
+    /// Index where the curve got back to the peak; `None` if it has not yet
+    pub recovery: Option<usize>,
+    /// Periods from the peak to the recovery (or to the last point)
+    pub duration: usize,
+    /// Longest time under water of the whole curve (peak to recovery or last point), in periods
+    pub longest_duration: usize,
+}
+
+/// Maximum drawdown of an equity curve and its duration.
+///
+/// # Errors
+/// * `EmptyInput`, `NaN` - invalid curve
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::performance::drawdown;
+///
+/// let equity = [100.0, 120.0, 90.0, 110.0, 125.0, 115.0];
+/// let dd = drawdown(&equity).unwrap();
+/// assert!((dd.max - 0.25).abs() < 1e-12); // 120 -> 90
+/// assert_eq!((dd.peak, dd.trough, dd.recovery), (1, 2, Some(4)));
+/// assert_eq!(dd.duration, 3);
+/// ```
+pub fn drawdown(equity: &[f64]) -> Result<Drawdown, StatsError> {
+    if equity.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if equity.iter().any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+
+    let mut result = Drawdown {
+        max: 0.0,
+        amount: 0.0,
+        peak: 0,
+        trough: 0,
+        recovery: Some(0),
+        duration: 0,
+        longest_duration: 0,
+    };
+    let mut peak = 0;
+    let mut under_water = false;
+    for (i, value) in equity.iter().enumerate() {
+        if *value >= equity[peak] {
+            if under_water {
+                result.longest_duration = result.longest_duration.max(i - peak);
+                under_water = false;
+            }
+            peak = i;
+            continue;
+        }
+        under_water = true;
+        let fall = equity[peak] - value;
+        // A curve of cumulative P&L from zero has no relative drawdown: ranked by amount
+        let relative = if equity[peak] > 0.0 { fall / equity[peak] } else { 0.0 };
+        if relative > result.max || (relative == result.max && fall > result.amount) {
+            result.max = relative;
+            result.amount = fall;
+            result.peak = peak;
+            result.trough = i;
+        }
+    }
+
+    if under_water {
+        result.longest_duration = result.longest_duration.max(equity.len() - 1 - peak);
+    }
+    result.recovery = match result.amount > 0.0 {
+        true => (result.trough..equity.len()).find(|i| equity[*i] >= equity[result.peak]),
+        false => Some(result.peak),
+    };
+    result.duration = result.recovery.unwrap_or(equity.len() - 1) - result.peak;
+    Ok(result)
+}
+//-------------------------------------------------------------------------------------
+
+// METRICS ----------------------------------------------------------------------------
+/// Performance of an equity curve. Ratios are `None` when undefined (no volatility,
+/// no drawdown or no losing period).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Performance {
+    /// Number of periods (returns) of the curve
+    pub periods: usize,
+    /// Return over the whole curve, `last / first - 1`
+    pub cumulative_return: f64,
+    /// Compound annual growth rate
+    pub annualized_return: f64,
+    /// Sample standard deviation of the period returns, annualized
+    pub annualized_volatility: f64,
+    /// Mean excess return over its standard deviation, annualized
+    pub sharpe: Option<f64>,
+    /// Mean excess return over the downside deviation, annualized
+    pub sortino: Option<f64>,
+    pub drawdown: Drawdown,
+    /// Annualized return over maximum drawdown
+    pub calmar: Option<f64>,
+    /// Share of the periods with a change that were gains
+    pub hit_rate: Option<f64>,
+    /// Gross gains over gross losses
+    pub profit_factor: Option<f64>,
+    /// Average gain of a winning period, in currency units
+    pub average_win: Option<f64>,
+    /// Average loss of a losing period, in currency units (positive)
+    pub average_loss: Option<f64>,
+}
+
+impl Performance {
+    /// Human-readable reading of the Sharpe ratio.
+    pub fn interpretation(&self) -> &'static str {
+        match self.sharpe {
+            None => "No volatility → risk-adjusted return undefined",
+            Some(s) if s < 0.0 => "Negative Sharpe → underperformed the risk-free rate",
+            Some(s) if s < 1.0 => "Sharpe below 1 → return small for the volatility taken",
+            Some(s) if s < 2.0 => "Sharpe between 1 and 2 → good risk-adjusted return",
+            Some(_) => "Sharpe above 2 → very strong risk-adjusted return",
+        }
+    }
+}
+
+/// Performance of an equity curve (account value per period, all positive).
+///
+/// Win/loss figures use the period changes of the curve in currency units; periods
+/// without a change are neither wins nor losses.
+///
+/// # Errors
+/// * `EmptyInput`, `NaN` - invalid curve
+/// * `InsufficientData` - a single point
+/// * `NonPositivePrice` - an equity value of zero or less
+/// * `InvalidWindow` - non-positive periods per year
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::performance::{performance, PerformanceConfig};
+///
+/// let equity = [100.0, 102.0, 101.0, 104.0, 103.0, 106.0];
+/// let perf = performance(&equity, &PerformanceConfig::new()).unwrap();
+/// assert!((perf.cumulative_return - 0.06).abs() < 1e-12);
+/// assert_eq!(perf.hit_rate, Some(0.6));
+/// assert_eq!(perf.profit_factor, Some(4.0)); // 8 gained, 2 lost
+/// assert!(perf.sharpe.unwrap() > 0.0);
+/// ```
+pub fn performance(equity: &[f64], config: &PerformanceConfig) -> Result<Performance, StatsError> {
+    if config.periods_per_year.is_nan() || config.periods_per_year <= 0.0 {
+        return Err(StatsError::InvalidWindow);
+    }
+    let period_returns = returns(equity, ReturnKind::Simple)?;
+    let n = period_returns.len() as f64;
+    let ppy = config.periods_per_year;
+    let rf = config.risk_free_per_period();
+
+    let cumulative_return = equity[equity.len() - 1] / equity[0] - 1.0;
+    let annualized_return = (1.0 + cumulative_return).powf(ppy / n) - 1.0;
+
+    let mean = period_returns.iter().sum::<f64>() / n;
+    let volatility = match period_returns.len() {
+        1 => 0.0,
+        _ => (period_returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt(),
+    };
+    let downside = (period_returns.iter().map(|r| (r - rf).min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+    let ratio = |risk: f64| (risk > 0.0).then(|| (mean - rf) / risk * ppy.sqrt());
+
+    let drawdown = drawdown(equity)?;
+    let changes: Vec<f64> = equity.windows(2).map(|w| w[1] - w[0]).collect();
+    let wins: Vec<f64> = changes.iter().copied().filter(|c| *c > 0.0).collect();
+    let losses: Vec<f64> = changes.iter().filter(|c| **c < 0.0).map(|c| -c).collect();
+    let (gains, lost) = (wins.iter().sum::<f64>(), losses.iter().sum::<f64>());
+    let decided = wins.len() + losses.len();
+    let average = |values: &[f64], total: f64| (!values.is_empty()).then(|| total / values.len() as f64);
+
+    Ok(Performance {
+        periods: period_returns.len(),
+        cumulative_return,
+        annualized_return,
+        annualized_volatility: volatility * ppy.sqrt(),
+        sharpe: ratio(volatility),
+        sortino: ratio(downside),
+        drawdown,
+        calmar: (drawdown.max > 0.0).then(|| annualized_return / drawdown.max),
+        hit_rate: (decided > 0).then(|| wins.len() as f64 / decided as f64),
+        profit_factor: (lost > 0.0).then(|| gains / lost),
+        average_win: average(&wins, gains),
+        average_loss: average(&losses, lost),
+    })
+}
+
+/// Performance of a P&L series (one change per period) on a starting `capital`.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::performance::{performance_from_pnl, PerformanceConfig};
+///
+/// let perf = performance_from_pnl(&[200.0, -100.0, 300.0], 10_000.0, &PerformanceConfig::new()).unwrap();
+/// assert!((perf.cumulative_return - 0.04).abs() < 1e-12);
+/// assert_eq!(perf.average_loss, Some(100.0));
+/// ```
+pub fn performance_from_pnl(pnl: &[f64], capital: f64, config: &PerformanceConfig) -> Result<Performance, StatsError> {
+    if pnl.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    performance(&equity_curve(pnl, capital), config)
+}
+//-------------------------------------------------------------------------------------
@@ -1,8 +1,62 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Direction of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionSide {
+    /// Bought first, sold later (profits when the price rises)
+    #[default]
+    Long,
+    /// Sold first (borrowed), bought back later (profits when the price falls)
+    Short,
+}
+
+impl PositionSide {
+    /// `1.0` for long, `-1.0` for short: the sign of the position's quantity.
+    pub fn sign(&self) -> f64 {
+        match self {
+            PositionSide::Long => 1.0,
+            PositionSide::Short => -1.0,
+        }
+    }
+
+    /// Side of a signed quantity (negative is short).
+    pub fn from_quantity(quantity: f64) -> PositionSide {
+        if quantity < 0.0 {
+            PositionSide::Short
+        } else {
+            PositionSide::Long
+        }
+    }
+}
+
+impl FromStr for PositionSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PositionSide, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "long" | "l" | "comprado" => Ok(PositionSide::Long),
+            "short" | "s" | "vendido" => Ok(PositionSide::Short),
+            other => Err(format!("Unknown position side '{}' (expected long or short)", other)),
+        }
+    }
+}
+
+impl fmt::Display for PositionSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PositionSide::Long => "long",
+            PositionSide::Short => "short",
+        })
+    }
+}
+
 /// Module for calculating generic Profit and Loss (P&L)
 /// for any type of traded asset: FX, stocks, futures, etc.
-/// Debug ->	Permite que você use {:?} no println! pra imprimir a struct
-/// Clone ->	Permite criar uma cópia exata da struct (let copy = original.clone();)
-#[derive(Debug, Clone)]
+/// Debug ->    Permite que você use {:?} no println! pra imprimir a struct
+/// Clone ->    Permite criar uma cópia exata da struct (let copy = original.clone();)
+/// Default ->  Permite omitir campos com `..Default::default()` (long, sem custos)
+#[derive(Debug, Clone, Default)]
 pub struct AssetPosition {
     /// Unique identifier for the asset (e.g. "BTC/USD", "AAPL", "EUR/BRL")
     pub asset_id: String,
@@ -24,31 +78,94 @@ pub struct AssetPosition {
 
     /// Extra costs: commissions, fees, slippage, taxes. Optional.
     pub additional_costs: f64,
+
+    /// Long (default) or short. Prices and volumes stay positive for both sides.
+    pub side: PositionSide,
+
+    /// Cost of borrowing the shares of a short position. Optional.
+    pub borrow_fee: f64,
+
+    /// Currency the prices and costs are quoted in (e.g. "USD", "BRL"). Optional;
+    /// see `fx::position_currency` for how it is inferred when missing.
+    pub currency: Option<String>,
+
+    /// Asset class for portfolio breakdowns (e.g. "equity", "fx", "futures"). Optional.
+    pub asset_class: Option<String>,
+
+    /// Free-form labels for portfolio breakdowns (e.g. strategy, desk, book).
+    pub tags: Vec<String>,
+
+    /// Contract multiplier: value of one point of price per unit of volume (futures,
+    /// options). Optional; `None` is `1.0`. See `instruments::InstrumentRegistry`.
+    pub multiplier: Option<f64>,
+}
+
+impl AssetPosition {
+    /// Contract multiplier, `1.0` when not set.
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier.unwrap_or(1.0)
+    }
 }
 
 /// Struct to hold the result of a P&L calculation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PnLResult {
     pub revenue: f64,
     pub cost: f64,
     pub exposure: f64,
+    /// Borrow fee of a short position (zero for longs)
+    pub borrow_fee: f64,
     pub pnl: f64,
 }
 
+impl PnLResult {
+    /// Every component multiplied by an FX `rate` (position currency → target currency).
+    pub fn converted(&self, rate: f64) -> PnLResult {
+        PnLResult {
+            revenue: self.revenue * rate,
+            cost: self.cost * rate,
+            exposure: self.exposure * rate,
+            borrow_fee: self.borrow_fee * rate,
+            pnl: self.pnl * rate,
+        }
+    }
+
+    /// Component-wise sum of two results (in the same currency).
+    pub fn add(&self, other: &PnLResult) -> PnLResult {
+        PnLResult {
+            revenue: self.revenue + other.revenue,
+            cost: self.cost + other.cost,
+            exposure: self.exposure + other.exposure,
+            borrow_fee: self.borrow_fee + other.borrow_fee,
+            pnl: self.pnl + other.pnl,
+        }
+    }
+}
+
 /// Calculates Profit & Loss (P&L) for a given asset position, in a generic way.
 ///
 /// # Formula:
 /// ```text
-/// Revenue  = sell_price × contracted_volume(amount)
-/// Cost     = buy_price × contracted_volume(amount)
-/// Exposure = (realized_volume(amount) - contracted_volume(amount)) × market_price
-/// P&L      = Revenue - Cost - Exposure - Additional Costs
+/// Revenue  = sell_price × contracted_volume(amount) × multiplier
+/// Cost     = buy_price × contracted_volume(amount) × multiplier
+/// Exposure = (realized_volume(amount) - contracted_volume(amount)) × market_price × multiplier   (long)
+/// P&L      = Revenue - Cost - Exposure - Additional Costs - Borrow Fee
 /// ```
 ///
+/// The multiplier is `1.0` unless the position sets one (futures and options contracts).
+///
 /// If `realized_volume` is not provided, exposure is assumed to be zero.
 ///
+/// For a short, `sell_price` is the opening sale and `buy_price` the cover. With a
+/// `realized_volume`, only that quantity was bought back: Cost is the cover of the realized
+/// quantity and Exposure the cost of buying back the rest at the market price,
+/// `(contracted_volume - realized_volume) × market_price × multiplier`. The borrow fee
+/// is charged as a cost.
+///
 /// # Example:
 /// ```
+/// use quant_marketstat_ws::pnl::{AssetPosition, PositionSide, calculate_pnl};
+///
 /// let position = AssetPosition {
 ///     asset_id: "EUR/USD".to_string(),
 ///     buy_price: 1.08,
@@ -57,26 +174,69 @@ pub struct PnLResult {
 ///     realized_volume: Some(1_020_000.0),
 ///     market_price: 1.10,
 ///     additional_costs: 1500.0,
+///     ..Default::default()
 /// };
 ///
 /// let result = calculate_pnl(&position);
 /// println!("{:?}", result);
+///
+/// // Short 100 shares at 50, covered at 45, paying 20 to borrow them
+/// let short = AssetPosition {
+///     asset_id: "PETR4".to_string(),
+///     side: PositionSide::Short,
+///     sell_price: 50.0,
+///     buy_price: 45.0,
+///     contracted_volume: 100.0,
+///     market_price: 45.0,
+///     borrow_fee: 20.0,
+///     ..Default::default()
+/// };
+/// assert_eq!(calculate_pnl(&short).pnl, 480.0);
+///
+/// // Short 100 at 10, 60 covered at 9, the other 40 still owed at 9.5
+/// let partly_covered = AssetPosition {
+///     asset_id: "PETR4".to_string(),
+///     side: PositionSide::Short,
+///     sell_price: 10.0,
+///     buy_price: 9.0,
+///     contracted_volume: 100.0,
+///     realized_volume: Some(60.0),
+///     market_price: 9.5,
+///     ..Default::default()
+/// };
+/// let result = calculate_pnl(&partly_covered);
+/// assert_eq!((result.revenue, result.cost, result.exposure), (1000.0, 540.0, 380.0));
+/// assert!((result.pnl - 80.0).abs() < 1e-9); // 60 × (10 - 9) + 40 × (10 - 9.5)
 /// ```
 pub fn calculate_pnl(position: &AssetPosition) -> PnLResult {
-    let revenue = position.sell_price * position.contracted_volume;
-    let cost = position.buy_price * position.contracted_volume;
+    let multiplier = position.multiplier();
+    let revenue = position.sell_price * position.contracted_volume * multiplier;
+
+    let (cost, exposure) = match (position.side, position.realized_volume) {
+        (_, None) => (position.buy_price * position.contracted_volume * multiplier, 0.0),
+        (PositionSide::Long, Some(realized)) => (
+            position.buy_price * position.contracted_volume * multiplier,
+            (realized - position.contracted_volume) * position.market_price * multiplier,
+        ),
+        // Only the realized quantity was covered; the rest is still owed at the market price
+        (PositionSide::Short, Some(realized)) => (
+            position.buy_price * realized * multiplier,
+            (position.contracted_volume - realized) * position.market_price * multiplier,
+        ),
+    };
 
-    let exposure = match position.realized_volume {
-        Some(realized) => (realized - position.contracted_volume) * position.market_price,
-        None => 0.0,
+    let borrow_fee = match position.side {
+        PositionSide::Short => position.borrow_fee,
+        PositionSide::Long => 0.0,
     };
 
-    let pnl = revenue - cost - exposure - position.additional_costs;
+    let pnl = revenue - cost - exposure - position.additional_costs - borrow_fee;
 
     PnLResult {
         revenue,
         cost,
         exposure,
+        borrow_fee,
         pnl,
     }
 }
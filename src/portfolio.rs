@@ -0,0 +1,187 @@
+//! Portfolio-level P&L: totals and breakdowns of many positions.
+//!
+//! A [`Portfolio`] holds the P&L of each `AssetPosition` (optionally converted
+//! into one reporting currency) and aggregates it: overall totals, breakdowns
+//! by asset, asset class or tag, and each group's share of the total P&L.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::fx::{self, FxError, FxRates};
+use crate::pnl::{calculate_pnl, AssetPosition, PnLResult};
+
+/// Group label for positions without an asset class.
+pub const UNCLASSIFIED: &str = "(unclassified)";
+/// Group label for positions without tags.
+pub const UNTAGGED: &str = "(untagged)";
+
+/// How positions are grouped in a breakdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    #[default]
+    Asset,
+    AssetClass,
+    /// A position with several tags counts in each of them, so shares may add up to more than 100%
+    Tag,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GroupBy, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "asset" => Ok(GroupBy::Asset),
+            "class" | "asset_class" | "asset-class" => Ok(GroupBy::AssetClass),
+            "tag" | "tags" => Ok(GroupBy::Tag),
+            other => Err(format!("Unknown grouping '{}' (expected asset, class or tag)", other)),
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GroupBy::Asset => "asset",
+            GroupBy::AssetClass => "class",
+            GroupBy::Tag => "tag",
+        })
+    }
+}
+
+/// Aggregated P&L of one group of positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakdownLine {
+    /// Asset id, asset class or tag
+    pub key: String,
+    /// Number of positions in the group
+    pub positions: usize,
+    pub result: PnLResult,
+    /// Share of the portfolio's total P&L, in percent; `None` when the total is zero
+    pub contribution: Option<f64>,
+}
+
+/// Positions with their P&L, all in the same currency.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::pnl::AssetPosition;
+/// use quant_marketstat_ws::portfolio::{GroupBy, Portfolio};
+///
+/// let position = |id: &str, class: &str, sell: f64| AssetPosition {
+///     asset_id: id.to_string(),
+///     buy_price: 10.0,
+///     sell_price: sell,
+///     contracted_volume: 100.0,
+///     asset_class: Some(class.to_string()),
+///     ..Default::default()
+/// };
+/// let portfolio = Portfolio::new(vec![
+///     position("PETR4", "equity", 13.0),
+///     position("VALE3", "equity", 11.0),
+///     position("WDOFUT", "futures", 9.0),
+/// ]);
+///
+/// assert_eq!(portfolio.totals().pnl, 300.0);
+/// let by_class = portfolio.breakdown(GroupBy::AssetClass);
+/// assert_eq!(by_class[0].key, "equity");
+/// assert_eq!(by_class[0].contribution, Some(400.0 / 300.0 * 100.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    currency: Option<String>,
+    entries: Vec<(AssetPosition, PnLResult)>,
+}
+
+impl Portfolio {
+    /// Portfolio of positions taken at face value (one currency, or unitless).
+    pub fn new(positions: Vec<AssetPosition>) -> Portfolio {
+        let entries = positions
+            .into_iter()
+            .map(|position| {
+                let result = calculate_pnl(&position);
+                (position, result)
+            })
+            .collect();
+        Portfolio { currency: None, entries }
+    }
+
+    /// Portfolio whose P&L is converted into `currency` with `rates` (see `fx::consolidate`).
+    pub fn in_currency(positions: Vec<AssetPosition>, rates: &FxRates, currency: &str) -> Result<Portfolio, FxError> {
+        let report = fx::consolidate(&positions, rates, currency)?;
+        let entries = positions
+            .into_iter()
+            .zip(report.positions)
+            .map(|(position, converted)| (position, converted.reporting))
+            .collect();
+        Ok(Portfolio {
+            currency: Some(report.currency),
+            entries,
+        })
+    }
+
+    /// Reporting currency, when the portfolio was built with [`Portfolio::in_currency`].
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    /// Positions with their P&L, in input order.
+    pub fn entries(&self) -> &[(AssetPosition, PnLResult)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sum of revenue, cost, exposure, borrow fees and P&L over all positions.
+    pub fn totals(&self) -> PnLResult {
+        self.entries
+            .iter()
+            .fold(PnLResult::default(), |total, (_, result)| total.add(result))
+    }
+
+    /// Share of `pnl` in the portfolio's total P&L, in percent (`None` when the total is zero).
+    pub fn contribution(&self, pnl: f64) -> Option<f64> {
+        share(pnl, self.totals().pnl)
+    }
+
+    /// Aggregated P&L per asset, asset class or tag, sorted by key.
+    pub fn breakdown(&self, group_by: GroupBy) -> Vec<BreakdownLine> {
+        let mut groups: BTreeMap<String, (usize, PnLResult)> = BTreeMap::new();
+
+        for (position, result) in &self.entries {
+            let keys = match group_by {
+                GroupBy::Asset => vec![position.asset_id.clone()],
+                GroupBy::AssetClass => vec![position.asset_class.clone().unwrap_or_else(|| UNCLASSIFIED.to_string())],
+                GroupBy::Tag if position.tags.is_empty() => vec![UNTAGGED.to_string()],
+                GroupBy::Tag => position.tags.clone(),
+            };
+            for key in keys {
+                let group = groups.entry(key).or_default();
+                group.0 += 1;
+                group.1 = group.1.add(result);
+            }
+        }
+
+        let total = self.totals().pnl;
+        groups
+            .into_iter()
+            .map(|(key, (positions, result))| BreakdownLine {
+                key,
+                positions,
+                contribution: share(result.pnl, total),
+                result,
+            })
+            .collect()
+    }
+}
+
+/// `pnl` as a percentage of `total`, `None` when the total is zero.
+fn share(pnl: f64, total: f64) -> Option<f64> {
+    (total != 0.0).then(|| pnl / total * 100.0)
+}
@@ -0,0 +1,398 @@
+//! Renderers for [`MarketSummary`], portfolio breakdowns and tabular series: human text, JSON and CSV.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::portfolio::{BreakdownLine, GroupBy, Portfolio};
+use crate::distribution::Distribution;
+use crate::stats::{DispersionResult, MarketSummary, SideSummary, VwapResult};
+
+/// Output format of a rendered report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OutputFormat, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(format!("Unknown output format '{}' (expected text, json or csv)", other)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Renders a summary in the requested format.
+pub fn render(summary: &MarketSummary, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => render_text(summary),
+        OutputFormat::Json => render_json(summary),
+        OutputFormat::Csv => render_csv(summary),
+    }
+}
+
+// TEXT -----------------------------------------------------------------------------
+/// Human-readable report, as printed by the menu (option 11).
+pub fn render_text(summary: &MarketSummary) -> String {
+    let vwap = |r: &VwapResult| format!("{:.4} => {}", r.value, r.signal);
+    let disp = |r: &DispersionResult| format!("{:.4} => {}", r.value, r.interpretation());
+    let (bid, ask, group) = (&summary.bid, &summary.ask, &summary.group);
+
+    let mut out = String::new();
+    out.push_str("\n📊 === Global Market Stats Summary ===\n\n");
+
+    // Mean
+    out.push_str(&format!("Mean Bid: {:.4}\n", bid.mean));
+    out.push_str(&format!("Mean Ask: {:.4}\n", ask.mean));
+
+    // VWAP
+    out.push_str(&format!("\nVWAP Bid: {}\n", vwap(&bid.vwap)));
+    out.push_str(&format!("VWAP Ask: {}\n", vwap(&ask.vwap)));
+
+    // VWAP Group
+    out.push_str(&format!("VWAP Group: {}\n", vwap(&group.vwap)));
+
+    // Variance & STD - raw
+    out.push_str(&format!("\nVariance Bid: {}\n", disp(&bid.variance)));
+    out.push_str(&format!("Variance Ask: {}\n", disp(&ask.variance)));
+    out.push_str(&format!("STD Bid: {}\n", disp(&bid.std)));
+    out.push_str(&format!("STD Ask: {}\n", disp(&ask.std)));
+
+    // VWAP Variance & STD
+    out.push_str(&format!("\nVWAP Variance Bid: {}\n", disp(&bid.vwap_variance)));
+    out.push_str(&format!("VWAP Variance Ask: {}\n", disp(&ask.vwap_variance)));
+    out.push_str(&format!("VWAP STD Bid: {}\n", disp(&bid.vwap_std)));
+    out.push_str(&format!("VWAP STD Ask: {}\n", disp(&ask.vwap_std)));
+
+    // Variance & STD - VWAP Group
+    out.push_str(&format!("\nVWAP Group Variance: {}\n", disp(&group.variance)));
+    out.push_str(&format!("VWAP Group STD: {}\n", disp(&group.std)));
+
+    // Distribution
+    for (label, d) in distributions(summary) {
+        out.push_str(&format!("\nDistribution {}: {}\n", label, distribution_text(&d)));
+        out.push_str(&format!(
+            "Shape {}: skewness {:.4} | excess kurtosis {:.4} | Jarque-Bera {:.4} (p {:.4}) => {}\n",
+            label,
+            d.skewness,
+            d.excess_kurtosis,
+            d.jarque_bera.statistic,
+            d.jarque_bera.p_value,
+            d.interpretation()
+        ));
+    }
+
+    out
+}
+
+/// Distributions of a summary, with their labels.
+fn distributions(summary: &MarketSummary) -> [(&'static str, Distribution); 5] {
+    [
+        ("Bid", summary.bid.distribution),
+        ("VWAP Bid", summary.bid.vwap_distribution),
+        ("Ask", summary.ask.distribution),
+        ("VWAP Ask", summary.ask.vwap_distribution),
+        ("VWAP Group", summary.group.distribution),
+    ]
+}
+
+/// `min | Q1 | median | Q3 | max | IQR | range` (quartiles only when computed).
+fn distribution_text(d: &Distribution) -> String {
+    match d.quartiles {
+        Some(q) => format!(
+            "min {:.4} | Q1 {:.4} | median {:.4} | Q3 {:.4} | max {:.4} | IQR {:.4} | range {:.4}",
+            d.min, q.q1, q.median, q.q3, d.max, q.iqr, d.range
+        ),
+        None => format!("min {:.4} | max {:.4} | range {:.4}", d.min, d.max, d.range),
+    }
+}
+//-----------------------------------------------------------------------------------
+
+// JSON -----------------------------------------------------------------------------
+fn vwap_json(r: &VwapResult) -> Value {
+    json!({
+        "value": r.value,
+        "last_price": r.last_price,
+        "signal": r.signal.as_str(),
+        "interpretation": r.signal.description(),
+    })
+}
+
+fn dispersion_json(r: &DispersionResult) -> Value {
+    json!({
+        "value": r.value,
+        "level": r.level.as_str(),
+        "interpretation": r.interpretation(),
+    })
+}
+
+fn distribution_json(d: &Distribution) -> Value {
+    json!({
+        "count": d.count,
+        "min": d.min,
+        "max": d.max,
+        "range": d.range,
+        "median": d.quartiles.map(|q| q.median),
+        "q1": d.quartiles.map(|q| q.q1),
+        "q3": d.quartiles.map(|q| q.q3),
+        "iqr": d.quartiles.map(|q| q.iqr),
+        "skewness": d.skewness,
+        "excess_kurtosis": d.excess_kurtosis,
+        "jarque_bera": d.jarque_bera.statistic,
+        "jarque_bera_p_value": d.jarque_bera.p_value,
+        "interpretation": d.interpretation(),
+    })
+}
+
+fn side_json(side: &SideSummary) -> Value {
+    json!({
+        "mean": side.mean,
+        "vwap": vwap_json(&side.vwap),
+        "variance": dispersion_json(&side.variance),
+        "std": dispersion_json(&side.std),
+        "vwap_variance": dispersion_json(&side.vwap_variance),
+        "vwap_std": dispersion_json(&side.vwap_std),
+        "distribution": distribution_json(&side.distribution),
+        "vwap_distribution": distribution_json(&side.vwap_distribution),
+    })
+}
+
+/// Summary as a JSON document, for dashboards and other tools.
+///
+/// ```text
+/// {"rows": 4, "bid": {"mean": .., "vwap": {"value": .., "signal": "strong", ..}, ..},
+///  "ask": {..}, "group": {"vwap": {..}, "variance": {..}, "std": {..}, "distribution": {..}}}
+/// ```
+pub fn render_json(summary: &MarketSummary) -> String {
+    let doc = json!({
+        "rows": summary.rows,
+        "bid": side_json(&summary.bid),
+        "ask": side_json(&summary.ask),
+        "group": {
+            "vwap": vwap_json(&summary.group.vwap),
+            "variance": dispersion_json(&summary.group.variance),
+            "std": dispersion_json(&summary.group.std),
+            "distribution": distribution_json(&summary.group.distribution),
+        },
+    });
+
+    // Serializing a `Value` cannot fail
+    serde_json::to_string_pretty(&doc).unwrap_or_default() + "\n"
+}
+//-----------------------------------------------------------------------------------
+
+// CSV ------------------------------------------------------------------------------
+/// Summary as CSV, one metric per row: `metric,value,signal,level,interpretation`.
+pub fn render_csv(summary: &MarketSummary) -> String {
+    let mut rows: Vec<[String; 5]> = vec![["rows".into(), summary.rows.to_string(), "".into(), "".into(), "".into()]];
+
+    let vwap_row = |name: &str, r: &VwapResult| {
+        [name.to_string(), r.value.to_string(), r.signal.as_str().into(), "".into(), r.signal.description().into()]
+    };
+    let disp_row = |name: &str, r: &DispersionResult| {
+        [name.to_string(), r.value.to_string(), "".into(), r.level.as_str().into(), r.interpretation().into()]
+    };
+
+    for (label, side) in [("bid", &summary.bid), ("ask", &summary.ask)] {
+        rows.push([format!("mean_{}", label), side.mean.to_string(), "".into(), "".into(), "".into()]);
+        rows.push(vwap_row(&format!("vwap_{}", label), &side.vwap));
+        rows.push(disp_row(&format!("variance_{}", label), &side.variance));
+        rows.push(disp_row(&format!("std_{}", label), &side.std));
+        rows.push(disp_row(&format!("vwap_variance_{}", label), &side.vwap_variance));
+        rows.push(disp_row(&format!("vwap_std_{}", label), &side.vwap_std));
+    }
+    rows.push(vwap_row("vwap_group", &summary.group.vwap));
+    rows.push(disp_row("vwap_group_variance", &summary.group.variance));
+    rows.push(disp_row("vwap_group_std", &summary.group.std));
+
+    for (label, d) in distributions(summary) {
+        let label = label.to_ascii_lowercase().replace(' ', "_");
+        let quartile = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let metrics = [
+            ("min", d.min.to_string()),
+            ("q1", quartile(d.quartiles.map(|q| q.q1))),
+            ("median", quartile(d.quartiles.map(|q| q.median))),
+            ("q3", quartile(d.quartiles.map(|q| q.q3))),
+            ("max", d.max.to_string()),
+            ("iqr", quartile(d.quartiles.map(|q| q.iqr))),
+            ("range", d.range.to_string()),
+            ("skewness", d.skewness.to_string()),
+            ("excess_kurtosis", d.excess_kurtosis.to_string()),
+            ("jarque_bera_p_value", d.jarque_bera.p_value.to_string()),
+        ];
+        for (metric, value) in metrics {
+            rows.push([format!("{}_{}", metric, label), value, "".into(), "".into(), "".into()]);
+        }
+        rows.push([
+            format!("jarque_bera_{}", label),
+            d.jarque_bera.statistic.to_string(),
+            "".into(),
+            "".into(),
+            d.interpretation().into(),
+        ]);
+    }
+
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    // Writing to a Vec<u8> cannot fail
+    let _ = wtr.write_record(["metric", "value", "signal", "level", "interpretation"]);
+    for row in &rows {
+        let _ = wtr.write_record(row);
+    }
+    let bytes = wtr.into_inner().unwrap_or_default();
+    String::from_utf8(bytes).unwrap_or_default()
+}
+//-----------------------------------------------------------------------------------
+
+// TABLES ---------------------------------------------------------------------------
+/// One value of a rendered table (time series, per-row results, ...).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    /// Missing value: empty in text/CSV, `null` in JSON
+    Empty,
+}
+
+impl Cell {
+    /// `Number` for `Some`, `Empty` for `None`.
+    pub fn from_option(value: Option<f64>) -> Cell {
+        value.map(Cell::Number).unwrap_or(Cell::Empty)
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Integer(n) => n.to_string(),
+            Cell::Number(n) => format!("{:.4}", n),
+            Cell::Empty => String::new(),
+        }
+    }
+
+    fn raw(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Integer(n) => n.to_string(),
+            Cell::Number(n) => n.to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+
+    fn json(&self) -> Value {
+        match self {
+            Cell::Text(s) => Value::String(s.clone()),
+            Cell::Integer(n) => Value::from(*n),
+            // NaN and infinities become null
+            Cell::Number(n) => serde_json::Number::from_f64(*n).map(Value::Number).unwrap_or(Value::Null),
+            Cell::Empty => Value::Null,
+        }
+    }
+}
+
+/// Renders rows of cells under `headers`: aligned columns (text), CSV, or a JSON array of objects.
+pub fn render_table(headers: &[&str], rows: &[Vec<Cell>], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => {
+            let texts: Vec<Vec<String>> = rows.iter().map(|row| row.iter().map(Cell::text).collect()).collect();
+            let widths: Vec<usize> = headers
+                .iter()
+                .enumerate()
+                .map(|(i, h)| texts.iter().filter_map(|r| r.get(i)).map(|c| c.chars().count()).fold(h.len(), usize::max))
+                .collect();
+
+            let line = |cells: Vec<String>| {
+                cells
+                    .iter()
+                    .zip(widths.iter())
+                    .map(|(c, w)| format!("{:>width$}", c, width = *w))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    + "\n"
+            };
+
+            let mut out = line(headers.iter().map(|h| h.to_string()).collect());
+            for row in texts {
+                out.push_str(&line(row));
+            }
+            out
+        }
+
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(Vec::new());
+            // Writing to a Vec<u8> cannot fail
+            let _ = wtr.write_record(headers);
+            for row in rows {
+                let _ = wtr.write_record(row.iter().map(Cell::raw));
+            }
+            String::from_utf8(wtr.into_inner().unwrap_or_default()).unwrap_or_default()
+        }
+
+        OutputFormat::Json => {
+            let items: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    let object = headers
+                        .iter()
+                        .zip(row.iter())
+                        .map(|(h, c)| (h.to_string(), c.json()))
+                        .collect::<serde_json::Map<String, Value>>();
+                    Value::Object(object)
+                })
+                .collect();
+            serde_json::to_string_pretty(&Value::Array(items)).unwrap_or_default() + "\n"
+        }
+    }
+}
+//-----------------------------------------------------------------------------------
+
+// PORTFOLIO ------------------------------------------------------------------------
+/// Portfolio breakdown as a table (one row per group plus a `TOTAL` row):
+/// `group,positions,revenue,cost,exposure,borrow_fee,pnl,contribution_pct`.
+pub fn render_breakdown(portfolio: &Portfolio, group_by: GroupBy, format: OutputFormat) -> String {
+    let row = |line: &BreakdownLine| {
+        vec![
+            Cell::Text(line.key.clone()),
+            Cell::Integer(line.positions as i64),
+            Cell::Number(line.result.revenue),
+            Cell::Number(line.result.cost),
+            Cell::Number(line.result.exposure),
+            Cell::Number(line.result.borrow_fee),
+            Cell::Number(line.result.pnl),
+            Cell::from_option(line.contribution),
+        ]
+    };
+
+    let totals = portfolio.totals();
+    let mut rows: Vec<Vec<Cell>> = portfolio.breakdown(group_by).iter().map(row).collect();
+    rows.push(row(&BreakdownLine {
+        key: "TOTAL".to_string(),
+        positions: portfolio.len(),
+        contribution: portfolio.contribution(totals.pnl),
+        result: totals,
+    }));
+
+    let group = group_by.to_string();
+    let headers = [group.as_str(), "positions", "revenue", "cost", "exposure", "borrow_fee", "pnl", "contribution_pct"];
+    render_table(&headers, &rows, format)
+}
+//-----------------------------------------------------------------------------------
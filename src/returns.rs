@@ -0,0 +1,186 @@
+//! Simple and log returns of a price series.
+//!
+//! Price levels are not comparable across instruments (a variance of 0.5 is
+//! tiny for an index future and huge for a penny stock), returns are. This
+//! module turns tick prices (bid, ask, mid or last) into simple or log
+//! returns, optionally after resampling the ticks to fixed intervals (1s, 1m,
+//! 1h, 1d), so the `stats` dispersion functions can be applied to returns.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::stats::StatsError;
+use crate::tick::{Frequency, PriceField, Tick, Timestamp};
+
+/// How a return is computed from two consecutive prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReturnKind {
+    /// `p_t / p_{t-1} - 1`
+    #[default]
+    Simple,
+    /// `ln(p_t / p_{t-1})`, additive over time
+    Log,
+}
+
+impl FromStr for ReturnKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ReturnKind, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "simple" | "arithmetic" | "pct" => Ok(ReturnKind::Simple),
+            "log" | "ln" | "continuous" => Ok(ReturnKind::Log),
+            other => Err(format!("Unknown return kind '{}' (expected simple or log)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ReturnKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReturnKind::Simple => "simple",
+            ReturnKind::Log => "log",
+        })
+    }
+}
+
+/// One return of a series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReturnPoint {
+    /// Time of the price the return ends at (period start when resampled)
+    pub timestamp: Option<Timestamp>,
+    pub value: f64,
+}
+
+/// Returns between consecutive prices: one less value than prices.
+///
+/// # Errors
+/// * `EmptyInput` - no prices
+/// * `InsufficientData` - a single price
+/// * `NaN` - a price is NaN
+/// * `NonPositivePrice` - a price is zero or negative
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::returns::{returns, ReturnKind};
+///
+/// let prices = [100.0, 110.0, 99.0];
+/// let simple = returns(&prices, ReturnKind::Simple).unwrap();
+/// assert!((simple[0] - 0.10).abs() < 1e-12);
+/// assert!((simple[1] + 0.10).abs() < 1e-12);
+///
+/// // Log returns add up to the log return of the whole period
+/// let log = returns(&prices, ReturnKind::Log).unwrap();
+/// assert!((log.iter().sum::<f64>() - (99.0f64 / 100.0).ln()).abs() < 1e-12);
+/// ```
+pub fn returns(prices: &[f64], kind: ReturnKind) -> Result<Vec<f64>, StatsError> {
+    match prices.len() {
+        0 => return Err(StatsError::EmptyInput),
+        1 => return Err(StatsError::InsufficientData),
+        _ => {}
+    }
+    if prices.iter().any(|p| p.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    if prices.iter().any(|p| *p <= 0.0) {
+        return Err(StatsError::NonPositivePrice);
+    }
+
+    Ok(prices
+        .windows(2)
+        .map(|w| match kind {
+            ReturnKind::Simple => w[1] / w[0] - 1.0,
+            ReturnKind::Log => (w[1] / w[0]).ln(),
+        })
+        .collect())
+}
+
+/// Last `field` price of each `period_ms` interval (UTC-aligned), labelled with the
+/// interval start. Intervals without ticks are left out; ticks without the `field`
+/// price are skipped.
+///
+/// # Errors
+/// * `InvalidWindow` - a period of zero
+/// * `MissingTimestamps`, `UnsortedTimestamps` - ticks without times or out of order
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::returns::resample;
+/// use quant_marketstat_ws::tick::{PriceField, Tick};
+///
+/// let tick = |ts: i64, price: f64| Tick { timestamp: Some(ts), ..Tick::from_quote(price, price, 1.0) };
+/// let ticks = [tick(1_000, 10.0), tick(59_000, 10.5), tick(61_000, 11.0)];
+///
+/// let closes = resample(&ticks, PriceField::Mid, 60_000).unwrap();
+/// assert_eq!(closes, vec![(0, 10.5), (60_000, 11.0)]);
+/// ```
+pub fn resample(ticks: &[Tick], field: PriceField, period_ms: i64) -> Result<Vec<(Timestamp, f64)>, StatsError> {
+    if period_ms <= 0 {
+        return Err(StatsError::InvalidWindow);
+    }
+    let mut closes: Vec<(Timestamp, f64)> = Vec::new();
+    let mut last_ts: Option<Timestamp> = None;
+
+    for tick in ticks {
+        let ts = tick.timestamp.ok_or(StatsError::MissingTimestamps)?;
+        if last_ts.is_some_and(|last| ts < last) {
+            return Err(StatsError::UnsortedTimestamps);
+        }
+        last_ts = Some(ts);
+
+        let Some(price) = field.of(tick) else { continue };
+        let start = ts.div_euclid(period_ms) * period_ms;
+        match closes.last_mut() {
+            Some(close) if close.0 == start => close.1 = price,
+            _ => closes.push((start, price)),
+        }
+    }
+    Ok(closes)
+}
+
+/// Returns of the `field` prices of `ticks`, tick to tick or between the closes of
+/// each `Frequency::Every` interval (see [`resample`]). Ticks without the `field`
+/// price are skipped; ticks of several symbols should be split first
+/// (`tick::for_symbol`).
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::returns::{tick_returns, ReturnKind};
+/// use quant_marketstat_ws::tick::{Frequency, PriceField, Tick};
+///
+/// let ticks = [Tick::from_quote(9.9, 10.1, 1.0), Tick::from_quote(10.9, 11.1, 1.0)];
+/// let series = tick_returns(&ticks, PriceField::Mid, ReturnKind::Simple, Frequency::Tick).unwrap();
+/// assert!((series[0].value - 0.10).abs() < 1e-12);
+/// ```
+pub fn tick_returns(
+    ticks: &[Tick],
+    field: PriceField,
+    kind: ReturnKind,
+    frequency: Frequency,
+) -> Result<Vec<ReturnPoint>, StatsError> {
+    let prices: Vec<(Option<Timestamp>, f64)> = match frequency {
+        Frequency::Tick => ticks
+            .iter()
+            .filter_map(|t| field.of(t).map(|price| (t.timestamp, price)))
+            .collect(),
+        Frequency::Every(ms) => resample(ticks, field, ms)?
+            .into_iter()
+            .map(|(ts, price)| (Some(ts), price))
+            .collect(),
+    };
+
+    let values = returns(&prices.iter().map(|(_, price)| *price).collect::<Vec<f64>>(), kind)?;
+    Ok(prices
+        .iter()
+        .skip(1)
+        .zip(values)
+        .map(|((timestamp, _), value)| ReturnPoint {
+            timestamp: *timestamp,
+            value,
+        })
+        .collect())
+}
+
+/// Values of a return series, for the `stats` functions.
+pub fn values(series: &[ReturnPoint]) -> Vec<f64> {
+    series.iter().map(|r| r.value).collect()
+}
@@ -0,0 +1,516 @@
+//! Value-at-Risk and Expected Shortfall.
+//!
+//! VaR is the loss not exceeded with a given confidence over a horizon;
+//! Expected Shortfall (CVaR) is the average loss beyond it. Both are computed
+//! from a P&L series in currency units, by historical simulation, parametric
+//! Gaussian or Student-t fits, or the Cornish–Fisher expansion (Gaussian
+//! corrected for skewness and kurtosis).
+//!
+//! A single price series becomes P&L through its returns times the position
+//! value ([`series_pnl`]); a portfolio of `pnl::AssetPosition`s through the
+//! returns of each asset times its exposure, summed per period
+//! ([`portfolio_pnl`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::distribution::{distribution, quantile};
+use crate::pnl::AssetPosition;
+use crate::returns::{self, resample, ReturnKind};
+use crate::stats::StatsError;
+use crate::tick::{PriceField, Tick, Timestamp};
+
+/// How the loss distribution is modelled.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::risk::VarMethod;
+///
+/// assert_eq!("hs".parse::<VarMethod>(), Ok(VarMethod::Historical));
+/// assert_eq!("Normal".parse::<VarMethod>(), Ok(VarMethod::Gaussian));
+/// assert_eq!("cf".parse::<VarMethod>(), Ok(VarMethod::CornishFisher));
+/// assert_eq!("student-t".parse::<VarMethod>(), Ok(VarMethod::StudentT(None)));
+/// assert_eq!("t:5".parse::<VarMethod>(), Ok(VarMethod::StudentT(Some(5.0))));
+/// assert_eq!(VarMethod::StudentT(Some(5.0)).to_string(), "student-t:5");
+/// assert!("t:2".parse::<VarMethod>().is_err()); // infinite variance
+/// assert!("monte-carlo".parse::<VarMethod>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VarMethod {
+    /// Empirical quantile of the P&L
+    #[default]
+    Historical,
+    /// Normal distribution with the sample mean and standard deviation
+    Gaussian,
+    /// Student-t with `dof` degrees of freedom scaled to the sample variance; `None` fits
+    /// them from the excess kurtosis (`4 + 6 / K`)
+    StudentT(Option<f64>),
+    /// Gaussian quantile adjusted for sample skewness and excess kurtosis
+    CornishFisher,
+}
+
+impl VarMethod {
+    pub const ALL: [VarMethod; 4] = [
+        VarMethod::Historical,
+        VarMethod::Gaussian,
+        VarMethod::StudentT(None),
+        VarMethod::CornishFisher,
+    ];
+}
+
+impl FromStr for VarMethod {
+    type Err = String;
+
+    /// `historical`, `gaussian`, `student-t` (fitted) or `student-t:5`, `cornish-fisher`.
+    fn from_str(s: &str) -> Result<VarMethod, String> {
+        let s = s.trim().to_ascii_lowercase();
+        let (name, dof) = match s.split_once(':') {
+            Some((name, dof)) => (name, Some(dof)),
+            None => (s.as_str(), None),
+        };
+        match (name, dof) {
+            ("historical" | "hs", None) => Ok(VarMethod::Historical),
+            ("gaussian" | "normal" | "parametric", None) => Ok(VarMethod::Gaussian),
+            ("cornish-fisher" | "cf", None) => Ok(VarMethod::CornishFisher),
+            ("student-t" | "t", None) => Ok(VarMethod::StudentT(None)),
+            ("student-t" | "t", Some(dof)) => match dof.parse::<f64>() {
+                Ok(dof) if dof > 2.0 => Ok(VarMethod::StudentT(Some(dof))),
+                _ => Err(format!("Invalid degrees of freedom '{}' (expected a number above 2)", dof)),
+            },
+            _ => Err(format!(
+                "Unknown VaR method '{}' (expected historical, gaussian, student-t[:DOF] or cornish-fisher)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VarMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarMethod::Historical => f.write_str("historical"),
+            VarMethod::Gaussian => f.write_str("gaussian"),
+            VarMethod::StudentT(None) => f.write_str("student-t"),
+            VarMethod::StudentT(Some(dof)) => write!(f, "student-t:{}", dof),
+            VarMethod::CornishFisher => f.write_str("cornish-fisher"),
+        }
+    }
+}
+
+/// VaR and Expected Shortfall of a P&L series, as positive losses in currency units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskEstimate {
+    pub method: VarMethod,
+    /// Confidence level, e.g. `0.99`
+    pub confidence: f64,
+    /// Horizon in periods of the P&L series
+    pub horizon: usize,
+    /// Loss not exceeded with probability `confidence`
+    pub var: f64,
+    /// Average loss in the `1 - confidence` worst cases
+    pub expected_shortfall: f64,
+    /// Degrees of freedom used by a Student-t estimate
+    pub dof: Option<f64>,
+}
+
+/// VaR and Expected Shortfall of `pnl` (one value per period, in currency units) at
+/// `confidence` over `horizon` periods. Parametric estimates scale the mean by the
+/// horizon and the volatility by its square root; the historical estimate scales the
+/// one-period figures by `sqrt(horizon)`.
+///
+/// # Errors
+/// * `EmptyInput`, `NaN` - invalid P&L
+/// * `InsufficientData` - fewer than 2 periods
+/// * `InvalidConfidence` - confidence outside (0, 1) or a zero horizon
+/// * `InvalidDegreesOfFreedom` - a fixed Student-t dof of 2 or less, or not finite
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::risk::{value_at_risk, VarMethod};
+/// use quant_marketstat_ws::stats::StatsError;
+///
+/// // Daily P&L of a 1,000,000 position with about 1% daily volatility
+/// let pnl: Vec<f64> = (0..1000).map(|i| if i % 2 == 0 { 10_000.0 } else { -10_000.0 }).collect();
+///
+/// // 1.645 and 2.063 standard deviations
+/// let risk = value_at_risk(&pnl, VarMethod::Gaussian, 0.95, 1).unwrap();
+/// assert!((risk.var - 16_456.8).abs() < 1.0);
+/// assert!((risk.expected_shortfall - 20_637.4).abs() < 1.0);
+///
+/// // 10-day horizon: sqrt(10) times larger
+/// let ten_days = value_at_risk(&pnl, VarMethod::Gaussian, 0.95, 10).unwrap();
+/// assert!((ten_days.var / risk.var - 10f64.sqrt()).abs() < 1e-9);
+///
+/// // Student-t with 5 degrees of freedom: t(0.95) = 2.015 on a unit-variance scale
+/// let t = value_at_risk(&pnl, VarMethod::StudentT(Some(5.0)), 0.95, 1).unwrap();
+/// assert!((t.var - 15_616.3).abs() < 1.0);
+/// assert_eq!(t.dof, Some(5.0));
+///
+/// // Fitted: this P&L has no excess kurtosis (-2), so the Gaussian limit
+/// let fitted = value_at_risk(&pnl, VarMethod::StudentT(None), 0.95, 1).unwrap();
+/// assert_eq!((fitted.var, fitted.dof), (risk.var, None));
+///
+/// // Two or fewer degrees of freedom have no finite variance to scale to
+/// assert_eq!(value_at_risk(&pnl, VarMethod::StudentT(Some(2.0)), 0.95, 1), Err(StatsError::InvalidDegreesOfFreedom));
+/// assert!(value_at_risk(&pnl, VarMethod::StudentT(Some(f64::NAN)), 0.95, 1).is_err());
+///
+/// // Cornish-Fisher: z = -1.645 moves to -1.685 for an excess kurtosis of -2
+/// let cf = value_at_risk(&pnl, VarMethod::CornishFisher, 0.95, 1).unwrap();
+/// assert!((cf.var - 16_860.6).abs() < 1.0);
+/// ```
+///
+/// Historical simulation and a fitted Student-t on fat tails:
+/// ```
+/// use quant_marketstat_ws::risk::{value_at_risk, VarMethod};
+///
+/// // Losses -50..49: the 95% quantile (type 7) is 44.05, the mean of 45..49 is 47
+/// let pnl: Vec<f64> = (1..=100).map(|i| i as f64 - 50.0).collect();
+/// let hs = value_at_risk(&pnl, VarMethod::Historical, 0.95, 4).unwrap();
+/// assert!((hs.var - 2.0 * 44.05).abs() < 1e-9);
+/// assert!((hs.expected_shortfall - 2.0 * 47.0).abs() < 1e-9);
+///
+/// // 10% at each of -1000 and +1000: excess kurtosis 2, so 4 + 6 / 2 = 7 degrees of freedom
+/// let fat: Vec<f64> = (0..100).map(|i| match i % 10 { 0 => -1000.0, 5 => 1000.0, _ => 0.0 }).collect();
+/// let t = value_at_risk(&fat, VarMethod::StudentT(None), 0.95, 1).unwrap();
+/// assert_eq!(t.dof, Some(7.0));
+/// assert!((t.var - 719.7).abs() < 0.1);
+/// ```
+pub fn value_at_risk(pnl: &[f64], method: VarMethod, confidence: f64, horizon: usize) -> Result<RiskEstimate, StatsError> {
+    if !(confidence > 0.0 && confidence < 1.0) || horizon == 0 {
+        return Err(StatsError::InvalidConfidence);
+    }
+    if let VarMethod::StudentT(Some(nu)) = method {
+        if !(nu > 2.0 && nu.is_finite()) {
+            return Err(StatsError::InvalidDegreesOfFreedom);
+        }
+    }
+    let shape = distribution(pnl)?;
+    if pnl.len() < 2 {
+        return Err(StatsError::InsufficientData);
+    }
+
+    let h = horizon as f64;
+    let tail = 1.0 - confidence;
+    let mean = shape.mean * h;
+    let sd = (pnl.iter().map(|v| (v - shape.mean).powi(2)).sum::<f64>() / (pnl.len() - 1) as f64).sqrt() * h.sqrt();
+    let mut dof = None;
+
+    let (var, expected_shortfall) = match method {
+        VarMethod::Historical => {
+            let losses: Vec<f64> = pnl.iter().map(|v| -v).collect();
+            let var = quantile(&losses, confidence)?;
+            let tail_losses: Vec<f64> = losses.iter().copied().filter(|l| *l >= var).collect();
+            let es = tail_losses.iter().sum::<f64>() / tail_losses.len() as f64;
+            (var * h.sqrt(), es * h.sqrt())
+        }
+        VarMethod::Gaussian => {
+            let z = normal_quantile(confidence);
+            (-mean + sd * z, -mean + sd * normal_pdf(z) / tail)
+        }
+        VarMethod::StudentT(fixed) => {
+            let nu = fixed.unwrap_or(match shape.excess_kurtosis {
+                k if k > 0.0 => 4.0 + 6.0 / k,
+                _ => f64::INFINITY,
+            });
+            if nu.is_finite() {
+                dof = Some(nu);
+                // Standard t has variance nu / (nu - 2): rescale to the sample volatility
+                let scale = sd * ((nu - 2.0) / nu).sqrt();
+                let t = t_quantile(confidence, nu);
+                let es_factor = t_pdf(t, nu) / tail * (nu + t * t) / (nu - 1.0);
+                (-mean + scale * t, -mean + scale * es_factor)
+            } else {
+                // No excess kurtosis to fit: the Gaussian limit
+                let z = normal_quantile(confidence);
+                (-mean + sd * z, -mean + sd * normal_pdf(z) / tail)
+            }
+        }
+        VarMethod::CornishFisher => {
+            let (s, k) = (shape.skewness, shape.excess_kurtosis);
+            let loss_at = |p: f64| {
+                // Lower-tail quantile of the return distribution, adjusted
+                let z = -normal_quantile(p);
+                let z_cf = z + (z * z - 1.0) * s / 6.0 + (z.powi(3) - 3.0 * z) * k / 24.0
+                    - (2.0 * z.powi(3) - 5.0 * z) * s * s / 36.0;
+                -(mean + z_cf * sd)
+            };
+            // Expected Shortfall: average of the adjusted VaR over the tail
+            const STEPS: usize = 1000;
+            let es = (0..STEPS)
+                .map(|i| loss_at(confidence + tail * (i as f64 + 0.5) / STEPS as f64))
+                .sum::<f64>()
+                / STEPS as f64;
+            (loss_at(confidence), es)
+        }
+    };
+
+    Ok(RiskEstimate {
+        method,
+        confidence,
+        horizon,
+        var,
+        expected_shortfall,
+        dof,
+    })
+}
+
+/// P&L per period of a position worth `value`: simple returns of `prices` times `value`.
+pub fn series_pnl(prices: &[f64], value: f64) -> Result<Vec<f64>, StatsError> {
+    Ok(returns::returns(prices, ReturnKind::Simple)?
+        .into_iter()
+        .map(|r| r * value)
+        .collect())
+}
+
+/// Exposure of a position at `price`: signed volume × multiplier × price.
+pub fn exposure(position: &AssetPosition, price: f64) -> f64 {
+    position.side.sign() * position.contracted_volume * position.multiplier() * price
+}
+
+/// Portfolio P&L scenarios built from historical prices.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PortfolioPnl {
+    /// End of each period
+    pub timestamps: Vec<Timestamp>,
+    /// Portfolio P&L of each period
+    pub pnl: Vec<f64>,
+    /// Current exposure per asset (signed, at the last price)
+    pub exposures: BTreeMap<String, f64>,
+    /// Assets of positions without prices in the data, left out
+    pub missing: Vec<String>,
+}
+
+/// P&L per `period_ms` period of the portfolio held today: each position's exposure at
+/// the last price times its asset's simple return over the period, summed over the
+/// periods where every priced asset has a return.
+///
+/// A tick with a symbol prices the positions with that `asset_id`; a tick without one
+/// prices every position.
+///
+/// # Errors
+/// * the errors of `returns::resample`
+/// * `EmptyInput` - no position has prices, or the assets have no common periods
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::pnl::{AssetPosition, PositionSide};
+/// use quant_marketstat_ws::risk::portfolio_pnl;
+/// use quant_marketstat_ws::tick::{PriceField, Tick};
+///
+/// let tick = |ts: i64, symbol: &str, price: f64| Tick {
+///     timestamp: Some(ts),
+///     symbol: Some(symbol.to_string()),
+///     ..Tick::from_quote(price, price, 0.0)
+/// };
+/// let ticks = [
+///     tick(0, "A", 100.0), tick(0, "B", 50.0),
+///     tick(60_000, "A", 110.0), tick(60_000, "B", 49.0),
+///     tick(120_000, "A", 99.0), tick(120_000, "B", 51.45),
+/// ];
+/// let positions = [
+///     AssetPosition { asset_id: "A".to_string(), contracted_volume: 10.0, ..Default::default() },
+///     AssetPosition { asset_id: "B".to_string(), contracted_volume: 100.0, side: PositionSide::Short, ..Default::default() },
+/// ];
+///
+/// let portfolio = portfolio_pnl(&positions, &ticks, PriceField::Bid, 60_000).unwrap();
+/// // Exposures at the last prices: 10 × 99 long, 100 × 51.45 short
+/// assert_eq!(portfolio.exposures["A"], 990.0);
+/// assert_eq!(portfolio.exposures["B"], -5145.0);
+/// // A +10%, B -2%: 99 + 102.9; then A -10%, B +5%: -99 - 257.25
+/// assert!((portfolio.pnl[0] - 201.9).abs() < 1e-9);
+/// assert!((portfolio.pnl[1] + 356.25).abs() < 1e-9);
+/// ```
+pub fn portfolio_pnl(positions: &[AssetPosition], ticks: &[Tick], field: PriceField, period_ms: i64) -> Result<PortfolioPnl, StatsError> {
+    let mut result = PortfolioPnl::default();
+    // Per asset: return of each period, keyed by period start
+    let mut asset_returns: Vec<(f64, HashMap<Timestamp, f64>)> = Vec::new();
+
+    let mut by_asset: BTreeMap<String, Vec<&AssetPosition>> = BTreeMap::new();
+    for position in positions {
+        by_asset.entry(position.asset_id.to_ascii_uppercase()).or_default().push(position);
+    }
+
+    for (asset, positions) in by_asset {
+        let asset_ticks: Vec<Tick> = ticks
+            .iter()
+            .filter(|t| t.symbol.as_deref().is_none_or(|s| s.eq_ignore_ascii_case(&asset)))
+            .cloned()
+            .collect();
+        let closes = resample(&asset_ticks, field, period_ms)?;
+        let Some(&(_, last_price)) = closes.last() else {
+            result.missing.push(positions[0].asset_id.clone());
+            continue;
+        };
+
+        let exposure: f64 = positions.iter().map(|p| exposure(p, last_price)).sum();
+        result.exposures.insert(positions[0].asset_id.clone(), exposure);
+        let mut period_returns = HashMap::new();
+        for w in closes.windows(2) {
+            if w[0].1 <= 0.0 {
+                return Err(StatsError::NonPositivePrice);
+            }
+            period_returns.insert(w[1].0, w[1].1 / w[0].1 - 1.0);
+        }
+        asset_returns.push((exposure, period_returns));
+    }
+
+    let Some((_, first)) = asset_returns.first() else {
+        return Err(StatsError::EmptyInput);
+    };
+    let mut timestamps: Vec<Timestamp> = first
+        .keys()
+        .copied()
+        .filter(|ts| asset_returns.iter().all(|(_, r)| r.contains_key(ts)))
+        .collect();
+    timestamps.sort_unstable();
+    if timestamps.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+
+    result.pnl = timestamps
+        .iter()
+        .map(|ts| asset_returns.iter().map(|(exposure, r)| exposure * r[ts]).sum())
+        .collect();
+    result.timestamps = timestamps;
+    Ok(result)
+}
+
+// DISTRIBUTIONS ----------------------------------------------------------------------
+/// Standard normal density.
+fn normal_pdf(z: f64) -> f64 {
+    (-z * z / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// Standard normal quantile (Acklam's rational approximation, relative error < 1.2e-9).
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00, 3.754408661907416e+00];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// ln Γ(x) for x > 0 (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = G[1..].iter().enumerate().fold(G[0], |sum, (i, g)| sum + g / (x + i as f64 + 1.0));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b) (continued fraction).
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(1.0 - x, b, a);
+    }
+
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp() / a;
+    const TINY: f64 = 1e-300;
+    let (mut c, mut d) = (1.0, 1.0 - (a + b) * x / (a + 1.0));
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut f = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + numerator / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            f *= c * d;
+        }
+        if (c * d - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    front * f
+}
+
+/// Student-t density with `nu` degrees of freedom.
+fn t_pdf(t: f64, nu: f64) -> f64 {
+    (ln_gamma((nu + 1.0) / 2.0) - ln_gamma(nu / 2.0)).exp() / (nu * PI).sqrt() * (1.0 + t * t / nu).powf(-(nu + 1.0) / 2.0)
+}
+
+/// Student-t distribution function.
+fn t_cdf(t: f64, nu: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(nu / (nu + t * t), nu / 2.0, 0.5);
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Student-t quantile, by bisection on the distribution function.
+fn t_quantile(p: f64, nu: f64) -> f64 {
+    let (mut low, mut high) = (-1e4, 1e4);
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if t_cdf(mid, nu) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+//-------------------------------------------------------------------------------------
@@ -0,0 +1,219 @@
+//! Rolling-window VWAP and volatility series.
+//!
+//! Unlike `stats::vwap` and `stats::std_vwap`, which return one number for the
+//! whole input, these functions return one value per input tick, computed over
+//! the window ending at that tick. The output is aligned with the input, so it
+//! can be plotted next to the prices or used in signals.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::stats::StatsError;
+use crate::tick::{parse_duration, Timestamp};
+
+/// Size of a rolling window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// Last `n` ticks. The first `n - 1` outputs are `None` (window not full yet).
+    Count(usize),
+    /// Ticks in the last `ms` milliseconds, i.e. with `t_now - ms < t <= t_now`.
+    /// Requires timestamps in chronological order.
+    Time(i64),
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    /// `"20"` is a 20-tick window; `"30s"`, `"5m"`, `"1h"` are time windows.
+    fn from_str(s: &str) -> Result<Window, String> {
+        if let Ok(count) = s.trim().parse::<usize>() {
+            return Ok(Window::Count(count));
+        }
+        parse_duration(s)
+            .map(Window::Time)
+            .ok_or_else(|| format!("Invalid window '{}' (expected a tick count like 20 or a duration like 5m)", s))
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Window::Count(n) => write!(f, "{} ticks", n),
+            Window::Time(ms) => write!(f, "{} ms", ms),
+        }
+    }
+}
+
+/// Start index of the window ending at each tick, or `None` while a count window is not full.
+fn window_starts(len: usize, window: Window, timestamps: Option<&[Timestamp]>) -> Result<Vec<Option<usize>>, StatsError> {
+    match window {
+        Window::Count(0) | Window::Time(..=0) => Err(StatsError::InvalidWindow),
+
+        Window::Count(n) => Ok((0..len).map(|i| (i + 1 >= n).then(|| i + 1 - n)).collect()),
+
+        Window::Time(span) => {
+            let ts = timestamps.ok_or(StatsError::MissingTimestamps)?;
+            if ts.len() != len {
+                return Err(StatsError::LengthMismatch);
+            }
+            if ts.windows(2).any(|w| w[1] < w[0]) {
+                return Err(StatsError::UnsortedTimestamps);
+            }
+
+            let mut start = 0;
+            Ok((0..len)
+                .map(|i| {
+                    while ts[start] <= ts[i] - span {
+                        start += 1;
+                    }
+                    Some(start)
+                })
+                .collect())
+        }
+    }
+}
+
+/// Prefix sums of `v`, `v·(p - p0)` and `v·(p - p0)²`, shifted by the first price `p0`
+/// to limit cancellation when window sums are subtracted.
+struct PrefixSums {
+    shift: f64,
+    volume: Vec<f64>,
+    value: Vec<f64>,
+    square: Vec<f64>,
+}
+
+impl PrefixSums {
+    fn new(prices: &[f64], volumes: &[f64]) -> PrefixSums {
+        let shift = prices.first().copied().unwrap_or(0.0);
+        let mut sums = PrefixSums {
+            shift,
+            volume: vec![0.0],
+            value: vec![0.0],
+            square: vec![0.0],
+        };
+
+        for (p, v) in prices.iter().zip(volumes.iter()) {
+            let d = p - shift;
+            sums.volume.push(sums.volume.last().unwrap() + v);
+            sums.value.push(sums.value.last().unwrap() + v * d);
+            sums.square.push(sums.square.last().unwrap() + v * d * d);
+        }
+        sums
+    }
+
+    /// (Σv, VWAP, Σv·(p - VWAP)²) over ticks `start..=end`, or `None` when Σv is zero.
+    fn window(&self, start: usize, end: usize) -> Option<(f64, f64, f64)> {
+        let volume = self.volume[end + 1] - self.volume[start];
+        if volume <= 0.0 {
+            return None;
+        }
+        let value = self.value[end + 1] - self.value[start];
+        let square = self.square[end + 1] - self.square[start];
+
+        let mean_shifted = value / volume;
+        let squared_diffs = (square - value * mean_shifted).max(0.0);
+        Some((volume, mean_shifted + self.shift, squared_diffs))
+    }
+}
+
+/// Checks prices and volumes (same length, no NaN) before a rolling computation.
+fn check_input(prices: &[f64], volumes: &[f64]) -> Result<(), StatsError> {
+    if prices.len() != volumes.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    if prices.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if prices.iter().chain(volumes.iter()).any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    Ok(())
+}
+
+//Rolling VWAP -----------------------------------------------------------------------
+/// Calculates the VWAP over a rolling window ending at each tick.
+///
+/// # Formula
+/// $$
+/// VWAP_t = \frac{\sum_{i \in W_t} P_i V_i}{\sum_{i \in W_t} V_i}
+/// $$
+///
+/// # Arguments
+/// * `prices`, `volumes` - tick prices and volumes
+/// * `window` - `Window::Count(n)` or `Window::Time(ms)`
+/// * `timestamps` - tick times, required for time windows
+///
+/// # Returns
+/// * One value per tick; `None` while a count window is not full or when the window volume is zero.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::rolling::{rolling_vwap, Window};
+///
+/// let prices = [10.0, 11.0, 12.0];
+/// let volumes = [100.0, 100.0, 200.0];
+/// let series = rolling_vwap(&prices, &volumes, Window::Count(2), None).unwrap();
+/// assert_eq!(series, vec![None, Some(10.5), Some(35.0 / 3.0)]);
+/// ```
+pub fn rolling_vwap(
+    prices: &[f64],
+    volumes: &[f64],
+    window: Window,
+    timestamps: Option<&[Timestamp]>,
+) -> Result<Vec<Option<f64>>, StatsError> {
+    check_input(prices, volumes)?;
+    let starts = window_starts(prices.len(), window, timestamps)?;
+    let sums = PrefixSums::new(prices, volumes);
+
+    Ok(starts
+        .iter()
+        .enumerate()
+        .map(|(end, start)| start.and_then(|start| sums.window(start, end)).map(|(_, vwap, _)| vwap))
+        .collect())
+}
+//-------------------------------------------------------------------------------------
+
+//Rolling VWAP Variance & STD ------------------------------------------------------------
+/// Calculates the volume-weighted variance around the rolling VWAP at each tick.
+///
+/// Same weighting as `stats::variance_vwap`: `Σv(p - VWAP)² / Σv` (population)
+/// or `/ (Σv - 1)` (sample).
+///
+/// # Returns
+/// * One value per tick; `None` while a count window is not full, when the window
+///   volume is zero, or when `Σv - 1 <= 0` for a sample estimate.
+pub fn rolling_variance_vwap(
+    prices: &[f64],
+    volumes: &[f64],
+    window: Window,
+    timestamps: Option<&[Timestamp]>,
+    is_population: bool,
+) -> Result<Vec<Option<f64>>, StatsError> {
+    check_input(prices, volumes)?;
+    let starts = window_starts(prices.len(), window, timestamps)?;
+    let sums = PrefixSums::new(prices, volumes);
+
+    Ok(starts
+        .iter()
+        .enumerate()
+        .map(|(end, start)| {
+            let (volume, _, squared_diffs) = sums.window((*start)?, end)?;
+            let denominator = if is_population { volume } else { volume - 1.0 };
+            (denominator > 0.0).then(|| squared_diffs / denominator)
+        })
+        .collect())
+}
+
+/// Calculates the rolling volume-weighted standard deviation around the rolling VWAP
+/// (rolling volatility), i.e. the square root of [`rolling_variance_vwap`].
+pub fn rolling_std_vwap(
+    prices: &[f64],
+    volumes: &[f64],
+    window: Window,
+    timestamps: Option<&[Timestamp]>,
+    is_population: bool,
+) -> Result<Vec<Option<f64>>, StatsError> {
+    let variances = rolling_variance_vwap(prices, volumes, window, timestamps, is_population)?;
+    Ok(variances.into_iter().map(|v| v.map(f64::sqrt)).collect())
+}
+//-------------------------------------------------------------------------------------
@@ -0,0 +1,113 @@
+//! In-memory dataset kept alive between menu choices.
+//!
+//! Data loaded from CSV (option 12) or typed by hand stays here, so options
+//! 1–11 can be run repeatedly against the same ticks.
+
+use quant_marketstat_ws::csv_loader::MarketColumns;
+use quant_marketstat_ws::stats::BookSizes;
+use quant_marketstat_ws::tick::{self, format_timestamp, Tick};
+
+/// Ticks currently loaded in the interactive session.
+#[derive(Debug, Default, Clone)]
+pub struct Session {
+    pub ticks: Vec<Tick>,
+
+    /// Where the data came from (CSV path or "manual entry")
+    pub source: Option<String>,
+}
+
+impl Session {
+    /// True when a dataset is loaded.
+    pub fn is_loaded(&self) -> bool {
+        !self.ticks.is_empty()
+    }
+
+    /// Number of ticks in the dataset.
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    /// Replaces the current dataset with new ticks.
+    pub fn replace(&mut self, ticks: Vec<Tick>, source: &str) {
+        self.ticks = ticks;
+        self.source = Some(source.to_string());
+    }
+
+    /// Appends ticks to the end of the current dataset.
+    pub fn append(&mut self, ticks: Vec<Tick>, source: &str) {
+        if !self.is_loaded() {
+            self.replace(ticks, source);
+            return;
+        }
+
+        self.ticks.extend(ticks);
+        if let Some(current) = &mut self.source {
+            current.push_str(" + ");
+            current.push_str(source);
+        }
+    }
+
+    /// Drops the current dataset.
+    pub fn clear(&mut self) {
+        *self = Session::default();
+    }
+
+    /// (bids, asks, volumes) columns of the dataset.
+    pub fn columns(&self) -> MarketColumns {
+        tick::columns(&self.ticks)
+    }
+
+    /// Returns a price series from the dataset: "bid", "ask" or "mid" ((bid + ask) / 2).
+    pub fn column(&self, name: &str) -> Option<Vec<f64>> {
+        match name {
+            "bid" => Some(self.ticks.iter().map(|t| t.bid).collect()),
+            "ask" => Some(self.ticks.iter().map(|t| t.ask).collect()),
+            "mid" => Some(tick::mids(&self.ticks)),
+            _ => None,
+        }
+    }
+
+    /// (bid sizes, ask sizes) of the dataset, or `None` unless every tick has both.
+    pub fn book_sizes(&self) -> Option<BookSizes> {
+        let sizes: Option<Vec<(f64, f64)>> = self.ticks.iter().map(|t| Some((t.bid_size?, t.ask_size?))).collect();
+        sizes.map(|sizes| sizes.into_iter().unzip())
+    }
+
+    /// Volumes of the dataset.
+    pub fn volumes(&self) -> Vec<f64> {
+        self.ticks.iter().map(|t| t.volume).collect()
+    }
+
+    /// One-line description used in the menu header.
+    pub fn status(&self) -> String {
+        match &self.source {
+            Some(source) if self.is_loaded() => format!("{} rows loaded from {}", self.len(), source),
+            _ => "no dataset loaded".to_string(),
+        }
+    }
+
+    /// Prints the dataset as a table.
+    pub fn show(&self) {
+        if !self.is_loaded() {
+            println!("No dataset loaded. Use option 12, 'append' or 'replace' to load data.");
+            return;
+        }
+
+        println!("\nDataset: {}", self.status());
+        println!(
+            "{:>5}  {:<24}  {:<10}  {:>12}  {:>12}  {:>12}",
+            "#", "Timestamp", "Symbol", "Bid", "Ask", "Volume"
+        );
+        for (i, t) in self.ticks.iter().enumerate() {
+            println!(
+                "{:>5}  {:<24}  {:<10}  {:>12.4}  {:>12.4}  {:>12.2}",
+                i + 1,
+                t.timestamp.map(format_timestamp).unwrap_or_else(|| "-".to_string()),
+                t.symbol.as_deref().unwrap_or("-"),
+                t.bid,
+                t.ask,
+                t.volume
+            );
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Bid–ask spread analytics.
+//!
+//! `vwap_group` averages bid and ask into a mid price and the spread between
+//! them is lost. This module measures it: the quoted spread of each quote, in
+//! price units and basis points of the mid, its simple, time-weighted and
+//! volume-weighted averages and its percentiles. When the ticks carry trade
+//! prices, it also measures what trading actually cost: the effective spread
+//! (twice the distance from the trade to the mid) and the realized spread
+//! against the mid some time after the trade, whose difference is the price
+//! impact.
+//!
+//! Ticks of several symbols should be split first (`tick::for_symbol`).
+
+use std::fmt;
+
+use crate::distribution::quantile;
+use crate::rolling::Window;
+use crate::stats::StatsError;
+use crate::tick::Tick;
+
+const BPS: f64 = 10_000.0;
+
+/// Quoted spread `ask - bid` of a tick.
+pub fn quoted_spread(tick: &Tick) -> f64 {
+    tick.ask - tick.bid
+}
+
+/// Quoted spread in basis points of the mid price.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::spread::spread_bps;
+/// use quant_marketstat_ws::tick::Tick;
+///
+/// let tick = Tick::from_quote(99.95, 100.05, 1.0);
+/// assert!((spread_bps(&tick) - 10.0).abs() < 1e-9);
+/// ```
+pub fn spread_bps(tick: &Tick) -> f64 {
+    quoted_spread(tick) / tick.mid() * BPS
+}
+
+/// Weighted mean, `None` when the weights add up to zero.
+fn weighted_average(values: &[f64], weights: &[f64]) -> Option<f64> {
+    let total = weights.iter().fold(0.0, |total, w| total + w);
+    (total > 0.0).then(|| values.iter().zip(weights).map(|(v, w)| v * w).sum::<f64>() / total)
+}
+
+/// Checks the quotes: not empty, free of NaN and with a positive mid.
+fn check_quotes(ticks: &[Tick]) -> Result<(), StatsError> {
+    if ticks.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if ticks.iter().any(|t| t.bid.is_nan() || t.ask.is_nan() || t.volume.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    if ticks.iter().any(|t| t.mid() <= 0.0) {
+        return Err(StatsError::NonPositivePrice);
+    }
+    Ok(())
+}
+
+// QUOTED SPREAD ----------------------------------------------------------------------
+/// A spread in price units and in basis points of the mid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpreadValue {
+    pub absolute: f64,
+    pub bps: f64,
+}
+
+/// Quoted spread statistics of a quote series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadSummary {
+    /// Number of quotes
+    pub count: usize,
+    /// Quotes with the ask below the bid
+    pub crossed: usize,
+    pub average: SpreadValue,
+    /// Each quote weighted by how long it stood (until the next tick); `None` without
+    /// timestamps in chronological order
+    pub time_weighted: Option<SpreadValue>,
+    /// Each quote weighted by the volume traded on it; `None` without volume
+    pub volume_weighted: Option<SpreadValue>,
+    pub min: SpreadValue,
+    pub max: SpreadValue,
+    /// Requested percentiles, as `(quantile, spread)`
+    pub percentiles: Vec<(f64, SpreadValue)>,
+}
+
+/// Quoted spread statistics of `ticks`, with the spread at each of `quantiles` (0 to 1).
+///
+/// # Errors
+/// * `EmptyInput` - no ticks
+/// * `NaN` - a bid, ask or volume is NaN
+/// * `NonPositivePrice` - a mid price of zero or less
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::spread::spread_summary;
+/// use quant_marketstat_ws::tick::Tick;
+///
+/// let quote = |ts: i64, bid: f64, ask: f64, volume: f64| Tick {
+///     timestamp: Some(ts),
+///     ..Tick::from_quote(bid, ask, volume)
+/// };
+/// // Wide spread for one second, tight for nine
+/// let ticks = [quote(0, 9.9, 10.1, 100.0), quote(1_000, 9.99, 10.01, 300.0), quote(10_000, 9.99, 10.01, 0.0)];
+///
+/// let summary = spread_summary(&ticks, &[0.5]).unwrap();
+/// assert!((summary.average.absolute - 0.08).abs() < 1e-9);
+/// assert!((summary.time_weighted.unwrap().absolute - 0.038).abs() < 1e-9);
+/// assert!((summary.volume_weighted.unwrap().absolute - 0.065).abs() < 1e-9);
+/// ```
+pub fn spread_summary(ticks: &[Tick], quantiles: &[f64]) -> Result<SpreadSummary, StatsError> {
+    check_quotes(ticks)?;
+    let absolute: Vec<f64> = ticks.iter().map(quoted_spread).collect();
+    let bps: Vec<f64> = ticks.iter().map(spread_bps).collect();
+    let weighted = |weights: &[f64]| {
+        Some(SpreadValue {
+            absolute: weighted_average(&absolute, weights)?,
+            bps: weighted_average(&bps, weights)?,
+        })
+    };
+    let at = |q: f64| -> Result<SpreadValue, StatsError> {
+        Ok(SpreadValue {
+            absolute: quantile(&absolute, q)?,
+            bps: quantile(&bps, q)?,
+        })
+    };
+
+    let timed = ticks.iter().all(|t| t.timestamp.is_some()) && ticks.windows(2).all(|w| w[0].timestamp <= w[1].timestamp);
+    let durations: Vec<f64> = match timed {
+        true => ticks
+            .windows(2)
+            .map(|w| (w[1].timestamp.unwrap_or(0) - w[0].timestamp.unwrap_or(0)) as f64)
+            .chain(std::iter::once(0.0))
+            .collect(),
+        false => vec![0.0; ticks.len()],
+    };
+    let volumes: Vec<f64> = ticks.iter().map(|t| t.volume).collect();
+
+    Ok(SpreadSummary {
+        count: ticks.len(),
+        crossed: absolute.iter().filter(|s| **s < 0.0).count(),
+        average: weighted(&vec![1.0; ticks.len()]).ok_or(StatsError::EmptyInput)?,
+        time_weighted: weighted(&durations),
+        volume_weighted: weighted(&volumes),
+        min: at(0.0)?,
+        max: at(1.0)?,
+        percentiles: quantiles.iter().map(|q| Ok((*q, at(*q)?))).collect::<Result<_, StatsError>>()?,
+    })
+}
+//-------------------------------------------------------------------------------------
+
+// TRADES -----------------------------------------------------------------------------
+/// Aggressor side of a trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    /// Buyer-initiated (lifted the ask)
+    Buy,
+    /// Seller-initiated (hit the bid)
+    Sell,
+}
+
+impl TradeSide {
+    /// `+1` for a buy, `-1` for a sell.
+    pub fn sign(&self) -> f64 {
+        match self {
+            TradeSide::Buy => 1.0,
+            TradeSide::Sell => -1.0,
+        }
+    }
+}
+
+impl fmt::Display for TradeSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TradeSide::Buy => "buy",
+            TradeSide::Sell => "sell",
+        })
+    }
+}
+
+/// Side of a trade at `price` against the prevailing `mid` (Lee–Ready): above the mid is a
+/// buy, below a sell; at the mid, the tick test against the `previous` trade price
+/// decides (an uptick is a buy). `None` when it cannot be told.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::spread::{classify_trade, TradeSide};
+///
+/// assert_eq!(classify_trade(10.05, 10.0, None), Some(TradeSide::Buy));
+/// assert_eq!(classify_trade(10.0, 10.0, Some(10.02)), Some(TradeSide::Sell));
+/// ```
+pub fn classify_trade(price: f64, mid: f64, previous: Option<f64>) -> Option<TradeSide> {
+    let reference = if price != mid { mid } else { previous? };
+    if price > reference {
+        Some(TradeSide::Buy)
+    } else if price < reference {
+        Some(TradeSide::Sell)
+    } else {
+        None
+    }
+}
+
+/// Realized spread and price impact over one horizon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RealizedSpread {
+    pub horizon: Window,
+    /// Trades whose horizon ends within the data
+    pub count: usize,
+    /// `2·D·(p - mid at t + horizon)`: what the liquidity provider kept; `None` when
+    /// no horizon ends within the data
+    pub realized: Option<SpreadValue>,
+    /// `2·D·(mid at t + horizon - mid at t)`: what the market moved against them
+    pub price_impact: Option<SpreadValue>,
+}
+
+/// Effective and realized spreads of the trades of a tick series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionSpreads {
+    /// Trades with a side (classified trades)
+    pub trades: usize,
+    /// Trades whose side could not be classified, left out
+    pub unclassified: usize,
+    /// `2·D·(p - mid)`, averaged over the trades
+    pub effective: SpreadValue,
+    /// Effective spread weighted by trade volume; `None` without volume
+    pub effective_volume_weighted: Option<SpreadValue>,
+    /// One entry per requested horizon
+    pub realized: Vec<RealizedSpread>,
+}
+
+/// Effective spread of the trades of `ticks` (ticks with a `last_price`, priced against
+/// the quote of the same tick) and realized spread at each of `horizons`: a tick count
+/// ahead, or a duration ahead (the last quote at or before `t + horizon`).
+///
+/// # Errors
+/// * `EmptyInput` - no classifiable trade
+/// * `NaN`, `NonPositivePrice` - invalid quotes
+/// * `MissingTimestamps`, `UnsortedTimestamps` - for duration horizons
+/// * `InvalidWindow` - a horizon of zero
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::rolling::Window;
+/// use quant_marketstat_ws::spread::execution_spreads;
+/// use quant_marketstat_ws::tick::Tick;
+///
+/// // A buy at the ask of a 10.00 x 10.02 market, after which the mid moves up one cent
+/// let trade = Tick { last_price: Some(10.02), ..Tick::from_quote(10.00, 10.02, 100.0) };
+/// let later = Tick::from_quote(10.01, 10.03, 0.0);
+///
+/// let spreads = execution_spreads(&[trade, later], &[Window::Count(1)]).unwrap();
+/// assert!((spreads.effective.absolute - 0.02).abs() < 1e-9);
+/// let one_tick = spreads.realized[0];
+/// assert!(one_tick.realized.unwrap().absolute.abs() < 1e-9);
+/// assert!((one_tick.price_impact.unwrap().absolute - 0.02).abs() < 1e-9);
+/// ```
+pub fn execution_spreads(ticks: &[Tick], horizons: &[Window]) -> Result<ExecutionSpreads, StatsError> {
+    check_quotes(ticks)?;
+    if horizons.iter().any(|h| matches!(h, Window::Count(0) | Window::Time(..=0))) {
+        return Err(StatsError::InvalidWindow);
+    }
+    if horizons.iter().any(|h| matches!(h, Window::Time(_))) {
+        if ticks.iter().any(|t| t.timestamp.is_none()) {
+            return Err(StatsError::MissingTimestamps);
+        }
+        if ticks.windows(2).any(|w| w[1].timestamp < w[0].timestamp) {
+            return Err(StatsError::UnsortedTimestamps);
+        }
+    }
+
+    // (tick index, side, price, volume) of each classified trade
+    let mut trades: Vec<(usize, TradeSide, f64, f64)> = Vec::new();
+    let mut unclassified = 0;
+    let mut previous = None;
+    for (i, tick) in ticks.iter().enumerate() {
+        let Some(price) = tick.last_price else { continue };
+        if price.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        match classify_trade(price, tick.mid(), previous) {
+            Some(side) => trades.push((i, side, price, tick.volume)),
+            None => unclassified += 1,
+        }
+        previous = Some(price);
+    }
+    if trades.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+
+    // Spread of each trade against a mid, in price units and in bps of the trade's own mid
+    let spreads = |of: &dyn Fn(f64, f64, f64) -> f64, later: &dyn Fn(usize) -> Option<f64>| {
+        let (mut absolute, mut bps, mut volumes) = (Vec::new(), Vec::new(), Vec::new());
+        for (i, side, price, volume) in &trades {
+            let Some(mid_after) = later(*i) else { continue };
+            let mid = ticks[*i].mid();
+            let value = 2.0 * side.sign() * of(*price, mid, mid_after);
+            absolute.push(value);
+            bps.push(value / mid * BPS);
+            volumes.push(*volume);
+        }
+        (absolute, bps, volumes)
+    };
+    let value = |absolute: &[f64], bps: &[f64], weights: &[f64]| {
+        Some(SpreadValue {
+            absolute: weighted_average(absolute, weights)?,
+            bps: weighted_average(bps, weights)?,
+        })
+    };
+
+    let (absolute, bps, volumes) = spreads(&|price, mid, _| price - mid, &|i| Some(ticks[i].mid()));
+    let effective = value(&absolute, &bps, &vec![1.0; absolute.len()]).ok_or(StatsError::EmptyInput)?;
+    let effective_volume_weighted = value(&absolute, &bps, &volumes);
+
+    let realized = horizons
+        .iter()
+        .map(|horizon| {
+            let later = |i: usize| -> Option<f64> {
+                let j = match horizon {
+                    Window::Count(n) => i + n,
+                    Window::Time(ms) => {
+                        let target = ticks[i].timestamp? + ms;
+                        if ticks[ticks.len() - 1].timestamp? < target {
+                            return None;
+                        }
+                        ticks.partition_point(|t| t.timestamp.is_some_and(|ts| ts <= target)) - 1
+                    }
+                };
+                ticks.get(j).map(Tick::mid)
+            };
+            let (kept, kept_bps, _) = spreads(&|price, _, mid_after| price - mid_after, &later);
+            let (impact, impact_bps, _) = spreads(&|_, mid, mid_after| mid_after - mid, &later);
+            let ones = vec![1.0; kept.len()];
+            RealizedSpread {
+                horizon: *horizon,
+                count: kept.len(),
+                realized: value(&kept, &kept_bps, &ones),
+                price_impact: value(&impact, &impact_bps, &ones),
+            }
+        })
+        .collect();
+
+    Ok(ExecutionSpreads {
+        trades: trades.len(),
+        unclassified,
+        effective,
+        effective_volume_weighted,
+        realized,
+    })
+}
+//-------------------------------------------------------------------------------------
@@ -1,3 +1,290 @@
+use std::fmt;
+
+use crate::book;
+use crate::distribution::{distribution, weighted_distribution, Distribution};
+
+// RESULT TYPES ---------------------------------------------------------------
+/// Reasons why a statistic cannot be computed from the given input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsError {
+    /// No values were provided
+    EmptyInput,
+    /// Input slices (prices, volumes, bids, asks) have different lengths
+    LengthMismatch,
+    /// The sum of volumes is zero, so no volume-weighted value exists
+    ZeroVolume,
+    /// The input contains NaN values
+    NaN,
+    /// Not enough observations for a sample estimate (n - 1 <= 0)
+    InsufficientData,
+    /// A time-based calculation was requested but timestamps are missing
+    MissingTimestamps,
+    /// Timestamps are not in chronological order
+    UnsortedTimestamps,
+    /// A rolling window of zero ticks or zero duration
+    InvalidWindow,
+    /// A return was requested from a zero or negative price
+    NonPositivePrice,
+    /// A confidence level outside (0, 1) or a horizon of zero periods
+    InvalidConfidence,
+    /// Student-t degrees of freedom of 2 or less (infinite variance), or not finite
+    InvalidDegreesOfFreedom,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            StatsError::EmptyInput => "No data provided.",
+            StatsError::LengthMismatch => "Invalid input: all input slices must have the same length.",
+            StatsError::ZeroVolume => "Total volume is zero. Cannot calculate VWAP.",
+            StatsError::NaN => "Invalid input: data contains NaN values.",
+            StatsError::InsufficientData => "Not enough data for a sample estimate (needs more than one observation).",
+            StatsError::MissingTimestamps => "Timestamps are required for a time-based calculation.",
+            StatsError::UnsortedTimestamps => "Timestamps must be in chronological order.",
+            StatsError::InvalidWindow => "Window must be at least one tick or a positive duration.",
+            StatsError::NonPositivePrice => "Returns need positive prices.",
+            StatsError::InvalidConfidence => "Confidence level must be between 0 and 1, and the horizon at least one period.",
+            StatsError::InvalidDegreesOfFreedom => "Student-t degrees of freedom must be a finite number above 2.",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Market signal obtained by comparing a VWAP against the current (last) price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketSignal {
+    /// VWAP above the current price: trading below the day's average value
+    Weak,
+    /// VWAP below the current price: trading above the day's average value
+    Strong,
+    /// VWAP equal to the current price
+    Neutral,
+}
+
+impl MarketSignal {
+    /// Classifies a VWAP against the current price.
+    pub fn from_prices(vwap: f64, current_price: f64) -> MarketSignal {
+        if vwap > current_price {
+            MarketSignal::Weak
+        } else if vwap < current_price {
+            MarketSignal::Strong
+        } else {
+            MarketSignal::Neutral
+        }
+    }
+
+    /// Short machine-readable label: "weak", "strong" or "neutral".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketSignal::Weak => "weak",
+            MarketSignal::Strong => "strong",
+            MarketSignal::Neutral => "neutral",
+        }
+    }
+
+    /// Human-readable interpretation of the signal.
+    pub fn description(&self) -> &'static str {
+        match self {
+            MarketSignal::Weak => "VWAP is above the current price → weak market (trading below the day's average value)",
+            MarketSignal::Strong => "VWAP is below the current price → strong market (trading above the day's average value)",
+            MarketSignal::Neutral => "VWAP is equal to the current price → neutral market",
+        }
+    }
+}
+
+impl fmt::Display for MarketSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Result of a VWAP calculation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VwapResult {
+    /// The volume weighted average price
+    pub value: f64,
+    /// Price the VWAP was compared against (last traded price, or last ask for VWAP Group)
+    pub last_price: f64,
+    /// Market signal from comparing `value` against `last_price`
+    pub signal: MarketSignal,
+}
+
+/// Volatility bucket of a dispersion measure (variance or STD).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl VolatilityLevel {
+    /// Buckets a variance: `< 0.5` low, `< 2.0` moderate, otherwise high.
+    pub fn from_variance(variance: f64) -> VolatilityLevel {
+        if variance < 0.5 {
+            VolatilityLevel::Low
+        } else if variance < 2.0 {
+            VolatilityLevel::Moderate
+        } else {
+            VolatilityLevel::High
+        }
+    }
+
+    /// Short machine-readable label: "low", "moderate" or "high".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolatilityLevel::Low => "low",
+            VolatilityLevel::Moderate => "moderate",
+            VolatilityLevel::High => "high",
+        }
+    }
+
+    /// Buckets a standard deviation: `< 0.7` low, `< 1.5` moderate, otherwise high.
+    pub fn from_std(std: f64) -> VolatilityLevel {
+        if std < 0.7 {
+            VolatilityLevel::Low
+        } else if std < 1.5 {
+            VolatilityLevel::Moderate
+        } else {
+            VolatilityLevel::High
+        }
+    }
+}
+
+/// Which dispersion statistic a [`DispersionResult`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    Variance,
+    Std,
+    VwapVariance,
+    VwapStd,
+    GroupVariance,
+    GroupStd,
+}
+
+/// Result of a variance or standard deviation calculation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DispersionResult {
+    /// The variance or standard deviation
+    pub value: f64,
+    /// Volatility bucket of the value
+    pub level: VolatilityLevel,
+    /// Statistic that produced the value
+    pub measure: Measure,
+}
+
+impl DispersionResult {
+    /// Human-readable market interpretation of the result.
+    pub fn interpretation(&self) -> &'static str {
+        use Measure::*;
+        use VolatilityLevel::*;
+
+        match (self.measure, self.level) {
+            (Variance | Std, Low) => "Low variance → stable market with low volatility",
+            (Variance | Std, Moderate) => "Moderate variance → normal market fluctuations",
+            (Variance | Std, High) => "High variance → volatile market with larger price swings",
+            (VwapVariance, Low) => "Low VWAP variance → stable volume-weighted market with small deviations from VWAP",
+            (VwapVariance, Moderate) => "Moderate VWAP variance → typical market volume-weighted fluctuation",
+            (VwapVariance, High) => "High VWAP variance → volatile market with large volume-weighted deviations from VWAP",
+            (VwapStd, Low) => "Low VWAP STD → low dispersion of prices around VWAP",
+            (VwapStd, Moderate) => "Moderate VWAP STD → normal dispersion of prices around VWAP",
+            (VwapStd, High) => "High VWAP STD → significant dispersion from VWAP, indicating higher risk",
+            (GroupVariance, Low) => "Low variance: stable market",
+            (GroupVariance, Moderate) => "Medium variance: moderate activity",
+            (GroupVariance, High) => "High variance: volatile market",
+            (GroupStd, Low) => "Low STD: low volatility",
+            (GroupStd, Moderate) => "Moderate STD: watch the market",
+            (GroupStd, High) => "High STD: market unstable",
+        }
+    }
+}
+
+/// Price each quote contributes to the VWAP Group family.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GroupPrice<'a> {
+    /// `(bid + ask) / 2`
+    #[default]
+    Mid,
+    /// Size-weighted microprice (`book::microprice`), from the sizes at the best bid and ask
+    Microprice { bid_sizes: &'a [f64], ask_sizes: &'a [f64] },
+}
+
+/// Bid and ask sizes at the top of the book, one pair of columns per quote.
+pub type BookSizes = (Vec<f64>, Vec<f64>);
+
+impl GroupPrice<'_> {
+    /// The microprice when `sizes` were loaded, else the mid.
+    pub fn from_sizes(sizes: &Option<BookSizes>) -> GroupPrice<'_> {
+        match sizes {
+            Some((bid_sizes, ask_sizes)) => GroupPrice::Microprice { bid_sizes, ask_sizes },
+            None => GroupPrice::Mid,
+        }
+    }
+}
+
+/// Checks a single series: not empty and free of NaN.
+fn check_series(values: &[f64]) -> Result<(), StatsError> {
+    if values.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    Ok(())
+}
+
+/// Checks prices and volumes: same length, not empty, free of NaN and with non-zero total volume.
+/// Returns the total volume.
+fn check_weighted(prices: &[f64], volumes: &[f64]) -> Result<f64, StatsError> {
+    if prices.len() != volumes.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    check_series(prices)?;
+    check_series(volumes)?;
+
+    let total_volume: f64 = volumes.iter().sum();
+    if total_volume == 0.0 {
+        return Err(StatsError::ZeroVolume);
+    }
+    Ok(total_volume)
+}
+
+/// Price of each quote for the VWAP Group family, checking that all columns have the same length.
+fn group_prices(bids: &[f64], asks: &[f64], price: GroupPrice) -> Result<Vec<f64>, StatsError> {
+    if bids.len() != asks.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    match price {
+        GroupPrice::Mid => Ok(bids.iter().zip(asks.iter()).map(|(b, a)| (b + a) / 2.0).collect()),
+        GroupPrice::Microprice { bid_sizes, ask_sizes } => {
+            if bid_sizes.len() != bids.len() || ask_sizes.len() != bids.len() {
+                return Err(StatsError::LengthMismatch);
+            }
+            Ok((0..bids.len())
+                .map(|i| book::microprice(bids[i], asks[i], bid_sizes[i], ask_sizes[i]))
+                .collect())
+        }
+    }
+}
+
+/// Volume-weighted variance around `center`, using `Σv` (population) or `Σv - 1` (sample).
+fn weighted_variance(prices: &[f64], volumes: &[f64], center: f64, total_volume: f64, is_population: bool) -> Result<f64, StatsError> {
+    let weighted_squared_diffs: f64 = prices.iter()
+        .zip(volumes.iter())
+        .map(|(p, v)| v * (p - center).powi(2))
+        .sum();
+
+    let denominator = if is_population { total_volume } else { total_volume - 1.0 };
+    if denominator <= 0.0 {
+        return Err(StatsError::InsufficientData);
+    }
+
+    Ok(weighted_squared_diffs / denominator)
+}
+//-------------------------------------------------------------------------------
+
+
 // MEAN ---------------------------------------------------------------------
 /// Calculates the arithmetic mean (average) of a given list of values.
 ///
@@ -12,23 +299,21 @@
 /// * `values` - A slice of `f64` values representing the dataset.
 ///
 /// # Returns
-/// * `f64` - The arithmetic mean.
+/// * `Result<f64, StatsError>` - The arithmetic mean, or `EmptyInput` / `NaN`.
 ///
 /// # Example
 /// ```
+/// use quant_marketstat_ws::stats;
+///
 /// let data = vec![10.0, 20.0, 30.0];
-/// let avg = stats::mean(&data);
+/// let avg = stats::mean(&data).unwrap();
 /// assert_eq!(avg, 20.0);
 /// ```
-pub fn mean(values: &[f64]) -> f64 {
-    let sum: f64 = values.iter().sum();
-    let count = values.len();
-
-    if count == 0 {
-        return 0.0;
-    }
+pub fn mean(values: &[f64]) -> Result<f64, StatsError> {
+    check_series(values)?;
 
-    sum / count as f64
+    let sum: f64 = values.iter().sum();
+    Ok(sum / values.len() as f64)
 }
 //-------------------------------------------------------------------------------
 
@@ -51,42 +336,33 @@ pub fn mean(values: &[f64]) -> f64 {
 /// * `volumes` - A slice of f64 representing the volume at each price.
 ///
 /// # Returns
-/// * `(f64, &'static str)` - Tuple containing VWAP and signal string.
+/// * `Result<VwapResult, StatsError>` - VWAP value, last price and signal.
 ///
 /// # Example
 /// ```
+/// use quant_marketstat_ws::stats::{self, MarketSignal};
+///
 /// let prices = vec![10.0, 11.0, 12.0];
 /// let volumes = vec![100.0, 200.0, 150.0];
-/// let (vwap, signal) = stats::vwap(&prices, &volumes);
+/// let result = stats::vwap(&prices, &volumes).unwrap();
+/// assert_eq!(result.signal, MarketSignal::Strong);
 /// ```
-pub fn vwap(prices: &[f64], volumes: &[f64]) -> (f64, &'static str) {
-    if prices.len() != volumes.len() || prices.is_empty() {
-        return (0.0, "Invalid input: prices and volumes must be the same length and not empty.");
-    }
+pub fn vwap(prices: &[f64], volumes: &[f64]) -> Result<VwapResult, StatsError> {
+    let total_volume = check_weighted(prices, volumes)?;
 
     let total_value: f64 = prices.iter()
         .zip(volumes.iter())
         .map(|(p, v)| p * v)
         .sum();
 
-    let total_volume: f64 = volumes.iter().sum();
-
-    if total_volume == 0.0 {
-        return (0.0, "Total volume is zero. Cannot calculate VWAP.");
-    }
-
     let vwap = total_value / total_volume;
     let last_price = *prices.last().unwrap();
 
-    let signal = if vwap > last_price {
-        "VWAP is above the current price → weak market (trading below the day's average value)"
-    } else if vwap < last_price {
-        "VWAP is below the current price → strong market (trading above the day's average value)"
-    } else {
-        "VWAP is equal to the current price → neutral market"
-    };
-
-    (vwap, signal)
+    Ok(VwapResult {
+        value: vwap,
+        last_price,
+        signal: MarketSignal::from_prices(vwap, last_price),
+    })
 }
 //-------------------------------------------------------------------------------------------
 
@@ -95,41 +371,54 @@ pub fn vwap(prices: &[f64], volumes: &[f64]) -> (f64, &'static str) {
 /// Calculates VWAP based on the average between bid and ask prices and returns a market signal.
 ///
 /// # Formula
-/// For each price point:  
+/// For each price point:
 /// $$
 /// Preço\_Médio_i = \frac{Compra_i + Venda_i}{2}
-/// $$  
+/// $$
 /// Then apply:
 /// $$
 /// VWAP = \frac{\sum (Preço\_Médio_i \times Volume_i)}{\sum Volume_i}
 /// $$
 ///
 /// # Signal
-/// - VWAP > Último Preço de Venda → Mercado Fraco  
+/// - VWAP > Último Preço de Venda → Mercado Fraco
 /// - VWAP < Último Preço de Venda → Mercado Forte
 ///
 /// # Returns
-/// * `(f64, &'static str)` - VWAP calculado e sinal textual.
+/// * `Result<VwapResult, StatsError>` - VWAP calculado, último preço de venda e sinal.
 ///
-/// # Panics
-/// Se os slices tiverem tamanhos diferentes.
+/// # Errors
+/// `LengthMismatch` se os slices tiverem tamanhos diferentes.
 pub fn vwap_group(
     precos_compra: &[f64],
     precos_venda: &[f64],
     volumes: &[f64],
-) -> (f64, &'static str) {
-    if precos_compra.len() != precos_venda.len() || precos_compra.len() != volumes.len() {
-        return (
-            0.0,
-            "Invalid input: all input slices must have the same length and not be empty.",
-        );
-    }
+) -> Result<VwapResult, StatsError> {
+    vwap_group_with(precos_compra, precos_venda, volumes, GroupPrice::Mid)
+}
 
-    let precos_medios: Vec<f64> = precos_compra
-        .iter()
-        .zip(precos_venda.iter())
-        .map(|(b, a)| (b + a) / 2.0)
-        .collect();
+/// VWAP Group with each quote priced by `price`: the mid, as in [`vwap_group`], or the
+/// size-weighted microprice.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::stats::{vwap_group_with, GroupPrice};
+///
+/// let (bids, asks, volumes) = ([10.0, 10.0], [10.04, 10.04], [100.0, 100.0]);
+/// // Bid side three times heavier on both quotes
+/// let sizes = GroupPrice::Microprice { bid_sizes: &[300.0, 300.0], ask_sizes: &[100.0, 100.0] };
+///
+/// let result = vwap_group_with(&bids, &asks, &volumes, sizes).unwrap();
+/// assert!((result.value - 10.03).abs() < 1e-9);
+/// ```
+pub fn vwap_group_with(
+    precos_compra: &[f64],
+    precos_venda: &[f64],
+    volumes: &[f64],
+    price: GroupPrice,
+) -> Result<VwapResult, StatsError> {
+    let precos_medios = group_prices(precos_compra, precos_venda, price)?;
+    let total_volume = check_weighted(&precos_medios, volumes)?;
 
     let total_valor: f64 = precos_medios
         .iter()
@@ -137,44 +426,29 @@ pub fn vwap_group(
         .map(|(pm, v)| pm * v)
         .sum();
 
-    let total_volume: f64 = volumes.iter().sum();
-
-    if total_volume == 0.0 {
-        return (0.0, "Total volume is zero. Cannot calculate VWAP Group.");
-    }
-
     let vwap = total_valor / total_volume;
     let ultimo_preco_venda = *precos_venda.last().unwrap();
 
-    let sinal = if vwap > ultimo_preco_venda {
-        "VWAP is above the current price → weak market (trading below the day's average value)"
-    } else if vwap < ultimo_preco_venda {
-        "VWAP is below the current price → strong market (trading above the day's average value)"
-    } else {
-        "VWAP is equal to the current price → neutral market"
-    };
-
-    (vwap, sinal)
+    Ok(VwapResult {
+        value: vwap,
+        last_price: ultimo_preco_venda,
+        signal: MarketSignal::from_prices(vwap, ultimo_preco_venda),
+    })
 }
 //---------------------------------------------------------------------------------------------------------
 
 
-/// Calculates the variance of a dataset and returns an interpretation string.
-/// 
+/// Calculates the variance of a dataset with its market interpretation.
+///
 /// # Arguments
 /// * `data` - A slice of f64 values representing the dataset.
 /// * `is_population` - A boolean indicating whether to use population variance or sample variance.
 ///
 /// # Returns
-/// A tuple `(variance_value, explanation)` where:
-/// - `variance_value`: f64, the calculated variance.
-/// - `explanation`: &'static str, interpretation based on the variance level.
-pub fn variance(data: &[f64], is_population: bool) -> (f64, &'static str) {
-    if data.is_empty() {
-        return (0.0, "No data provided.");
-    }
-
-    let mean = mean(data);
+/// A `DispersionResult` with the variance and its volatility level
+/// (`< 0.5` low, `< 2.0` moderate, otherwise high).
+pub fn variance(data: &[f64], is_population: bool) -> Result<DispersionResult, StatsError> {
+    let mean = mean(data)?;
     let squared_diffs: f64 = data
         .iter()
         .map(|value| (value - mean).powi(2))
@@ -186,18 +460,17 @@ pub fn variance(data: &[f64], is_population: bool) -> (f64, &'static str) {
         (data.len().saturating_sub(1)) as f64
     };
 
-    let variance = squared_diffs / denominator;
+    if denominator == 0.0 {
+        return Err(StatsError::InsufficientData);
+    }
 
-    // Interpretation of the variance value
-    let explanation = if variance < 0.5 {
-        "Low variance → stable market with low volatility"
-    } else if variance < 2.0 {
-        "Moderate variance → normal market fluctuations"
-    } else {
-        "High variance → volatile market with larger price swings"
-    };
+    let variance = squared_diffs / denominator;
 
-    (variance, explanation)
+    Ok(DispersionResult {
+        value: variance,
+        level: VolatilityLevel::from_variance(variance),
+        measure: Measure::Variance,
+    })
 }
 //-------------------------------------------------------------------------------------------------------
 
@@ -209,88 +482,64 @@ pub fn variance(data: &[f64], is_population: bool) -> (f64, &'static str) {
 /// - `is_population`: true for population standard deviation, false for sample
 ///
 /// # Returns
-/// - Standard deviation as `f64`
+/// - `DispersionResult` with the standard deviation; the level is the one of the underlying variance
 ///
 /// $$ \text{STD} = \sqrt{\text{Variance}} $$
-pub fn std(data: &[f64], is_population: bool) -> (f64, &'static str) {
-    let (variance_value, explanation) = variance(data, is_population);
-    let std_dev = variance_value.sqrt();
-    (std_dev, explanation)
+pub fn std(data: &[f64], is_population: bool) -> Result<DispersionResult, StatsError> {
+    let variance_result = variance(data, is_population)?;
+
+    Ok(DispersionResult {
+        value: variance_result.value.sqrt(),
+        level: variance_result.level,
+        measure: Measure::Std,
+    })
 }
 //--------------------------------------------------------------------------------------------------------
 
 //Variance VWAP-------------------------------------------------------------------------------------------
-/// Calculates the variance of prices weighted by volumes around the VWAP.
-/// 
+/// Calculates the variance of prices weighted by volumes around the VWAP, with interpretation.
+///
 /// # Arguments
 /// - `prices`: slice of prices (f64)
 /// - `volumes`: slice of volumes (f64)
 /// - `is_population`: true if population variance, false for sample
-/// 
+///
 /// # Returns
-/// - Weighted variance around the VWAP
-/// 
+/// - `DispersionResult` with the weighted variance around the VWAP
+///
 /// $$ \text{Variance} = \frac{\sum v_i(p_i - \text{VWAP})^2}{\sum v_i \text{ or } \sum v_i - 1} $$
-/// Calculates the variance of prices weighted by volumes around the VWAP, with explanation.
-/// 
-/// # Returns
-/// - (variance_value, explanation)
-pub fn variance_vwap(prices: &[f64], volumes: &[f64], is_population: bool) -> (f64, &'static str) {
-    let (vwap_value, _) = vwap(prices, volumes);
-    let weighted_squared_diffs: f64 = prices.iter()
-        .zip(volumes.iter())
-        .map(|(p, v)| v * (p - vwap_value).powi(2))
-        .sum();
-
+pub fn variance_vwap(prices: &[f64], volumes: &[f64], is_population: bool) -> Result<DispersionResult, StatsError> {
+    let vwap_value = vwap(prices, volumes)?.value;
     let total_volume: f64 = volumes.iter().sum();
-    let variance = if is_population {
-        weighted_squared_diffs / total_volume
-    } else {
-        let adjusted_total = total_volume - 1.0;
-        if adjusted_total <= 0.0 {
-            0.0
-        } else {
-            weighted_squared_diffs / adjusted_total
-        }
-    };
+    let variance = weighted_variance(prices, volumes, vwap_value, total_volume, is_population)?;
 
-    // Explanation
-    let explanation = if variance < 0.5 {
-        "Low VWAP variance → stable volume-weighted market with small deviations from VWAP"
-    } else if variance < 2.0 {
-        "Moderate VWAP variance → typical market volume-weighted fluctuation"
-    } else {
-        "High VWAP variance → volatile market with large volume-weighted deviations from VWAP"
-    };
-
-    (variance, explanation)
+    Ok(DispersionResult {
+        value: variance,
+        level: VolatilityLevel::from_variance(variance),
+        measure: Measure::VwapVariance,
+    })
 }
 //---------------------------------------------------------------------------------------------------------
 
 //STD for the Variance VWAP--------------------------------------------------------------------------------
-/// Calculates the standard deviation around the VWAP (weighted by volumes), with explanation.
-/// 
+/// Calculates the standard deviation around the VWAP (weighted by volumes), with interpretation.
+///
 /// # Arguments
 /// - `prices`: slice of prices
 /// - `volumes`: slice of volumes
 /// - `is_population`: true for population STD, false for sample
-/// 
+///
 /// # Returns
-/// - Weighted standard deviation around VWAP
-pub fn std_vwap(prices: &[f64], volumes: &[f64], is_population: bool) -> (f64, &'static str) {
-    let (var, _) = variance_vwap(prices, volumes, is_population);
-    let std = var.sqrt();
-
-    // Explanation
-    let explanation = if std < 0.7 {
-        "Low VWAP STD → low dispersion of prices around VWAP"
-    } else if std < 1.5 {
-        "Moderate VWAP STD → normal dispersion of prices around VWAP"
-    } else {
-        "High VWAP STD → significant dispersion from VWAP, indicating higher risk"
-    };
-
-    (std, explanation)
+/// - `DispersionResult` with the weighted standard deviation around VWAP
+///   (`< 0.7` low, `< 1.5` moderate, otherwise high)
+pub fn std_vwap(prices: &[f64], volumes: &[f64], is_population: bool) -> Result<DispersionResult, StatsError> {
+    let std = variance_vwap(prices, volumes, is_population)?.value.sqrt();
+
+    Ok(DispersionResult {
+        value: std,
+        level: VolatilityLevel::from_std(std),
+        measure: Measure::VwapStd,
+    })
 }
 //-----------------------------------------------------------------------------------------------------------
 
@@ -304,42 +553,35 @@ pub fn std_vwap(prices: &[f64], volumes: &[f64], is_population: bool) -> (f64, &
 /// - `is_population`: true for population variance, false for sample
 ///
 /// # Returns
-/// - (variance_value, interpretation)
+/// - `DispersionResult` with the variance of mid prices around the VWAP Group
 pub fn variance_vwap_group(
     bids: &[f64],
     asks: &[f64],
     volumes: &[f64],
     is_population: bool,
-) -> (f64, &'static str) {
-    let mid_prices: Vec<f64> = bids.iter()
-        .zip(asks.iter())
-        .map(|(b, a)| (b + a) / 2.0)
-        .collect();
+) -> Result<DispersionResult, StatsError> {
+    variance_vwap_group_with(bids, asks, volumes, is_population, GroupPrice::Mid)
+}
 
-    let (vwap_group, _) = vwap_group(bids, asks, volumes);
+/// VWAP Group variance with each quote priced by `price` (see [`vwap_group_with`]).
+pub fn variance_vwap_group_with(
+    bids: &[f64],
+    asks: &[f64],
+    volumes: &[f64],
+    is_population: bool,
+    price: GroupPrice,
+) -> Result<DispersionResult, StatsError> {
+    let group_prices = group_prices(bids, asks, price)?;
+    let vwap_group = vwap_group_with(bids, asks, volumes, price)?.value;
 
     let total_volume: f64 = volumes.iter().sum();
-    let weighted_diffs: f64 = mid_prices.iter()
-        .zip(volumes.iter())
-        .map(|(p, v)| v * (p - vwap_group).powi(2))
-        .sum();
-
-    let variance = if is_population {
-        weighted_diffs / total_volume
-    } else {
-        let adjusted = total_volume - 1.0;
-        if adjusted <= 0.0 { 0.0 } else { weighted_diffs / adjusted }
-    };
+    let variance = weighted_variance(&group_prices, volumes, vwap_group, total_volume, is_population)?;
 
-    let interpretation = if variance < 0.5 {
-        "Low variance: stable market"
-    } else if variance < 2.0 {
-        "Medium variance: moderate activity"
-    } else {
-        "High variance: volatile market"
-    };
-
-    (variance, interpretation)
+    Ok(DispersionResult {
+        value: variance,
+        level: VolatilityLevel::from_variance(variance),
+        measure: Measure::GroupVariance,
+    })
 }
 //-------------------------------------------------------------------------------------------------
 
@@ -350,79 +592,123 @@ pub fn variance_vwap_group(
 /// - same as variance_vwap_group
 ///
 /// # Returns
-/// - (std_value, interpretation)
+/// - `DispersionResult` with the standard deviation of mid prices around the VWAP Group
 pub fn std_vwap_group(
     bids: &[f64],
     asks: &[f64],
     volumes: &[f64],
     is_population: bool,
-) -> (f64, &'static str) {
-    let (variance, _) = variance_vwap_group(bids, asks, volumes, is_population);
-    let std = variance.sqrt();
-
-    let interpretation = if std < 0.7 {
-        "Low STD: low volatility"
-    } else if std < 1.5 {
-        "Moderate STD: watch the market"
-    } else {
-        "High STD: market unstable"
-    };
+) -> Result<DispersionResult, StatsError> {
+    std_vwap_group_with(bids, asks, volumes, is_population, GroupPrice::Mid)
+}
 
-    (std, interpretation)
+/// VWAP Group standard deviation with each quote priced by `price` (see [`vwap_group_with`]).
+pub fn std_vwap_group_with(
+    bids: &[f64],
+    asks: &[f64],
+    volumes: &[f64],
+    is_population: bool,
+    price: GroupPrice,
+) -> Result<DispersionResult, StatsError> {
+    let std = variance_vwap_group_with(bids, asks, volumes, is_population, price)?.value.sqrt();
+
+    Ok(DispersionResult {
+        value: std,
+        level: VolatilityLevel::from_std(std),
+        measure: Measure::GroupStd,
+    })
 }
 //----------------------------------------------------------------------------------------------------
 
 
 //Funcao Global with all fn---------------------------------------------------------------------------
-/// Global market statistics summary based on bids, asks, and volumes.
-/// Computes and interprets all relevant stats in one call.
-pub fn global_stats_summary(bids: &[f64], asks: &[f64], volumes: &[f64]) {
-    println!("\n📊 === Global Market Stats Summary ===\n");
-
-    // Mean
-    let mean_bid = mean(bids);
-    let mean_ask = mean(asks);
-    println!("Mean Bid: {:.4}", mean_bid);
-    println!("Mean Ask: {:.4}", mean_ask);
-
-    // VWAP
-    let (vwap_bid, sig_bid) = vwap(bids, volumes);
-    let (vwap_ask, sig_ask) = vwap(asks, volumes);
-    println!("\nVWAP Bid: {:.4} => {}", vwap_bid, sig_bid);
-    println!("VWAP Ask: {:.4} => {}", vwap_ask, sig_ask);
-
-    // VWAP Group
-    let (vwap_group, sig_group) = vwap_group(bids, asks, volumes);
-    println!("VWAP Group: {:.4} => {}", vwap_group, sig_group);
-
-    // Variance & STD - raw
-    let (var_bid, note_var_bid) = variance(bids, true);
-    let (var_ask, note_var_ask) = variance(asks, true);
-    println!("\nVariance Bid: {:.4} => {}", var_bid, note_var_bid);
-    println!("Variance Ask: {:.4} => {}", var_ask, note_var_ask);
-
-    let (std_bid, note_std_bid) = std(bids, true);
-    let (std_ask, note_std_ask) = std(asks, true);
-    println!("STD Bid: {:.4} => {}", std_bid, note_std_bid);
-    println!("STD Ask: {:.4} => {}", std_ask, note_std_ask);
-
-    // VWAP Variance & STD
-    let (var_vwap_bid, note_var_vwap_bid) = variance_vwap(bids, volumes, true);
-    let (var_vwap_ask, note_var_vwap_ask) = variance_vwap(asks, volumes, true);
-    println!("\nVWAP Variance Bid: {:.4} => {}", var_vwap_bid, note_var_vwap_bid);
-    println!("VWAP Variance Ask: {:.4} => {}", var_vwap_ask, note_var_vwap_ask);
+/// All statistics of one price side (bid or ask), weighted by the shared volumes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SideSummary {
+    pub mean: f64,
+    pub vwap: VwapResult,
+    pub variance: DispersionResult,
+    pub std: DispersionResult,
+    pub vwap_variance: DispersionResult,
+    pub vwap_std: DispersionResult,
+    /// Shape of the prices, unweighted
+    pub distribution: Distribution,
+    /// Shape of the prices, weighted by the volumes
+    pub vwap_distribution: Distribution,
+}
 
-    let (std_vwap_bid, note_std_vwap_bid) = std_vwap(bids, volumes, true);
-    let (std_vwap_ask, note_std_vwap_ask) = std_vwap(asks, volumes, true);
-    println!("VWAP STD Bid: {:.4} => {}", std_vwap_bid, note_std_vwap_bid);
-    println!("VWAP STD Ask: {:.4} => {}", std_vwap_ask, note_std_vwap_ask);
+/// VWAP Group statistics, computed on mid prices `(bid + ask) / 2`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupSummary {
+    pub vwap: VwapResult,
+    pub variance: DispersionResult,
+    pub std: DispersionResult,
+    /// Shape of the mid prices, weighted by the volumes
+    pub distribution: Distribution,
+}
 
-    // Variance & STD - VWAP Group
-    let (var_vwap_group, note_var_group) = variance_vwap_group(bids, asks, volumes, true);
-    let (std_vwap_group, note_std_group) = std_vwap_group(bids, asks, volumes, true);
-    println!("\nVWAP Group Variance: {:.4} => {}", var_vwap_group, note_var_group);
-    println!("VWAP Group STD: {:.4} => {}", std_vwap_group, note_std_group);
+/// Global market statistics for a bid/ask/volume dataset (population estimates).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketSummary {
+    /// Number of rows (bid, ask, volume) in the dataset
+    pub rows: usize,
+    pub bid: SideSummary,
+    pub ask: SideSummary,
+    pub group: GroupSummary,
+}
 
+fn side_summary(prices: &[f64], volumes: &[f64]) -> Result<SideSummary, StatsError> {
+    Ok(SideSummary {
+        mean: mean(prices)?,
+        vwap: vwap(prices, volumes)?,
+        variance: variance(prices, true)?,
+        std: std(prices, true)?,
+        vwap_variance: variance_vwap(prices, volumes, true)?,
+        vwap_std: std_vwap(prices, volumes, true)?,
+        distribution: distribution(prices)?,
+        vwap_distribution: weighted_distribution(prices, volumes)?,
+    })
 }
 
+/// Computes every statistic of the global summary in one call.
+///
+/// # Returns
+/// * `Result<MarketSummary, StatsError>` - means, VWAPs, variances, STDs, signals and
+///   distributions (quartiles, skewness, kurtosis, Jarque–Bera) for bid, ask and VWAP Group,
+///   using population estimates.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::stats;
+///
+/// let summary = stats::market_summary(&[10.5, 10.6], &[11.0, 11.1], &[100.0, 150.0]).unwrap();
+/// assert_eq!(summary.rows, 2);
+/// assert!(summary.group.vwap.value > summary.bid.vwap.value);
+/// ```
+pub fn market_summary(bids: &[f64], asks: &[f64], volumes: &[f64]) -> Result<MarketSummary, StatsError> {
+    if bids.len() != asks.len() || asks.len() != volumes.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    let mids: Vec<f64> = bids.iter().zip(asks.iter()).map(|(b, a)| (b + a) / 2.0).collect();
+
+    Ok(MarketSummary {
+        rows: bids.len(),
+        bid: side_summary(bids, volumes)?,
+        ask: side_summary(asks, volumes)?,
+        group: GroupSummary {
+            vwap: vwap_group(bids, asks, volumes)?,
+            variance: variance_vwap_group(bids, asks, volumes, true)?,
+            std: std_vwap_group(bids, asks, volumes, true)?,
+            distribution: weighted_distribution(&mids, volumes)?,
+        },
+    })
+}
 
+/// Global market statistics summary based on bids, asks, and volumes.
+/// Computes and prints all relevant stats with their interpretations.
+pub fn global_stats_summary(bids: &[f64], asks: &[f64], volumes: &[f64]) {
+    match market_summary(bids, asks, volumes) {
+        Ok(summary) => print!("{}", crate::report::render_text(&summary)),
+        Err(err) => println!("\nCannot compute the global summary: {}", err),
+    }
+}
@@ -0,0 +1,270 @@
+//! Real-time market data from a WebSocket feed.
+//!
+//! Each text message is parsed as JSON and turned into a [`Tick`] through a
+//! [`MessageSchema`] (where bid, ask, volume, ... live in the message). Ticks are
+//! fed into [`OnlineMarketStats`] and the updated global summary is handed to a
+//! callback every `report_every` ticks or every `report_every` of wall time.
+//!
+//! Only plain `ws://` endpoints are supported (no TLS). `examples/mock_ws_server.rs`
+//! serves a CSV file as a local feed for trying the stream mode; `tests/stream.rs`
+//! runs sessions against an in-process server.
+
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::online::OnlineMarketStats;
+use crate::rolling::Window;
+use crate::stats::MarketSummary;
+use crate::tick::{parse_timestamp, Tick};
+
+/// Where the tick fields are found in a JSON message.
+///
+/// A path is either a JSON pointer (`/data/b`) or a dotted path (`data.b`, `levels.0.price`).
+/// Numbers may be JSON numbers or numeric strings, as many exchanges send them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSchema {
+    pub bid: String,
+    pub ask: String,
+    pub volume: String,
+    pub timestamp: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl Default for MessageSchema {
+    /// Flat messages: `{"timestamp": .., "symbol": "PETR4", "bid": 37.1, "ask": 37.12, "volume": 1200}`.
+    fn default() -> MessageSchema {
+        MessageSchema {
+            bid: "bid".to_string(),
+            ask: "ask".to_string(),
+            volume: "volume".to_string(),
+            timestamp: Some("timestamp".to_string()),
+            symbol: Some("symbol".to_string()),
+        }
+    }
+}
+
+impl MessageSchema {
+    /// Parses a schema spec such as `bid=data.b,ask=data.a,volume=data.q,timestamp=data.T`.
+    /// Fields not listed keep their default path.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::stream::{parse_message, MessageSchema};
+    ///
+    /// let schema = MessageSchema::parse("bid=data.b,ask=data.a,volume=data.q,symbol=data.s").unwrap();
+    /// let tick = parse_message(r#"{"data": {"s": "BTCUSDT", "b": "100.5", "a": "100.7", "q": 3}}"#, &schema).unwrap();
+    /// assert_eq!((tick.bid, tick.ask, tick.volume), (100.5, 100.7, 3.0));
+    /// assert_eq!(tick.symbol.as_deref(), Some("BTCUSDT"));
+    ///
+    /// // "inf" and "NaN" strings are not prices
+    /// assert!(parse_message(r#"{"data": {"b": "inf", "a": "100.7", "q": 3}}"#, &schema).is_err());
+    /// ```
+    pub fn parse(spec: &str) -> Result<MessageSchema, String> {
+        let mut schema = MessageSchema::default();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (field, path) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid schema entry '{}' (expected field=path)", entry))?;
+            let path = path.trim().to_string();
+
+            match field.trim().to_ascii_lowercase().as_str() {
+                "bid" => schema.bid = path,
+                "ask" => schema.ask = path,
+                "volume" => schema.volume = path,
+                "timestamp" => schema.timestamp = Some(path),
+                "symbol" => schema.symbol = Some(path),
+                other => return Err(format!("Unknown schema field '{}' (expected bid, ask, volume, timestamp or symbol)", other)),
+            }
+        }
+
+        Ok(schema)
+    }
+}
+
+/// Value at `path` (JSON pointer or dotted path) in `doc`.
+fn lookup<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') {
+        doc.pointer(path)
+    } else {
+        doc.pointer(&format!("/{}", path.replace('.', "/")))
+    }
+}
+
+/// Finite number at `path`, from a JSON number or a numeric string.
+fn number(doc: &Value, path: &str) -> Result<f64, String> {
+    let value = lookup(doc, path).ok_or_else(|| format!("missing field '{}'", path))?;
+    let parsed = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    parsed
+        .filter(|n: &f64| n.is_finite())
+        .ok_or_else(|| format!("field '{}' is not a number ({})", path, value))
+}
+
+/// Parses one JSON message into a tick.
+///
+/// Messages without the bid, ask or volume fields (subscription acks, heartbeats, ...)
+/// are errors, so the caller can skip them. Timestamp and symbol are optional.
+pub fn parse_message(text: &str, schema: &MessageSchema) -> Result<Tick, String> {
+    let doc: Value = serde_json::from_str(text).map_err(|err| format!("invalid JSON: {}", err))?;
+
+    let timestamp = schema.timestamp.as_deref().and_then(|path| match lookup(&doc, path)? {
+        Value::Number(n) => n.as_f64().and_then(|n| parse_timestamp(&n.to_string())),
+        Value::String(s) => parse_timestamp(s),
+        _ => None,
+    });
+    let symbol = schema.symbol.as_deref().and_then(|path| match lookup(&doc, path)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    });
+
+    Ok(Tick {
+        timestamp,
+        symbol,
+        bid: number(&doc, &schema.bid)?,
+        ask: number(&doc, &schema.ask)?,
+        volume: number(&doc, &schema.volume)?,
+        ..Tick::default()
+    })
+}
+
+/// Settings of a stream session.
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Endpoint, e.g. `ws://localhost:9001`
+    pub url: String,
+    pub schema: MessageSchema,
+    /// Message sent right after connecting (e.g. a subscription request)
+    pub subscribe: Option<String>,
+    /// Report every `n` ticks, or every duration of wall time (checked as messages arrive)
+    pub report_every: Window,
+    /// Keep only ticks of this symbol
+    pub symbol: Option<String>,
+    /// Stop after this many ticks
+    pub max_ticks: Option<usize>,
+}
+
+impl StreamConfig {
+    /// Config with the default schema, reporting every 100 ticks.
+    pub fn new(url: &str) -> StreamConfig {
+        StreamConfig {
+            url: url.to_string(),
+            schema: MessageSchema::default(),
+            subscribe: None,
+            report_every: Window::Count(100),
+            symbol: None,
+            max_ticks: None,
+        }
+    }
+}
+
+/// Counters of a stream session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Messages received (text or binary)
+    pub messages: usize,
+    /// Ticks fed into the statistics
+    pub ticks: usize,
+    /// Messages that were not ticks (wrong format, other symbol, ...)
+    pub skipped: usize,
+}
+
+/// Connects to `config.url` and streams ticks into the statistics until the server
+/// closes the connection or `max_ticks` is reached.
+///
+/// `on_summary` gets the updated summary at each report and once more at the end
+/// (when at least one new tick arrived since the last report). Returns the session
+/// counters; connection and protocol errors are returned as `Err`.
+pub fn run<F>(config: &StreamConfig, mut on_summary: F) -> Result<StreamStats, String>
+where
+    F: FnMut(&MarketSummary, &StreamStats),
+{
+    if let Window::Count(0) | Window::Time(..=0) = config.report_every {
+        return Err("The report interval must be positive.".to_string());
+    }
+
+    let (mut socket, _) =
+        tungstenite::connect(config.url.as_str()).map_err(|err| format!("Failed to connect to {}: {}", config.url, err))?;
+    if let Some(subscribe) = &config.subscribe {
+        socket
+            .send(Message::Text(subscribe.clone()))
+            .map_err(|err| format!("Failed to send subscription: {}", err))?;
+    }
+
+    let result = consume(&mut socket, config, &mut on_summary);
+    let _ = socket.close(None);
+    result
+}
+
+fn consume<F>(
+    socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+    config: &StreamConfig,
+    on_summary: &mut F,
+) -> Result<StreamStats, String>
+where
+    F: FnMut(&MarketSummary, &StreamStats),
+{
+    let mut online = OnlineMarketStats::new();
+    let mut counters = StreamStats::default();
+    let mut pending = 0;
+    let mut last_report = Instant::now();
+
+    let mut report = |online: &OnlineMarketStats, counters: &StreamStats| {
+        if let Ok(summary) = online.summary() {
+            on_summary(&summary, counters);
+        }
+    };
+
+    loop {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Binary(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+            // Pings are answered by tungstenite; after a Close the next read ends the loop
+            Ok(_) => continue,
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => break,
+            Err(err) => return Err(format!("WebSocket error: {}", err)),
+        };
+        counters.messages += 1;
+
+        let tick = match parse_message(&text, &config.schema) {
+            Ok(tick) if config.symbol.is_none() || tick.symbol == config.symbol => tick,
+            _ => {
+                counters.skipped += 1;
+                continue;
+            }
+        };
+        if online.update(&tick).is_err() {
+            counters.skipped += 1;
+            continue;
+        }
+        counters.ticks += 1;
+        pending += 1;
+
+        let due = match config.report_every {
+            Window::Count(n) => pending >= n,
+            Window::Time(ms) => last_report.elapsed() >= Duration::from_millis(ms as u64),
+        };
+        if due {
+            report(&online, &counters);
+            pending = 0;
+            last_report = Instant::now();
+        }
+
+        if config.max_ticks.is_some_and(|max| counters.ticks >= max) {
+            break;
+        }
+    }
+
+    if pending > 0 {
+        report(&online, &counters);
+    }
+    Ok(counters)
+}
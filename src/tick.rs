@@ -0,0 +1,357 @@
+//! Timestamped, per-instrument market data records.
+//!
+//! A [`Tick`] is one row of a quote/trade file: timestamp, symbol, top of book
+//! (bid/ask and their sizes), last traded price and traded volume. Helpers in
+//! this module turn a slice of ticks back into the plain `&[f64]` columns the
+//! `stats` functions work on, and split them per instrument.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::book;
+use crate::csv_loader::MarketColumns;
+
+/// Milliseconds since the Unix epoch (UTC).
+pub type Timestamp = i64;
+
+/// One market data record (quote and/or trade).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tick {
+    /// Event time, when the source has one
+    pub timestamp: Option<Timestamp>,
+
+    /// Instrument identifier (e.g. "PETR4", "WDOFUT", "EUR/USD")
+    pub symbol: Option<String>,
+
+    /// Best bid price (compra)
+    pub bid: f64,
+
+    /// Best ask price (venda)
+    pub ask: f64,
+
+    /// Quantity available at the best bid. Optional.
+    pub bid_size: Option<f64>,
+
+    /// Quantity available at the best ask. Optional.
+    pub ask_size: Option<f64>,
+
+    /// Last traded price. Optional.
+    pub last_price: Option<f64>,
+
+    /// Traded volume
+    pub volume: f64,
+}
+
+impl Tick {
+    /// Tick with only bid, ask and volume, as in the original three-column files.
+    pub fn from_quote(bid: f64, ask: f64, volume: f64) -> Tick {
+        Tick { bid, ask, volume, ..Tick::default() }
+    }
+
+    /// Mid price `(bid + ask) / 2`.
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+
+    /// Size-weighted microprice (see `book::microprice`), or `None` without both sizes.
+    pub fn microprice(&self) -> Option<f64> {
+        Some(book::microprice(self.bid, self.ask, self.bid_size?, self.ask_size?))
+    }
+}
+
+/// Which price of a tick a series is built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceField {
+    Bid,
+    Ask,
+    #[default]
+    Mid,
+    /// Last traded price
+    Last,
+}
+
+impl PriceField {
+    /// Price of `tick` for this field; `None` for `Last` when the tick has no trade price.
+    pub fn of(&self, tick: &Tick) -> Option<f64> {
+        match self {
+            PriceField::Bid => Some(tick.bid),
+            PriceField::Ask => Some(tick.ask),
+            PriceField::Mid => Some(tick.mid()),
+            PriceField::Last => tick.last_price,
+        }
+    }
+}
+
+impl FromStr for PriceField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PriceField, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "bid" => Ok(PriceField::Bid),
+            "ask" => Ok(PriceField::Ask),
+            "mid" => Ok(PriceField::Mid),
+            "last" | "trade" => Ok(PriceField::Last),
+            other => Err(format!("Unknown price field '{}' (expected bid, ask, mid or last)", other)),
+        }
+    }
+}
+
+impl fmt::Display for PriceField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PriceField::Bid => "bid",
+            PriceField::Ask => "ask",
+            PriceField::Mid => "mid",
+            PriceField::Last => "last",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How often a series is sampled (P&L curves, returns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Frequency {
+    /// One point per tick
+    #[default]
+    Tick,
+    /// One point per period of `ms` milliseconds (UTC-aligned), at the last tick of the period
+    Every(i64),
+}
+
+impl FromStr for Frequency {
+    type Err = String;
+
+    /// `"tick"`, `"daily"`, or a period like `"1h"`, `"1d"`.
+    fn from_str(s: &str) -> Result<Frequency, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "tick" | "ticks" => Ok(Frequency::Tick),
+            "daily" | "day" => Ok(Frequency::Every(86_400_000)),
+            other => parse_duration(other)
+                .map(Frequency::Every)
+                .ok_or_else(|| format!("Invalid frequency '{}' (expected tick, daily or a period like 1h)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frequency::Tick => f.write_str("tick"),
+            Frequency::Every(86_400_000) => f.write_str("daily"),
+            Frequency::Every(ms) => write!(f, "every {} ms", ms),
+        }
+    }
+}
+
+/// Price series of the ticks for `field`, or `None` if a tick lacks that price.
+pub fn prices(ticks: &[Tick], field: PriceField) -> Option<Vec<f64>> {
+    ticks.iter().map(|t| field.of(t)).collect()
+}
+
+/// Volumes of the ticks.
+pub fn volumes(ticks: &[Tick]) -> Vec<f64> {
+    ticks.iter().map(|t| t.volume).collect()
+}
+
+/// Splits ticks into (bids, asks, volumes) columns for the `stats` functions.
+pub fn columns(ticks: &[Tick]) -> MarketColumns {
+    (
+        ticks.iter().map(|t| t.bid).collect(),
+        ticks.iter().map(|t| t.ask).collect(),
+        ticks.iter().map(|t| t.volume).collect(),
+    )
+}
+
+/// Builds ticks (without time or symbol) from (bids, asks, volumes) columns.
+pub fn from_columns(columns: MarketColumns) -> Vec<Tick> {
+    let (bids, asks, volumes) = columns;
+    bids.into_iter()
+        .zip(asks)
+        .zip(volumes)
+        .map(|((bid, ask), volume)| Tick::from_quote(bid, ask, volume))
+        .collect()
+}
+
+/// Mid prices of the ticks.
+pub fn mids(ticks: &[Tick]) -> Vec<f64> {
+    ticks.iter().map(Tick::mid).collect()
+}
+
+/// Timestamps of the ticks, or `None` if any tick has no timestamp.
+pub fn timestamps(ticks: &[Tick]) -> Option<Vec<Timestamp>> {
+    ticks.iter().map(|t| t.timestamp).collect()
+}
+
+/// Groups ticks per symbol, keeping their original order. Ticks without a symbol go under "".
+pub fn by_symbol(ticks: &[Tick]) -> BTreeMap<String, Vec<Tick>> {
+    let mut groups: BTreeMap<String, Vec<Tick>> = BTreeMap::new();
+    for tick in ticks {
+        let key = tick.symbol.clone().unwrap_or_default();
+        groups.entry(key).or_default().push(tick.clone());
+    }
+    groups
+}
+
+/// Ticks of a single symbol.
+pub fn for_symbol(ticks: &[Tick], symbol: &str) -> Vec<Tick> {
+    ticks
+        .iter()
+        .filter(|t| t.symbol.as_deref() == Some(symbol))
+        .cloned()
+        .collect()
+}
+
+// TIMESTAMPS --------------------------------------------------------------------------
+/// Days since 1970-01-01 for a civil (proleptic Gregorian) date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Civil date (year, month, day) for a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parses a timestamp into milliseconds since the Unix epoch (UTC).
+///
+/// Accepted forms:
+/// - Unix epoch as a number, in seconds (`1700000000`, `1700000000.25`) or milliseconds (`1700000000000`)
+/// - ISO 8601 date and time: `2024-03-15T10:30:00`, `2024-03-15 10:30:00.125`,
+///   with an optional `Z` or `±HH:MM` offset (no offset means UTC)
+/// - Date only: `2024-03-15`
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::tick::parse_timestamp;
+///
+/// assert_eq!(parse_timestamp("1970-01-02"), Some(86_400_000));
+/// assert_eq!(parse_timestamp("1970-01-01T00:00:01.5Z"), Some(1_500));
+/// assert_eq!(parse_timestamp("86400"), Some(86_400_000));
+/// assert_eq!(parse_timestamp("nan"), None);
+/// assert_eq!(parse_timestamp("-inf"), None);
+/// ```
+pub fn parse_timestamp(raw: &str) -> Option<Timestamp> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    if let Ok(number) = raw.parse::<f64>() {
+        // "nan" and "inf" parse as numbers but are not times
+        if !number.is_finite() {
+            return None;
+        }
+        // Epoch numbers below 1e11 are seconds (1e11 s is the year 5138)
+        return Some(if number.abs() < 1e11 { (number * 1000.0).round() as i64 } else { number as i64 });
+    }
+
+    let (date, time) = match raw.find(['T', ' ']) {
+        Some(pos) => (&raw[..pos], raw[pos + 1..].trim()),
+        None => (raw, ""),
+    };
+
+    let mut date_parts = date.split(['-', '/']);
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut millis = days_from_civil(year, month, day) * 86_400_000;
+    if time.is_empty() {
+        return Some(millis);
+    }
+
+    // Split off the UTC offset: "Z", "+HH:MM" or "-HH:MM"
+    let (clock, offset_minutes) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(pos) = time.rfind(['+', '-']) {
+        let sign = if time[pos..].starts_with('-') { -1 } else { 1 };
+        let offset = time[pos + 1..].replace(':', "");
+        if offset.len() != 4 {
+            return None;
+        }
+        let hours: i64 = offset[..2].parse().ok()?;
+        let minutes: i64 = offset[2..].parse().ok()?;
+        (&time[..pos], sign * (hours * 60 + minutes))
+    } else {
+        (time, 0)
+    };
+
+    let mut clock_parts = clock.split(':');
+    let hours: i64 = clock_parts.next()?.parse().ok()?;
+    let minutes: i64 = clock_parts.next().unwrap_or("0").parse().ok()?;
+    let seconds: f64 = clock_parts.next().unwrap_or("0").parse().ok()?;
+    if clock_parts.next().is_some() || hours > 23 || minutes > 59 || !(0.0..61.0).contains(&seconds) {
+        return None;
+    }
+
+    millis += hours * 3_600_000 + minutes * 60_000 + (seconds * 1000.0).round() as i64;
+    millis -= offset_minutes * 60_000;
+    Some(millis)
+}
+
+/// Parses a duration such as `500ms`, `30s`, `5m`, `1h` or `1d` into milliseconds.
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::tick::parse_duration;
+///
+/// assert_eq!(parse_duration("5m"), Some(300_000));
+/// assert_eq!(parse_duration("1d"), Some(86_400_000));
+/// assert_eq!(parse_duration("5"), None);
+/// ```
+pub fn parse_duration(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = raw.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let unit_ms = match unit.to_ascii_lowercase().as_str() {
+        "ms" => 1.0,
+        "s" | "sec" => 1_000.0,
+        "m" | "min" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        _ => return None,
+    };
+
+    let millis = (number * unit_ms).round() as i64;
+    (millis > 0).then_some(millis)
+}
+
+/// Formats a timestamp as ISO 8601 in UTC: `2024-03-15T10:30:00.125Z`.
+pub fn format_timestamp(ts: Timestamp) -> String {
+    let days = ts.div_euclid(86_400_000);
+    let ms_of_day = ts.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+//-------------------------------------------------------------------------------------
@@ -0,0 +1,412 @@
+//! Realized volatility estimators.
+//!
+//! `stats::std` and `stats::std_vwap` measure the dispersion of price levels.
+//! The estimators here measure volatility of returns on intraday data:
+//! range-based estimators on OHLC bars (close-to-close, Parkinson, Garman–Klass,
+//! Rogers–Satchell, Yang–Zhang) and realized variance and bipower variation on
+//! high-frequency log returns. Every estimate is a variance per bar (or per
+//! return), annualized with a [`TradingCalendar`].
+
+use std::f64::consts::{LN_2, PI};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::stats::StatsError;
+use crate::tick::{PriceField, Tick, Timestamp};
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// Open, high, low and close of one interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    /// Interval start
+    pub timestamp: Timestamp,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// OHLC bars of the `field` prices of `ticks`, one per `period_ms` interval (UTC-aligned)
+/// that has ticks. Ticks without the `field` price are skipped.
+///
+/// # Errors
+/// * `InvalidWindow` - a period of zero
+/// * `MissingTimestamps`, `UnsortedTimestamps` - ticks without times or out of order
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::tick::{PriceField, Tick};
+/// use quant_marketstat_ws::volatility::bars;
+///
+/// let tick = |ts: i64, price: f64| Tick { timestamp: Some(ts), ..Tick::from_quote(price, price, 1.0) };
+/// let ticks = [tick(0, 10.0), tick(20_000, 10.6), tick(40_000, 9.8), tick(59_000, 10.2), tick(60_000, 10.3)];
+///
+/// let bars = bars(&ticks, PriceField::Mid, 60_000).unwrap();
+/// assert_eq!(bars.len(), 2);
+/// assert_eq!((bars[0].open, bars[0].high, bars[0].low, bars[0].close), (10.0, 10.6, 9.8, 10.2));
+/// ```
+pub fn bars(ticks: &[Tick], field: PriceField, period_ms: i64) -> Result<Vec<Bar>, StatsError> {
+    if period_ms <= 0 {
+        return Err(StatsError::InvalidWindow);
+    }
+    let mut bars: Vec<Bar> = Vec::new();
+    let mut last_ts: Option<Timestamp> = None;
+
+    for tick in ticks {
+        let ts = tick.timestamp.ok_or(StatsError::MissingTimestamps)?;
+        if last_ts.is_some_and(|last| ts < last) {
+            return Err(StatsError::UnsortedTimestamps);
+        }
+        last_ts = Some(ts);
+
+        let Some(price) = field.of(tick) else { continue };
+        let start = ts.div_euclid(period_ms) * period_ms;
+        match bars.last_mut() {
+            Some(bar) if bar.timestamp == start => {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+            }
+            _ => bars.push(Bar {
+                timestamp: start,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+            }),
+        }
+    }
+    Ok(bars)
+}
+
+/// Trading days per year and trading hours per day, to annualize variances.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradingCalendar {
+    pub days_per_year: f64,
+    /// Length of a trading session; intraday bars count `session_hours / bar length` per day
+    pub session_hours: f64,
+}
+
+impl Default for TradingCalendar {
+    /// 252 days of 6.5 hours (US equities).
+    fn default() -> TradingCalendar {
+        TradingCalendar::new(252.0, 6.5)
+    }
+}
+
+impl TradingCalendar {
+    pub fn new(days_per_year: f64, session_hours: f64) -> TradingCalendar {
+        TradingCalendar {
+            days_per_year,
+            session_hours,
+        }
+    }
+
+    /// Number of `period_ms` periods in a trading year.
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::volatility::TradingCalendar;
+    ///
+    /// let calendar = TradingCalendar::new(252.0, 6.5);
+    /// assert_eq!(calendar.periods_per_year(86_400_000), 252.0);
+    /// assert_eq!(calendar.periods_per_year(300_000), 252.0 * 78.0); // 5-minute bars
+    /// ```
+    pub fn periods_per_year(&self, period_ms: i64) -> f64 {
+        let period = period_ms as f64;
+        if period >= MS_PER_DAY {
+            self.days_per_year * MS_PER_DAY / period
+        } else {
+            self.days_per_year * (self.session_hours * MS_PER_HOUR / period).max(1.0)
+        }
+    }
+
+    /// Annualized volatility of a variance per `period_ms` period.
+    pub fn annualize(&self, variance: f64, period_ms: i64) -> f64 {
+        (variance * self.periods_per_year(period_ms)).sqrt()
+    }
+}
+
+impl FromStr for TradingCalendar {
+    type Err = String;
+
+    /// `us` (252 × 6.5h), `b3` (252 × 7h), `fx` (260 × 24h), `crypto` (365 × 24h), or
+    /// `DAYS:HOURS` (e.g. `252:8`).
+    fn from_str(s: &str) -> Result<TradingCalendar, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "us" | "nyse" | "equity" => Ok(TradingCalendar::new(252.0, 6.5)),
+            "b3" | "br" => Ok(TradingCalendar::new(252.0, 7.0)),
+            "fx" => Ok(TradingCalendar::new(260.0, 24.0)),
+            "crypto" | "24x7" => Ok(TradingCalendar::new(365.0, 24.0)),
+            other => other
+                .split_once(':')
+                .and_then(|(days, hours)| Some(TradingCalendar::new(days.parse().ok()?, hours.parse().ok()?)))
+                .filter(|c| c.days_per_year > 0.0 && c.session_hours > 0.0 && c.session_hours <= 24.0)
+                .ok_or_else(|| format!("Invalid calendar '{}' (expected us, b3, fx, crypto or DAYS:HOURS)", s)),
+        }
+    }
+}
+
+impl fmt::Display for TradingCalendar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} days x {}h", self.days_per_year, self.session_hours)
+    }
+}
+
+/// Range-based estimator of the variance of OHLC bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Estimator {
+    /// Sample variance of close-to-close log returns
+    CloseToClose,
+    /// High-low range (no drift, no opening jumps)
+    Parkinson,
+    /// Open, high, low and close (no drift)
+    GarmanKlass,
+    /// Drift-independent
+    RogersSatchell,
+    /// Drift-independent and robust to opening jumps (overnight gaps)
+    YangZhang,
+}
+
+impl Estimator {
+    pub const ALL: [Estimator; 5] = [
+        Estimator::CloseToClose,
+        Estimator::Parkinson,
+        Estimator::GarmanKlass,
+        Estimator::RogersSatchell,
+        Estimator::YangZhang,
+    ];
+
+    /// Variance per bar.
+    ///
+    /// # Errors
+    /// * `EmptyInput` - no bars
+    /// * `InsufficientData` - fewer than 3 bars for close-to-close and Yang–Zhang,
+    ///   which take a sample variance of returns between bars
+    /// * `NaN`, `NonPositivePrice` - invalid prices
+    ///
+    /// # Example
+    /// ```
+    /// use quant_marketstat_ws::volatility::{Bar, Estimator};
+    ///
+    /// let bar = |open: f64, high: f64, low: f64, close: f64| Bar { timestamp: 0, open, high, low, close };
+    /// let bars = [bar(100.0, 102.0, 99.0, 101.0), bar(101.0, 103.0, 100.0, 100.5), bar(100.5, 101.0, 98.0, 99.0)];
+    ///
+    /// let parkinson = Estimator::Parkinson.variance(&bars).unwrap();
+    /// let expected = bars.iter().map(|b| (b.high / b.low).ln().powi(2)).sum::<f64>() / (4.0 * 2f64.ln() * 3.0);
+    /// assert!((parkinson - expected).abs() < 1e-15);
+    /// ```
+    pub fn variance(&self, bars: &[Bar]) -> Result<f64, StatsError> {
+        check_bars(bars)?;
+
+        match self {
+            Estimator::CloseToClose => {
+                let returns: Vec<f64> = bars.windows(2).map(|w| (w[1].close / w[0].close).ln()).collect();
+                sample_variance(&returns)
+            }
+            Estimator::Parkinson => Ok(average(bars.iter().map(|b| (b.high / b.low).ln().powi(2))) / (4.0 * LN_2)),
+            Estimator::GarmanKlass => Ok(average(bars.iter().map(|b| {
+                0.5 * (b.high / b.low).ln().powi(2) - (2.0 * LN_2 - 1.0) * (b.close / b.open).ln().powi(2)
+            }))),
+            Estimator::RogersSatchell => Ok(average(bars.iter().map(rogers_satchell))),
+            Estimator::YangZhang => {
+                if bars.len() < 3 {
+                    return Err(StatsError::InsufficientData);
+                }
+                // Bars after the first, each with the close before its open
+                let bars_after_first = &bars[1..];
+                let m = bars_after_first.len() as f64;
+                let overnight: Vec<f64> = bars.windows(2).map(|w| (w[1].open / w[0].close).ln()).collect();
+                let open_to_close: Vec<f64> = bars_after_first.iter().map(|b| (b.close / b.open).ln()).collect();
+                let rs = average(bars_after_first.iter().map(rogers_satchell));
+
+                let k = 0.34 / (1.34 + (m + 1.0) / (m - 1.0));
+                Ok(sample_variance(&overnight)? + k * sample_variance(&open_to_close)? + (1.0 - k) * rs)
+            }
+        }
+    }
+}
+
+impl FromStr for Estimator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Estimator, String> {
+        match s.trim().to_ascii_lowercase().replace(['_', ' '], "-").as_str() {
+            "close-to-close" | "cc" | "close" => Ok(Estimator::CloseToClose),
+            "parkinson" => Ok(Estimator::Parkinson),
+            "garman-klass" | "gk" => Ok(Estimator::GarmanKlass),
+            "rogers-satchell" | "rs" => Ok(Estimator::RogersSatchell),
+            "yang-zhang" | "yz" => Ok(Estimator::YangZhang),
+            other => Err(format!(
+                "Unknown estimator '{}' (expected close-to-close, parkinson, garman-klass, rogers-satchell or yang-zhang)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Estimator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Estimator::CloseToClose => "close-to-close",
+            Estimator::Parkinson => "parkinson",
+            Estimator::GarmanKlass => "garman-klass",
+            Estimator::RogersSatchell => "rogers-satchell",
+            Estimator::YangZhang => "yang-zhang",
+        })
+    }
+}
+
+/// Mean of a non-empty sequence.
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0usize), |(sum, n), v| (sum + v, n + 1));
+    sum / n as f64
+}
+
+/// Rogers–Satchell term of one bar.
+fn rogers_satchell(bar: &Bar) -> f64 {
+    (bar.high / bar.close).ln() * (bar.high / bar.open).ln() + (bar.low / bar.close).ln() * (bar.low / bar.open).ln()
+}
+
+/// Sample variance (n - 1) of log returns.
+fn sample_variance(values: &[f64]) -> Result<f64, StatsError> {
+    if values.len() < 2 {
+        return Err(StatsError::InsufficientData);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    Ok(values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0))
+}
+
+/// Checks OHLC bars: not empty, no NaN, positive prices.
+fn check_bars(bars: &[Bar]) -> Result<(), StatsError> {
+    if bars.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    let prices = || bars.iter().flat_map(|b| [b.open, b.high, b.low, b.close]);
+    if prices().any(|p| p.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    if prices().any(|p| p <= 0.0) {
+        return Err(StatsError::NonPositivePrice);
+    }
+    Ok(())
+}
+
+/// Checks a return series: not empty and free of NaN.
+fn check_returns(returns: &[f64]) -> Result<(), StatsError> {
+    if returns.is_empty() {
+        return Err(StatsError::EmptyInput);
+    }
+    if returns.iter().any(|r| r.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    Ok(())
+}
+
+/// Realized variance: the sum of squared (log) returns over the sample.
+///
+/// # Formula
+/// $$
+/// RV = \sum_{t} r_t^2
+/// $$
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::volatility::realized_variance;
+///
+/// assert!((realized_variance(&[0.01, -0.02, 0.01]).unwrap() - 0.0006).abs() < 1e-15);
+/// ```
+pub fn realized_variance(returns: &[f64]) -> Result<f64, StatsError> {
+    check_returns(returns)?;
+    Ok(returns.iter().map(|r| r * r).sum())
+}
+
+/// Bipower variation: the continuous part of the realized variance, robust to jumps.
+///
+/// # Formula
+/// $$
+/// BV = \frac{\pi}{2} \sum_{t \ge 2} |r_t| \, |r_{t-1}|
+/// $$
+///
+/// # Example
+/// ```
+/// use quant_marketstat_ws::volatility::{bipower_variation, realized_variance};
+///
+/// // A jump inflates the realized variance much more than the bipower variation
+/// let returns = [0.001, -0.001, 0.05, 0.001, -0.001];
+/// assert!(realized_variance(&returns).unwrap() > 10.0 * bipower_variation(&returns).unwrap());
+/// ```
+pub fn bipower_variation(returns: &[f64]) -> Result<f64, StatsError> {
+    check_returns(returns)?;
+    if returns.len() < 2 {
+        return Err(StatsError::InsufficientData);
+    }
+    Ok(PI / 2.0 * returns.windows(2).map(|w| w[0].abs() * w[1].abs()).sum::<f64>())
+}
+
+/// Volatility from one estimator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolatilityEstimate {
+    /// Estimator name
+    pub estimator: String,
+    /// Variance per bar (per return for realized variance and bipower variation)
+    pub variance: f64,
+    /// Volatility per bar: `sqrt(variance)`
+    pub volatility: f64,
+    /// Volatility scaled to a trading year
+    pub annualized: f64,
+}
+
+impl VolatilityEstimate {
+    fn new(estimator: String, variance: f64, period_ms: i64, calendar: &TradingCalendar) -> VolatilityEstimate {
+        VolatilityEstimate {
+            estimator,
+            variance,
+            volatility: variance.max(0.0).sqrt(),
+            annualized: calendar.annualize(variance.max(0.0), period_ms),
+        }
+    }
+}
+
+/// Every estimator on `period_ms` bars of the `field` prices of `ticks`: the five
+/// range-based ones on the bars, and realized variance and bipower variation on the
+/// log returns between bar closes (both reported per return). Estimators that need
+/// more bars than available are left out.
+///
+/// # Errors
+/// * the errors of [`bars`]
+/// * `InsufficientData` - fewer than two bars
+pub fn estimate(
+    ticks: &[Tick],
+    field: PriceField,
+    period_ms: i64,
+    calendar: &TradingCalendar,
+) -> Result<Vec<VolatilityEstimate>, StatsError> {
+    let bars = bars(ticks, field, period_ms)?;
+    check_bars(&bars)?;
+    if bars.len() < 2 {
+        return Err(StatsError::InsufficientData);
+    }
+
+    let mut estimates = Vec::new();
+    for estimator in Estimator::ALL {
+        match estimator.variance(&bars) {
+            Ok(variance) => estimates.push(VolatilityEstimate::new(estimator.to_string(), variance, period_ms, calendar)),
+            Err(StatsError::InsufficientData) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let returns: Vec<f64> = bars.windows(2).map(|w| (w[1].close / w[0].close).ln()).collect();
+    let n = returns.len() as f64;
+    let rv = realized_variance(&returns)?;
+    estimates.push(VolatilityEstimate::new("realized-variance".to_string(), rv / n, period_ms, calendar));
+    if let Ok(bv) = bipower_variation(&returns) {
+        // Per return, over the n - 1 adjacent pairs
+        estimates.push(VolatilityEstimate::new("bipower-variation".to_string(), bv / (n - 1.0), period_ms, calendar));
+    }
+    Ok(estimates)
+}
@@ -0,0 +1,172 @@
+//! Runs the command line binary and checks its exit codes and output:
+//! `0` success, `1` invalid data or I/O error, `2` usage error.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_quant_marketstat_ws"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("failed to run the binary")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Writes `contents` to a file of the test scratch directory.
+fn scratch_file(name: &str, contents: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, contents).expect("failed to write scratch file");
+    path.to_string_lossy().into_owned()
+}
+
+// SUCCESS ----------------------------------------------------------------------------
+#[test]
+fn mean_of_inline_values() {
+    let output = run(&["mean", "--values", "1,2,3"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "Mean: 2.0000\n");
+}
+
+#[test]
+fn vwap_of_inline_prices() {
+    let output = run(&["vwap", "--prices", "10,11", "--volumes", "1,3"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("VWAP: 10.7500\n"));
+}
+
+#[test]
+fn summary_of_file_as_json() {
+    let output = run(&["summary", "--file", "data.csv", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let summary: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(summary["rows"], 4);
+    assert_eq!(summary["bid"]["mean"], 10.6);
+}
+
+#[test]
+fn mean_of_returns_defaults_to_mid() {
+    let file = scratch_file("returns.csv", "bid,ask,volume\n10,12,1\n11,13,1\n");
+    let output = run(&["mean", "--file", &file, "--returns", "simple"]);
+    assert_eq!(output.status.code(), Some(0));
+    // mid 11 -> 12, not bid 10 -> 11
+    assert_eq!(stdout(&output), "Mean: 0.0909\n");
+}
+
+#[test]
+fn mean_of_the_last_column() {
+    let file = scratch_file("last.csv", "bid,ask,last,volume\n10,12,11,1\n11,13,13,1\n");
+    let output = run(&["mean", "--file", &file, "--column", "last"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "Mean: 12.0000\n");
+}
+
+#[test]
+fn pnl_shows_the_side() {
+    let file = scratch_file(
+        "positions.csv",
+        "asset_id,buy_price,sell_price,contracted_volume,market_price,side\n\
+         PETR4,37.10,38.00,1000,37.90,long\n\
+         VALE3,61.0,60.0,100,60.5,short\n",
+    );
+    let output = run(&["pnl", "--positions", &file]);
+    assert_eq!(output.status.code(), Some(0));
+    let text = stdout(&output);
+    assert!(text.contains("--- P&L Result for PETR4 (long) ---"));
+    assert!(text.contains("--- P&L Result for VALE3 (short) ---"));
+    assert!(text.contains("Total P&L     : 900.00"));
+}
+
+#[test]
+fn ledger_reads_semicolon_comma_exports() {
+    let file = scratch_file(
+        "fills.csv",
+        "asset_id;side;quantity;price;fees\nPETR4;buy;1.000;37,10;1,50\nPETR4;sell;400;37,40;1,00\n",
+    );
+    let output = run(&["ledger", "--fills", &file, "--delimiter", ";", "--decimal", "comma", "--output", "csv"]);
+    assert_eq!(output.status.code(), Some(0));
+    // 400 × 0.30 less 0.60 of the buy fee and the 1.00 sell fee
+    let text = stdout(&output);
+    let row: Vec<&str> = text.lines().nth(1).unwrap().split(',').collect();
+    assert_eq!(row[0], "PETR4");
+    assert!((row[1].parse::<f64>().unwrap() - 118.4).abs() < 1e-9);
+    assert_eq!((row[2], row[3]), ("600", "37.1"));
+}
+
+#[test]
+fn help_exits_cleanly() {
+    let output = run(&["help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("Usage: quant_marketstat_ws"));
+}
+//-------------------------------------------------------------------------------------
+
+// DATA ERRORS ------------------------------------------------------------------------
+#[test]
+fn missing_file_is_a_data_error() {
+    let output = run(&["vwap", "--file", "does_not_exist.csv"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Failed to open file."));
+    assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn missing_last_price_is_a_data_error() {
+    let output = run(&["vwap", "--file", "data.csv", "--column", "last"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Some rows have no last price"));
+}
+
+#[test]
+fn zero_volume_is_a_data_error() {
+    let output = run(&["vwap", "--prices", "10,11", "--volumes", "0,0"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Total volume is zero"));
+}
+//-------------------------------------------------------------------------------------
+
+// USAGE ERRORS -----------------------------------------------------------------------
+#[test]
+fn unknown_command_is_a_usage_error() {
+    let output = run(&["frobnicate"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Unknown command 'frobnicate'"));
+}
+
+#[test]
+fn invalid_list_is_a_usage_error() {
+    let output = run(&["mean", "--values", "1,x"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Invalid number 'x' in --values"));
+}
+
+#[test]
+fn unknown_option_is_a_usage_error() {
+    let output = run(&["mean", "--values", "1,2,3", "--colum", "ask"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Unknown option --colum"));
+    assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn flag_with_a_value_is_a_usage_error() {
+    let output = run(&["mean", "--values", "1,2,3", "--lenient=yes"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Option --lenient does not take a value"));
+}
+
+#[test]
+fn returns_without_file_is_a_usage_error() {
+    let output = run(&["mean", "--values", "1,2,3", "--returns", "log"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+}
+//-------------------------------------------------------------------------------------
@@ -0,0 +1,121 @@
+//! Stream sessions against an in-process WebSocket server on a free local port.
+
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+use quant_marketstat_ws::rolling::Window;
+use quant_marketstat_ws::stream::{run, StreamConfig, StreamStats};
+use serde_json::json;
+use tungstenite::Message;
+
+/// Server that accepts one connection, forwards the first client message (when
+/// `subscription` is set) and sends `messages` before closing.
+struct MockServer {
+    url: String,
+    received: Receiver<String>,
+    handle: JoinHandle<()>,
+}
+
+fn serve(messages: Vec<String>, subscription: bool) -> MockServer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (sender, received) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        if subscription {
+            if let Ok(Message::Text(text)) = socket.read() {
+                sender.send(text).unwrap();
+            }
+        }
+        for message in messages {
+            // The client may hang up early (max_ticks)
+            if socket.send(Message::Text(message)).is_err() {
+                return;
+            }
+        }
+        let _ = socket.close(None);
+        while socket.read().is_ok() {}
+    });
+
+    MockServer { url, received, handle }
+}
+
+fn tick(symbol: &str, bid: f64, volume: f64) -> String {
+    json!({ "symbol": symbol, "bid": bid, "ask": bid + 0.02, "volume": volume }).to_string()
+}
+
+/// Runs a session and collects (rows, counters) of every report.
+fn session(config: &StreamConfig) -> (StreamStats, Vec<(usize, StreamStats)>) {
+    let mut reports = Vec::new();
+    let stats = run(config, |summary, counters| reports.push((summary.rows, *counters))).unwrap();
+    (stats, reports)
+}
+
+#[test]
+fn counts_ticks_and_skipped_messages() {
+    let mut messages = vec![json!({ "event": "subscribed" }).to_string()];
+    messages.extend((0..5).map(|i| tick("PETR4", 37.0 + i as f64 * 0.1, 100.0)));
+    messages.push("not json".to_string());
+    messages.push(json!({ "bid": "inf", "ask": 37.2, "volume": 100 }).to_string());
+    let server = serve(messages, false);
+
+    let mut config = StreamConfig::new(&server.url);
+    config.report_every = Window::Count(2);
+    let (stats, reports) = session(&config);
+    server.handle.join().unwrap();
+
+    assert_eq!(stats, StreamStats { messages: 8, ticks: 5, skipped: 3 });
+    // Every 2 ticks, then once more for the fifth at the end
+    let rows: Vec<usize> = reports.iter().map(|(rows, _)| *rows).collect();
+    assert_eq!(rows, vec![2, 4, 5]);
+    assert_eq!(reports[0].1, StreamStats { messages: 3, ticks: 2, skipped: 1 });
+}
+
+#[test]
+fn keeps_only_the_requested_symbol() {
+    let messages = vec![
+        tick("PETR4", 37.0, 100.0),
+        tick("VALE3", 61.0, 200.0),
+        tick("PETR4", 37.1, 300.0),
+        tick("VALE3", 61.2, 100.0),
+    ];
+    let server = serve(messages, true);
+
+    let mut config = StreamConfig::new(&server.url);
+    config.symbol = Some("PETR4".to_string());
+    config.subscribe = Some(r#"{"op": "subscribe", "args": ["PETR4"]}"#.to_string());
+    let (stats, reports) = session(&config);
+    server.handle.join().unwrap();
+
+    assert_eq!(server.received.recv().unwrap(), r#"{"op": "subscribe", "args": ["PETR4"]}"#);
+    assert_eq!(stats, StreamStats { messages: 4, ticks: 2, skipped: 2 });
+    // Below the default 100-tick interval: a single report at the end
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0, 2);
+}
+
+#[test]
+fn stops_at_max_ticks() {
+    let messages = (0..10).map(|i| tick("PETR4", 37.0 + i as f64 * 0.1, 100.0)).collect();
+    let server = serve(messages, false);
+
+    let mut config = StreamConfig::new(&server.url);
+    config.max_ticks = Some(3);
+    config.report_every = Window::Time(60_000);
+    let (stats, reports) = session(&config);
+    server.handle.join().unwrap();
+
+    assert_eq!(stats.ticks, 3);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0, 3);
+}
+
+#[test]
+fn rejects_a_zero_report_interval() {
+    let mut config = StreamConfig::new("ws://127.0.0.1:9");
+    config.report_every = Window::Count(0);
+    assert!(run(&config, |_, _| {}).is_err());
+}